@@ -0,0 +1,143 @@
+use super::*;
+
+/// Struct holding the settings of the metrics refreshed on every applied message
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnalyticsConfig {
+    /// Number of levels per side used for the volume imbalance
+    pub imbalance_levels: usize,
+
+    /// Distance from the mid price, in basis points, used for the liquidity band
+    pub liquidity_band_bps: f64,
+}
+
+impl Default for AnalyticsConfig {
+    fn default() -> Self {
+        Self {
+            imbalance_levels: 5,
+            liquidity_band_bps: 10.0,
+        }
+    }
+}
+
+/// Struct representing the resting liquidity of both sides within a price band
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Liquidity {
+    /// Bid quantity within the band
+    pub bid_qty: Qty,
+
+    /// Ask quantity within the band
+    pub ask_qty: Qty,
+
+    /// Bid notional (price * quantity) within the band
+    pub bid_notional: Decimal,
+
+    /// Ask notional (price * quantity) within the band
+    pub ask_notional: Decimal,
+}
+
+/// Struct holding the microstructure metrics of an order book at one point in time
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BookMetrics {
+    /// Mid price between the best bid and the best ask
+    pub mid: Price,
+
+    /// Absolute spread (best ask - best bid)
+    pub spread: Price,
+
+    /// Spread relative to the mid price in basis points
+    pub spread_bps: f64,
+
+    /// Size-weighted mid price, leaning towards the side with less quantity at the top
+    pub microprice: Price,
+
+    /// Volume imbalance of the top levels in [-1, 1], positive when bids outweigh asks
+    pub imbalance: Option<f64>,
+
+    /// Liquidity within `liquidity_band_bps` of the mid price
+    pub liquidity: Liquidity,
+}
+
+impl BookMetrics {
+    /// Function to compute the metrics of an order book, if both sides have a visible level
+    pub fn compute(orderbook: &OrderBook, config: &AnalyticsConfig) -> Option<Self> {
+        Some(Self {
+            mid: orderbook.mid_price()?,
+            spread: orderbook.spread()?,
+            spread_bps: orderbook.spread_bps()?,
+            microprice: orderbook.microprice()?,
+            imbalance: orderbook.imbalance(config.imbalance_levels),
+            liquidity: orderbook.liquidity_within_bps(config.liquidity_band_bps),
+        })
+    }
+}
+
+impl OrderBook {
+    /// Function to get the absolute spread between the best ask and the best bid
+    pub fn spread(&self) -> Option<Price> {
+        self.get_best_bid_ask()
+            .map(|((bid, _), (ask, _))| ask - bid)
+    }
+
+    /// Function to get the spread relative to the mid price in basis points
+    pub fn spread_bps(&self) -> Option<f64> {
+        let mid = self.mid_price()?;
+        if !mid.is_positive() {
+            return None;
+        }
+        Some(self.spread()?.to_f64() / mid.to_f64() * 10_000.0)
+    }
+
+    /// Function to get the size-weighted microprice of the best levels
+    /// (bid * ask_qty + ask * bid_qty) / (bid_qty + ask_qty)
+    pub fn microprice(&self) -> Option<Price> {
+        let ((bid, bid_qty), (ask, ask_qty)) = self.get_best_bid_ask()?;
+        let total_qty = bid_qty + ask_qty;
+        if !total_qty.is_positive() {
+            return None;
+        }
+        Some((bid * ask_qty + ask * bid_qty) / total_qty)
+    }
+
+    /// Function to get the volume imbalance of the top `levels` per side
+    /// (bid_qty - ask_qty) / (bid_qty + ask_qty), None if both sides are empty
+    pub fn imbalance(&self, levels: usize) -> Option<f64> {
+        let bid_qty: Qty = self
+            .visible_bids()
+            .rev()
+            .take(levels)
+            .map(|(_, &q)| q)
+            .sum();
+        let ask_qty: Qty = self.visible_asks().take(levels).map(|(_, &q)| q).sum();
+        let total_qty = bid_qty + ask_qty;
+        if !total_qty.is_positive() {
+            return None;
+        }
+        Some((bid_qty - ask_qty).to_f64() / total_qty.to_f64())
+    }
+
+    /// Function to get the visible liquidity within `bps` basis points of the mid price
+    pub fn liquidity_within_bps(&self, bps: f64) -> Liquidity {
+        let Some(mid) = self.mid_price() else {
+            return Liquidity::default();
+        };
+        let lowest_bid = Price::from_f64(mid.to_f64() * (1.0 - bps / 10_000.0));
+        let highest_ask = Price::from_f64(mid.to_f64() * (1.0 + bps / 10_000.0));
+
+        let mut liquidity = Liquidity::default();
+        for (&price, &qty) in self.visible_bids().rev() {
+            if price < lowest_bid {
+                break;
+            }
+            liquidity.bid_qty += qty;
+            liquidity.bid_notional += price * qty;
+        }
+        for (&price, &qty) in self.visible_asks() {
+            if price > highest_ask {
+                break;
+            }
+            liquidity.ask_qty += qty;
+            liquidity.ask_notional += price * qty;
+        }
+        liquidity
+    }
+}
@@ -0,0 +1,413 @@
+use super::*;
+
+// Number of levels per side served by `/book` without a `depth` parameter
+const DEFAULT_DEPTH: usize = 10;
+
+// Largest number of levels per side served by `/book`
+const MAX_DEPTH: usize = 5000;
+
+// Number of recent trades (or liquidations) served by `/trades` (`/liquidations`) without a `limit` parameter
+const DEFAULT_TRADES: usize = 20;
+
+// Number of closed candles served by `/candles` without a `limit` parameter
+const DEFAULT_CANDLES: usize = 60;
+
+// Largest base quantity or quote notional accepted by `/impact`, which keeps the estimate's arithmetic in range
+const MAX_ORDER_SIZE: i64 = 1_000_000_000_000;
+
+/// Function to build the router serving the books of the manager as JSON
+/// Handlers copy what they need while holding the read lock; responses are serialized and sent after it is released.
+///
+/// - `GET /health`: 200 if every book is in sync (diff books only), not stale and has both sides, 503 otherwise
+/// - `GET /metrics`: the [`FeedMetrics`] of the process in the Prometheus text format
+/// - `GET /symbols`: the managed symbols
+/// - `GET /book/{symbol}?depth=N`: the top N levels per side, sync state and metrics
+/// - `GET /bbo/{symbol}`: the best bid and ask
+/// - `GET /consolidated/{symbol}?depth=N`: the top N levels per side of a consolidated book with the quantity
+///   of every source, the best bid and ask across the sources and the sources crossing each other
+/// - `GET /trades/{symbol}?limit=N`: the rolling trade statistics and the N most recent trades
+/// - `GET /candles/{symbol}?limit=N`: the candle in progress and the N most recent closed candles
+/// - `GET /liquidations/{symbol}?limit=N`: the N most recent liquidations of a futures book
+/// - `GET /volume/{symbol}?price=P`: the quantity resting at a price
+/// - `GET /impact/{symbol}?side=buy|sell&qty=Q`: the estimated execution of a market order (`Q` with a `q` suffix is a quote notional)
+pub fn api_router(manager: Arc<RwLock<BookManager>>) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/metrics", get(metrics))
+        .route("/symbols", get(symbols))
+        .route("/book/:symbol", get(book))
+        .route("/bbo/:symbol", get(bbo))
+        .route("/consolidated/:symbol", get(consolidated))
+        .route("/trades/:symbol", get(trades))
+        .route("/candles/:symbol", get(candles))
+        .route("/liquidations/:symbol", get(liquidations))
+        .route("/volume/:symbol", get(volume))
+        .route("/impact/:symbol", get(impact))
+        .with_state(manager)
+}
+
+/// Function to serve the API on the listener until the task is dropped or aborted
+pub async fn serve_api(
+    listener: TcpListener,             // The bound listener (e.g., 127.0.0.1:8080)
+    manager: Arc<RwLock<BookManager>>, // A shared, thread-safe reference to the book manager
+) -> Result<(), OrderBookError> {
+    axum::serve(listener, api_router(manager)).await?;
+    Ok(())
+}
+
+/// Struct representing an error response: the status code is derived from the error variant
+struct ApiError(OrderBookError);
+
+impl From<OrderBookError> for ApiError {
+    fn from(error: OrderBookError) -> Self {
+        ApiError(error)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match self.0 {
+            OrderBookError::UnknownSymbol(_) => StatusCode::NOT_FOUND,
+            OrderBookError::ParseError(_) => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        let body = serde_json::json!({ "error": self.0.to_string() });
+        (status, Json(body)).into_response()
+    }
+}
+
+/// Struct representing the `depth` query parameter of `/book` and `/consolidated`
+#[derive(Deserialize)]
+struct DepthQuery {
+    // Number of levels per side
+    depth: Option<usize>,
+}
+
+/// Struct representing the `limit` query parameter of `/trades`, `/candles` and `/liquidations`
+#[derive(Deserialize)]
+struct LimitQuery {
+    // Number of recent trades, closed candles or liquidations
+    limit: Option<usize>,
+}
+
+/// Struct representing the `price` query parameter of `/volume`
+#[derive(Deserialize)]
+struct VolumeQuery {
+    // Price level to look up
+    price: String,
+}
+
+/// Struct representing the query parameters of `/impact`
+#[derive(Deserialize)]
+struct ImpactQuery {
+    // `buy` or `sell`
+    side: String,
+
+    // Base quantity, or quote notional with a `q` suffix
+    qty: String,
+}
+
+/// Struct representing the response of `/bbo`
+#[derive(Serialize)]
+struct BestBidAsk {
+    // Symbol of the book
+    symbol: String,
+
+    // Whether the book may be outdated after a stream interruption
+    stale: bool,
+
+    // Best bid, None while either side is empty
+    bid: Option<PriceLevel>,
+
+    // Best ask, None while either side is empty
+    ask: Option<PriceLevel>,
+}
+
+/// Struct representing the response of `/trades`
+#[derive(Serialize)]
+struct RecentTrades {
+    // Symbol of the book
+    symbol: String,
+
+    // Statistics of every rolling window
+    windows: Vec<TradeStats>,
+
+    // Most recent trades, newest first
+    trades: Vec<Trade>,
+}
+
+/// Struct representing the response of `/candles`
+#[derive(Serialize)]
+struct RecentCandles {
+    // Symbol of the book
+    symbol: String,
+
+    // Length of the candles (e.g., 1m)
+    interval: KlineInterval,
+
+    // What the candles are built from
+    source: CandleSource,
+
+    // The candle in progress, None before the first sample
+    current: Option<Candle>,
+
+    // Most recent closed candles, newest first
+    closed: Vec<Candle>,
+}
+
+/// Struct representing the response of `/liquidations`
+#[derive(Serialize)]
+struct RecentLiquidations {
+    // Symbol of the book
+    symbol: String,
+
+    // Mark price and funding, None for spot books
+    mark_price: Option<MarkPrice>,
+
+    // Most recent liquidations, newest first
+    liquidations: Vec<Liquidation>,
+}
+
+/// Struct representing the response of `/volume`
+#[derive(Serialize)]
+struct VolumeAtPrice {
+    // Symbol of the book
+    symbol: String,
+
+    // The requested price
+    price: Price,
+
+    // Quantity resting at the price, zero if there is no level
+    qty: Qty,
+}
+
+/// Struct representing the response of `/health`
+#[derive(Serialize)]
+struct Health {
+    // `ok` or `degraded`
+    status: &'static str,
+
+    // Number of managed symbols
+    symbols: usize,
+
+    // Symbols whose book is stale or has an empty side
+    unhealthy: Vec<String>,
+}
+
+// Function to run `f` on the book of the symbol while holding the read lock
+async fn with_book<T>(
+    manager: &RwLock<BookManager>,
+    symbol: &str,
+    f: impl FnOnce(&OrderBook) -> T,
+) -> Result<T, ApiError> {
+    let symbol = symbol.to_uppercase();
+    let manager = manager.read().await;
+    let orderbook = manager
+        .book(&symbol)
+        .ok_or(OrderBookError::UnknownSymbol(symbol))?;
+    Ok(f(orderbook))
+}
+
+// Handler for `/health`
+async fn health(State(manager): State<Arc<RwLock<BookManager>>>) -> (StatusCode, Json<Health>) {
+    let (symbols, unhealthy) = {
+        let manager = manager.read().await;
+        let symbols = manager.symbols();
+        // Partial depth books have no snapshot to wait for; a diff book is only usable once synced
+        let diff = manager.depth_mode() == DepthMode::Diff;
+        let unhealthy: Vec<String> = symbols
+            .iter()
+            .filter(|symbol| {
+                manager.book(symbol).is_some_and(|orderbook| {
+                    orderbook.is_stale()
+                        || (diff && orderbook.sync_state() != SyncState::Synced)
+                        || orderbook.get_best_bid_ask().is_none()
+                })
+            })
+            .cloned()
+            .collect();
+        (symbols.len(), unhealthy)
+    };
+
+    let (code, status) = if unhealthy.is_empty() {
+        (StatusCode::OK, "ok")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "degraded")
+    };
+    let health = Health {
+        status,
+        symbols,
+        unhealthy,
+    };
+    (code, Json(health))
+}
+
+// Handler for `/metrics`
+async fn metrics(State(manager): State<Arc<RwLock<BookManager>>>) -> Result<Response, ApiError> {
+    let metrics = FeedMetrics::global();
+    metrics.observe_books(&*manager.read().await);
+    let body = metrics.encode()?;
+    Ok(([(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)], body).into_response())
+}
+
+// Handler for `/symbols`
+async fn symbols(State(manager): State<Arc<RwLock<BookManager>>>) -> Json<Vec<String>> {
+    let symbols = manager.read().await.symbols();
+    Json(symbols)
+}
+
+// Handler for `/book/{symbol}`
+async fn book(
+    State(manager): State<Arc<RwLock<BookManager>>>,
+    UrlPath(symbol): UrlPath<String>,
+    Query(query): Query<DepthQuery>,
+) -> Result<Json<BookView>, ApiError> {
+    let depth = query.depth.unwrap_or(DEFAULT_DEPTH).min(MAX_DEPTH);
+    let view = with_book(&manager, &symbol, |orderbook| orderbook.view(depth)).await?;
+    Ok(Json(view))
+}
+
+// Handler for `/bbo/{symbol}`
+async fn bbo(
+    State(manager): State<Arc<RwLock<BookManager>>>,
+    UrlPath(symbol): UrlPath<String>,
+) -> Result<Json<BestBidAsk>, ApiError> {
+    let (symbol, stale, best) = with_book(&manager, &symbol, |orderbook| {
+        (
+            orderbook.symbol().to_string(),
+            orderbook.is_stale(),
+            orderbook.get_best_bid_ask(),
+        )
+    })
+    .await?;
+
+    let (bid, ask) = match best {
+        Some(((bid, bid_qty), (ask, ask_qty))) => (
+            Some(PriceLevel {
+                price: bid,
+                qty: bid_qty,
+            }),
+            Some(PriceLevel {
+                price: ask,
+                qty: ask_qty,
+            }),
+        ),
+        None => (None, None),
+    };
+    Ok(Json(BestBidAsk {
+        symbol,
+        stale,
+        bid,
+        ask,
+    }))
+}
+
+// Handler for `/consolidated/{symbol}`
+async fn consolidated(
+    State(manager): State<Arc<RwLock<BookManager>>>,
+    UrlPath(symbol): UrlPath<String>,
+    Query(query): Query<DepthQuery>,
+) -> Result<Json<ConsolidatedView>, ApiError> {
+    let depth = query.depth.unwrap_or(DEFAULT_DEPTH).min(MAX_DEPTH);
+    let view = manager
+        .read()
+        .await
+        .consolidated(&symbol)
+        .map(|consolidated| consolidated.view(depth))
+        .ok_or_else(|| {
+            OrderBookError::UnknownSymbol(format!("{} is not a consolidated book", symbol))
+        })?;
+    Ok(Json(view))
+}
+
+// Handler for `/trades/{symbol}`
+async fn trades(
+    State(manager): State<Arc<RwLock<BookManager>>>,
+    UrlPath(symbol): UrlPath<String>,
+    Query(query): Query<LimitQuery>,
+) -> Result<Json<RecentTrades>, ApiError> {
+    let limit = query.limit.unwrap_or(DEFAULT_TRADES);
+    let trades = with_book(&manager, &symbol, |orderbook| RecentTrades {
+        symbol: orderbook.symbol().to_string(),
+        windows: orderbook.trades().window_stats(orderbook.clock()),
+        trades: orderbook.trades().recent().take(limit).copied().collect(),
+    })
+    .await?;
+    Ok(Json(trades))
+}
+
+// Handler for `/candles/{symbol}`
+async fn candles(
+    State(manager): State<Arc<RwLock<BookManager>>>,
+    UrlPath(symbol): UrlPath<String>,
+    Query(query): Query<LimitQuery>,
+) -> Result<Json<RecentCandles>, ApiError> {
+    let limit = query.limit.unwrap_or(DEFAULT_CANDLES);
+    let candles = with_book(&manager, &symbol, |orderbook| {
+        let candles = orderbook.candles();
+        RecentCandles {
+            symbol: orderbook.symbol().to_string(),
+            interval: candles.interval(),
+            source: candles.source(),
+            current: candles.current().copied(),
+            closed: candles.closed().take(limit).copied().collect(),
+        }
+    })
+    .await?;
+    Ok(Json(candles))
+}
+
+// Handler for `/liquidations/{symbol}`
+async fn liquidations(
+    State(manager): State<Arc<RwLock<BookManager>>>,
+    UrlPath(symbol): UrlPath<String>,
+    Query(query): Query<LimitQuery>,
+) -> Result<Json<RecentLiquidations>, ApiError> {
+    let limit = query.limit.unwrap_or(DEFAULT_TRADES);
+    let liquidations = with_book(&manager, &symbol, |orderbook| RecentLiquidations {
+        symbol: orderbook.symbol().to_string(),
+        mark_price: orderbook.mark_price().copied(),
+        liquidations: orderbook.liquidations().take(limit).copied().collect(),
+    })
+    .await?;
+    Ok(Json(liquidations))
+}
+
+// Handler for `/volume/{symbol}`
+async fn volume(
+    State(manager): State<Arc<RwLock<BookManager>>>,
+    UrlPath(symbol): UrlPath<String>,
+    Query(query): Query<VolumeQuery>,
+) -> Result<Json<VolumeAtPrice>, ApiError> {
+    let price: Price = query.price.parse()?;
+    let volume = with_book(&manager, &symbol, |orderbook| VolumeAtPrice {
+        symbol: orderbook.symbol().to_string(),
+        price,
+        qty: orderbook.get_volume_at_price(price),
+    })
+    .await?;
+    Ok(Json(volume))
+}
+
+// Handler for `/impact/{symbol}`
+async fn impact(
+    State(manager): State<Arc<RwLock<BookManager>>>,
+    UrlPath(symbol): UrlPath<String>,
+    Query(query): Query<ImpactQuery>,
+) -> Result<Json<ExecutionEstimate>, ApiError> {
+    let side: Side = query.side.parse()?;
+    let size: OrderSize = query.qty.parse()?;
+    let (OrderSize::Base(value) | OrderSize::Quote(value)) = size;
+    if value.abs() > Decimal::new(MAX_ORDER_SIZE, 0)? {
+        return Err(OrderBookError::ParseError(format!(
+            "order size {} exceeds {}",
+            value, MAX_ORDER_SIZE
+        ))
+        .into());
+    }
+    let estimate = with_book(&manager, &symbol, |orderbook| {
+        orderbook.simulate_market_order(side, size)
+    })
+    .await?;
+    Ok(Json(estimate))
+}
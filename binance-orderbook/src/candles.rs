@@ -0,0 +1,271 @@
+use super::*;
+
+// Number of closed candles kept per book
+const CANDLE_HISTORY: usize = 500;
+
+/// Struct representing one OHLCV candle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Candle {
+    /// Open time in milliseconds
+    pub open_time: u64,
+
+    /// Close time in milliseconds (the last millisecond of the interval)
+    pub close_time: u64,
+
+    /// Price of the first sample
+    pub open: Price,
+
+    /// Highest price
+    pub high: Price,
+
+    /// Lowest price
+    pub low: Price,
+
+    /// Price of the latest sample
+    pub close: Price,
+
+    /// Base volume (zero for mid price candles)
+    pub volume: Qty,
+
+    /// Number of trades (zero for mid price candles)
+    pub trade_count: u64,
+
+    /// Whether the interval has ended
+    pub closed: bool,
+
+    /// Whether every sample of the interval was seen; false if the stream was interrupted during it
+    pub complete: bool,
+}
+
+impl Candle {
+    /// Function to construct a Candle from a `kline` event reader (deserialized data)
+    pub fn from_kline(reader: KlineDataReader) -> Result<Self, OrderBookError> {
+        Ok(Self {
+            open_time: reader.open_time,
+            close_time: reader.close_time,
+            open: parse_decimal(&reader.open, "open")?,
+            high: parse_decimal(&reader.high, "high")?,
+            low: parse_decimal(&reader.low, "low")?,
+            close: parse_decimal(&reader.close, "close")?,
+            volume: parse_decimal(&reader.volume, "volume")?,
+            trade_count: reader.trade_count,
+            closed: reader.closed,
+            complete: true,
+        })
+    }
+
+    // Function to open a candle of `interval` at the open time with a single price
+    fn open_at(interval: KlineInterval, open_time: u64, price: Price) -> Self {
+        Self {
+            open_time,
+            close_time: open_time + interval.millis() - 1,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: Qty::ZERO,
+            trade_count: 0,
+            closed: false,
+            complete: true,
+        }
+    }
+
+    // Function to add a price sample with its volume and number of trades
+    fn add(&mut self, price: Price, volume: Qty, trades: u64) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        self.volume += volume;
+        self.trade_count += trades;
+    }
+}
+
+/// Struct holding the candles of a symbol: the closed ones and the one in progress
+/// Intervals are aligned to the exchange clock (trade or event times), not to the local clock, so a
+/// reconnect or a rollover never splits a candle. A candle in progress while the stream was interrupted
+/// is flagged incomplete, and no flat candles are invented for the intervals the interruption spans.
+#[derive(Debug, Clone)]
+pub struct CandleStore {
+    // Length of the candles
+    interval: KlineInterval,
+
+    // What the candles are built from
+    source: CandleSource,
+
+    // Closed candles, oldest first
+    closed: VecDeque<Candle>,
+
+    // The candle in progress
+    current: Option<Candle>,
+
+    // Whether the stream was interrupted since the last sample
+    interrupted: bool,
+}
+
+impl Default for CandleStore {
+    fn default() -> Self {
+        Self::new(KlineInterval::OneMinute, CandleSource::Mid)
+    }
+}
+
+impl CandleStore {
+    /// Constructor function to create an empty CandleStore
+    pub fn new(interval: KlineInterval, source: CandleSource) -> Self {
+        Self {
+            interval,
+            source,
+            closed: VecDeque::new(),
+            current: None,
+            interrupted: false,
+        }
+    }
+
+    /// Function to get the length of the candles
+    pub fn interval(&self) -> KlineInterval {
+        self.interval
+    }
+
+    /// Function to get what the candles are built from
+    pub fn source(&self) -> CandleSource {
+        self.source
+    }
+
+    /// Function to change the interval or source of the candles, dropping the candles built so far
+    pub fn configure(&mut self, interval: KlineInterval, source: CandleSource) {
+        if (interval, source) != (self.interval, self.source) {
+            *self = Self::new(interval, source);
+        }
+    }
+
+    /// Function to get the candle in progress
+    pub fn current(&self) -> Option<&Candle> {
+        self.current.as_ref()
+    }
+
+    /// Function to iterate over the closed candles, most recent first
+    pub fn closed(&self) -> impl Iterator<Item = &Candle> {
+        self.closed.iter().rev()
+    }
+
+    /// Function to flag the candle in progress incomplete after the stream was interrupted
+    pub fn mark_interrupted(&mut self) {
+        if let Some(candle) = &mut self.current {
+            candle.complete = false;
+        }
+        self.interrupted = true;
+    }
+
+    /// Function to add a candle of the kline stream; the exchange's candle replaces the local one
+    pub fn record_kline(&mut self, candle: Candle) -> Result<(), OrderBookError> {
+        if self.source != CandleSource::Kline {
+            return Err(OrderBookError::CandleMismatch(format!(
+                "candles are built from {:?}, not from klines",
+                self.source
+            )));
+        }
+        let length = candle.close_time.saturating_sub(candle.open_time) + 1;
+        if length != self.interval.millis()
+            || self.interval.open_time(candle.open_time) != candle.open_time
+        {
+            return Err(OrderBookError::CandleMismatch(format!(
+                "kline {}-{} is not a {} candle",
+                candle.open_time, candle.close_time, self.interval
+            )));
+        }
+        self.interrupted = false;
+
+        // A late update of a closed candle, e.g., resent after a reconnect
+        if let Some(closed) = self
+            .closed
+            .iter_mut()
+            .rev()
+            .find(|closed| closed.open_time == candle.open_time)
+        {
+            *closed = Candle {
+                closed: true,
+                ..candle
+            };
+            return Ok(());
+        }
+        match self.current.map(|current| current.open_time) {
+            // Older than every candle kept
+            Some(open_time) if candle.open_time < open_time => return Ok(()),
+            Some(open_time) if candle.open_time > open_time => self.close_current(),
+            _ => {}
+        }
+
+        if candle.closed {
+            self.current = None;
+            self.push_closed(candle);
+        } else {
+            self.current = Some(candle);
+        }
+        Ok(())
+    }
+
+    /// Function to add a trade to the candles built from trades
+    pub fn record_trade(&mut self, trade: &Trade) {
+        if self.source == CandleSource::Trades {
+            self.add_sample(trade.time, trade.price, trade.qty, 1);
+        }
+    }
+
+    /// Function to add a mid price sample taken at `time` (in milliseconds) to the candles built from mid prices
+    pub fn sample_mid(&mut self, time: u64, mid: Price) {
+        if self.source == CandleSource::Mid {
+            self.add_sample(time, mid, Qty::ZERO, 0);
+        }
+    }
+
+    // Function to add a price sample to the candle of its interval, closing the candles it passed
+    fn add_sample(&mut self, time: u64, price: Price, volume: Qty, trades: u64) {
+        let open_time = self.interval.open_time(time);
+        let interrupted = std::mem::take(&mut self.interrupted);
+
+        match &mut self.current {
+            Some(current) if current.open_time == open_time => {
+                current.add(price, volume, trades);
+                return;
+            }
+            // Samples of closed intervals are too late to be added
+            Some(current) if current.open_time > open_time => return,
+            _ => {}
+        }
+
+        // Without an interruption, intervals without samples really had no activity
+        let previous = self.current.or_else(|| self.closed.back().copied());
+        self.close_current();
+        if let Some(previous) = previous.filter(|_| !interrupted) {
+            let length = self.interval.millis();
+            let gap_start = (previous.open_time + length)
+                .max(open_time.saturating_sub(length * CANDLE_HISTORY as u64));
+            for flat_open_time in (gap_start..open_time).step_by(length as usize) {
+                let mut flat = Candle::open_at(self.interval, flat_open_time, previous.close);
+                flat.closed = true;
+                self.push_closed(flat);
+            }
+        }
+
+        let mut candle = Candle::open_at(self.interval, open_time, price);
+        candle.add(price, volume, trades);
+        // The interval may have started before the stream was back
+        candle.complete = !interrupted;
+        self.current = Some(candle);
+    }
+
+    // Function to move the candle in progress to the closed candles
+    fn close_current(&mut self) {
+        if let Some(mut candle) = self.current.take() {
+            candle.closed = true;
+            self.push_closed(candle);
+        }
+    }
+
+    // Function to append a closed candle and drop the oldest beyond the history
+    fn push_closed(&mut self, candle: Candle) {
+        self.closed.push_back(candle);
+        if self.closed.len() > CANDLE_HISTORY {
+            self.closed.pop_front();
+        }
+    }
+}
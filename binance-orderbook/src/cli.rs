@@ -0,0 +1,396 @@
+use super::*;
+
+/// Struct representing the command line of the binary
+#[derive(Debug, Parser)]
+#[command(version, about = "Binance order book client")]
+pub struct Cli {
+    /// Record every received raw frame to DIR (default: recordings)
+    #[arg(long, global = true, value_name = "DIR", num_args = 0..=1, default_missing_value = "recordings")]
+    pub record: Option<PathBuf>,
+
+    /// Gzip compress the recording files
+    #[arg(long, global = true)]
+    pub record_gzip: bool,
+
+    /// Servers publishing the live books to other processes
+    #[command(flatten)]
+    pub servers: ServerArgs,
+
+    /// Market, trade and candle streams of the live books
+    #[command(flatten)]
+    pub feed: FeedArgs,
+
+    /// The subcommand to run, the terminal UI if omitted
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl Cli {
+    /// Function to check whether the command runs the full-screen terminal UI
+    pub fn is_interactive(&self) -> bool {
+        matches!(
+            self.command,
+            None | Some(Command::Tui { .. }) | Some(Command::Replay { tui: true, .. })
+        )
+    }
+}
+
+/// Struct representing the servers publishing the live books to other processes
+#[derive(Debug, Clone, Copy, Args)]
+pub struct ServerArgs {
+    /// Serve the live books as JSON over HTTP on ADDR (e.g., 127.0.0.1:8080)
+    #[arg(long, global = true, value_name = "ADDR")]
+    pub http: Option<SocketAddr>,
+
+    /// Push normalized book updates to WebSocket subscribers on ADDR (e.g., 127.0.0.1:8081)
+    #[arg(long, global = true, value_name = "ADDR")]
+    pub ws: Option<SocketAddr>,
+}
+
+/// Struct representing the market, the trade stream, the rolling trade statistics and the candles of the live books
+#[derive(Debug, Clone, Args)]
+pub struct FeedArgs {
+    /// Market of the symbols
+    #[arg(long, global = true, value_enum, default_value_t = MarketArg::Spot)]
+    pub market: MarketArg,
+
+    /// Subscribe to a trade stream for every symbol
+    #[arg(long, global = true, value_enum)]
+    pub trades: Option<TradeStreamArg>,
+
+    /// Comma separated rolling windows of the trade statistics in seconds
+    #[arg(
+        long,
+        global = true,
+        value_delimiter = ',',
+        default_value = "60,300,900"
+    )]
+    pub trade_windows: Vec<u64>,
+
+    /// What the candles of every book are built from
+    #[arg(long, global = true, value_enum, default_value_t = CandleSourceArg::Mid)]
+    pub candles: CandleSourceArg,
+
+    /// Interval of the candles (e.g., 1s, 1m, 15m, 1h, 1d, 1w)
+    #[arg(long, global = true, default_value = "1m")]
+    pub candle_interval: KlineInterval,
+}
+
+impl FeedArgs {
+    /// Function to get the market of the symbols
+    pub fn market(&self) -> Market {
+        self.market.into()
+    }
+
+    /// Function to get the rolling windows of the trade statistics
+    pub fn windows(&self) -> Vec<Duration> {
+        self.trade_windows
+            .iter()
+            .map(|&secs| Duration::from_secs(secs))
+            .collect()
+    }
+
+    /// Function to get the trade stream to subscribe to; candles built from trades need one
+    pub fn trade_stream(&self) -> Option<TradeStream> {
+        match (self.trades, self.candles) {
+            (Some(stream), _) => Some(stream.into()),
+            (None, CandleSourceArg::Trades) => Some(TradeStream::Trade),
+            (None, _) => None,
+        }
+    }
+
+    /// Function to apply the market, trade stream, windows and candles to a book manager
+    pub fn configure(&self, manager: &mut BookManager) -> Result<(), OrderBookError> {
+        manager.set_market(self.market())?;
+        manager.set_trade_windows(self.windows())?;
+        manager.set_trade_stream(self.trade_stream())?;
+        manager.set_candles(self.candle_interval, self.candles.into())
+    }
+
+    /// Function to apply the windows and candles to a single book
+    pub fn configure_book(&self, orderbook: &mut OrderBook) -> Result<(), OrderBookError> {
+        orderbook.trades_mut().set_windows(self.windows())?;
+        orderbook
+            .candles_mut()
+            .configure(self.candle_interval, self.candles.into());
+        Ok(())
+    }
+}
+
+/// Enum representing the subcommands of the binary
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Full-screen terminal UI on live books (the default); prompts for anything not given as a flag
+    #[command(visible_alias = "menu")]
+    Tui {
+        /// Comma separated symbols (e.g., bnbusdt,btcusdt)
+        #[arg(long, value_delimiter = ',')]
+        symbols: Vec<String>,
+
+        /// How the depth of the books is maintained
+        #[arg(long, value_enum)]
+        depth_mode: Option<DepthModeArg>,
+    },
+
+    /// Live book display, refreshed until interrupted with Ctrl+C
+    Watch {
+        /// Symbol to watch (e.g., bnbusdt)
+        symbol: String,
+
+        /// Number of levels per side
+        #[arg(long, default_value_t = 10)]
+        depth: usize,
+
+        /// How the depth of the book is maintained
+        #[arg(long, value_enum, default_value_t = DepthModeArg::Diff)]
+        depth_mode: DepthModeArg,
+
+        /// Refresh interval in milliseconds
+        #[arg(long, default_value_t = 1000)]
+        interval_ms: u64,
+    },
+
+    /// Serve the live books over HTTP (and WebSocket with `--ws`) without a UI until interrupted with Ctrl+C
+    Serve {
+        /// Comma separated symbols (e.g., bnbusdt,btcusdt)
+        #[arg(required = true, value_delimiter = ',')]
+        symbols: Vec<String>,
+
+        /// How the depth of the books is maintained
+        #[arg(long, value_enum, default_value_t = DepthModeArg::Diff)]
+        depth_mode: DepthModeArg,
+    },
+
+    /// Serve the books of another venue's public feed over HTTP (and WebSocket with `--ws`) until interrupted with Ctrl+C
+    Venue {
+        /// Venue of the feed
+        #[arg(value_enum)]
+        venue: VenueArg,
+
+        /// Comma separated symbols as named by the venue (e.g., BTC-USD for Coinbase, BTC/USD for Kraken)
+        #[arg(required = true, value_delimiter = ',')]
+        symbols: Vec<String>,
+
+        /// Number of levels per side subscribed to on Kraken (10, 25, 100, 500 or 1000)
+        #[arg(long, default_value_t = 100)]
+        kraken_depth: usize,
+    },
+
+    /// Merge the books of one instrument from several sources and serve them like `serve` until interrupted with Ctrl+C
+    Consolidate {
+        /// Symbol of the consolidated book (e.g., BTCUSD)
+        symbol: String,
+
+        /// Sources as venue:symbol; venues are binance, binance-usd-m, binance-coin-m, coinbase and kraken
+        /// (e.g., binance:btcusdt binance-usd-m:btcusdt coinbase:BTC-USD kraken:BTC/USD)
+        #[arg(required = true)]
+        sources: Vec<SourceSpec>,
+
+        /// Number of levels per side subscribed to on Kraken (10, 25, 100, 500 or 1000)
+        #[arg(long, default_value_t = 100)]
+        kraken_depth: usize,
+    },
+
+    /// Print the current book of a symbol once and exit
+    Snapshot {
+        /// Symbol to print (e.g., bnbusdt)
+        symbol: String,
+
+        /// Number of levels per side
+        #[arg(long, default_value_t = 10)]
+        depth: usize,
+
+        /// Print JSON instead of the price ladder
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Replay recording files into the books and print the final books
+    Replay {
+        /// Recording files, replayed in the given order
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+
+        /// Replay speed: max, realtime or a factor such as 10x
+        #[arg(long, default_value = "max")]
+        speed: ReplaySpeed,
+
+        /// Number of levels per side printed for every book
+        #[arg(long, default_value_t = 10)]
+        depth: usize,
+
+        /// Print JSON instead of the price ladders
+        #[arg(long)]
+        json: bool,
+
+        /// Open the terminal UI on the replayed books instead of exiting
+        #[arg(long, visible_alias = "menu")]
+        tui: bool,
+    },
+
+    /// Apply the JSON updates of a file (one message per line, e.g., test-bed/*.json) and print the book
+    Process {
+        /// File with book ticker, depth, diff depth, trade, aggTrade or kline messages
+        file: PathBuf,
+
+        /// Symbol of the book the updates are applied to
+        #[arg(long)]
+        symbol: String,
+
+        /// Number of levels per side
+        #[arg(long, default_value_t = 10)]
+        depth: usize,
+
+        /// Print JSON instead of the price ladder
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// Enum representing the depth modes selectable on the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DepthModeArg {
+    /// Diff depth stream synced with a REST snapshot
+    Diff,
+
+    /// Partial depth20 stream
+    Partial,
+}
+
+/// Enum representing the venues selectable on the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum VenueArg {
+    /// Binance combined streams of `--market` (diff depth)
+    Binance,
+
+    /// Coinbase Advanced Trade `level2` channel
+    Coinbase,
+
+    /// Kraken WebSocket v2 `book` channel
+    Kraken,
+}
+
+/// Struct representing a source of a consolidated book given on the command line as venue:symbol
+#[derive(Debug, Clone)]
+pub struct SourceSpec {
+    /// Venue of the source
+    pub venue: Venue,
+
+    /// Market of a Binance source
+    pub market: Market,
+
+    /// Symbol as named by the venue (e.g., BTC-USD)
+    pub symbol: String,
+}
+
+impl SourceSpec {
+    /// Function to get the label the source is attributed with (e.g., coinbase:BTC-USD)
+    pub fn label(&self) -> String {
+        let venue = match (self.venue, self.market) {
+            (Venue::Binance, Market::UsdM) => "binance-usd-m",
+            (Venue::Binance, Market::CoinM) => "binance-coin-m",
+            (venue, _) => venue.as_str(),
+        };
+        format!("{}:{}", venue, self.symbol)
+    }
+}
+
+impl FromStr for SourceSpec {
+    type Err = OrderBookError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || OrderBookError::ParseError(format!("Invalid source: {}", value));
+        let (venue, symbol) = value.split_once(':').ok_or_else(invalid)?;
+        let (venue, market) = match venue.to_lowercase().as_str() {
+            "binance" => (Venue::Binance, Market::Spot),
+            "binance-usd-m" => (Venue::Binance, Market::UsdM),
+            "binance-coin-m" => (Venue::Binance, Market::CoinM),
+            "coinbase" => (Venue::Coinbase, Market::Spot),
+            "kraken" => (Venue::Kraken, Market::Spot),
+            _ => return Err(invalid()),
+        };
+        let symbol = symbol.trim();
+        if symbol.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            venue,
+            market,
+            symbol: symbol.to_string(),
+        })
+    }
+}
+
+/// Enum representing the markets selectable on the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum MarketArg {
+    /// Spot market
+    Spot,
+
+    /// USD-M futures
+    UsdM,
+
+    /// COIN-M futures
+    CoinM,
+}
+
+impl From<MarketArg> for Market {
+    fn from(market: MarketArg) -> Self {
+        match market {
+            MarketArg::Spot => Market::Spot,
+            MarketArg::UsdM => Market::UsdM,
+            MarketArg::CoinM => Market::CoinM,
+        }
+    }
+}
+
+/// Enum representing the trade streams selectable on the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TradeStreamArg {
+    /// Raw trade stream, one event per executed trade
+    Trade,
+
+    /// Aggregate trade stream, one event per taker order and price
+    AggTrade,
+}
+
+impl From<TradeStreamArg> for TradeStream {
+    fn from(stream: TradeStreamArg) -> Self {
+        match stream {
+            TradeStreamArg::Trade => TradeStream::Trade,
+            TradeStreamArg::AggTrade => TradeStream::AggTrade,
+        }
+    }
+}
+
+/// Enum representing the candle sources selectable on the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CandleSourceArg {
+    /// Kline stream of the exchange
+    Kline,
+
+    /// Aggregated from the trade stream (`--trades`, or the raw trade stream if not given)
+    Trades,
+
+    /// Aggregated from the mid price of the book
+    Mid,
+}
+
+impl From<CandleSourceArg> for CandleSource {
+    fn from(source: CandleSourceArg) -> Self {
+        match source {
+            CandleSourceArg::Kline => CandleSource::Kline,
+            CandleSourceArg::Trades => CandleSource::Trades,
+            CandleSourceArg::Mid => CandleSource::Mid,
+        }
+    }
+}
+
+impl From<DepthModeArg> for DepthMode {
+    fn from(mode: DepthModeArg) -> Self {
+        match mode {
+            DepthModeArg::Diff => DepthMode::Diff,
+            DepthModeArg::Partial => DepthMode::Partial,
+        }
+    }
+}
@@ -0,0 +1,436 @@
+use super::*;
+
+// Number of levels per side taken from every source book
+const SOURCE_DEPTH: usize = 1000;
+
+/// Struct representing the quantity a source book contributes to a consolidated level
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SourceQty {
+    /// Label of the source (e.g., coinbase:BTC-USD)
+    pub source: String,
+
+    /// Quantity of the source at the price
+    pub qty: Qty,
+}
+
+/// Struct representing a level of the consolidated ladder with the quantity of every source at its price
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AttributedLevel {
+    /// Price of the level
+    pub price: Price,
+
+    /// Total quantity of every source at the price
+    pub qty: Qty,
+
+    /// Quantities of the sources quoting the price, largest first
+    pub sources: Vec<SourceQty>,
+}
+
+/// Struct representing the state of a source book as of its last update
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SourceStatus {
+    /// Label of the source
+    pub source: String,
+
+    /// Synchronization state of the source book
+    pub sync_state: SyncState,
+
+    /// Whether the source book may be outdated after a stream interruption
+    pub stale: bool,
+
+    /// Best bid of the source, None while the side is empty
+    pub bid: Option<PriceLevel>,
+
+    /// Best ask of the source, None while the side is empty
+    pub ask: Option<PriceLevel>,
+}
+
+/// Struct representing a cross-venue crossed market: one source bids at or above the ask of another
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CrossedMarket {
+    /// Label of the source with the bid
+    pub bid_source: String,
+
+    /// Best bid of that source
+    pub bid: PriceLevel,
+
+    /// Label of the source with the ask
+    pub ask_source: String,
+
+    /// Best ask of that source
+    pub ask: PriceLevel,
+}
+
+impl fmt::Display for CrossedMarket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} bid {} >= {} ask {}",
+            self.bid_source, self.bid.price, self.ask_source, self.ask.price
+        )
+    }
+}
+
+/// Struct representing a consolidated book as served by `/consolidated`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConsolidatedView {
+    /// Symbol of the consolidated book
+    pub symbol: String,
+
+    /// Whether any source book may be outdated after a stream interruption
+    pub stale: bool,
+
+    /// State of every source book
+    pub sources: Vec<SourceStatus>,
+
+    /// Best bid across the sources, None while every bid side is empty
+    pub best_bid: Option<AttributedLevel>,
+
+    /// Best ask across the sources, None while every ask side is empty
+    pub best_ask: Option<AttributedLevel>,
+
+    /// Pairs of sources whose markets cross each other
+    pub crossed: Vec<CrossedMarket>,
+
+    /// Bid levels, best (highest) first
+    pub bids: Vec<AttributedLevel>,
+
+    /// Ask levels, best (lowest) first
+    pub asks: Vec<AttributedLevel>,
+}
+
+/// Struct holding the levels and state of a source book as of its last update
+#[derive(Debug, Clone)]
+struct SourceBook {
+    // Visible bid levels of the source (price -> quantity)
+    bids: BTreeMap<Price, Qty>,
+
+    // Visible ask levels of the source
+    asks: BTreeMap<Price, Qty>,
+
+    // Synchronization state of the source book
+    sync_state: SyncState,
+
+    // Whether the source book is stale
+    stale: bool,
+
+    // Clock of the source book
+    clock: u64,
+}
+
+impl SourceBook {
+    // Function to copy the best levels and the state of a book
+    fn from_book(orderbook: &OrderBook) -> Self {
+        Self {
+            bids: orderbook
+                .visible_bids()
+                .rev()
+                .take(SOURCE_DEPTH)
+                .map(|(price, qty)| (*price, *qty))
+                .collect(),
+            asks: orderbook
+                .visible_asks()
+                .take(SOURCE_DEPTH)
+                .map(|(price, qty)| (*price, *qty))
+                .collect(),
+            sync_state: orderbook.sync_state(),
+            stale: orderbook.is_stale(),
+            clock: orderbook.clock(),
+        }
+    }
+
+    // Function to get the best bid of the source
+    fn best_bid(&self) -> Option<PriceLevel> {
+        self.bids.last_key_value().map(|(price, qty)| PriceLevel {
+            price: *price,
+            qty: *qty,
+        })
+    }
+
+    // Function to get the best ask of the source
+    fn best_ask(&self) -> Option<PriceLevel> {
+        self.asks.first_key_value().map(|(price, qty)| PriceLevel {
+            price: *price,
+            qty: *qty,
+        })
+    }
+
+    // Function to get the levels of one side the source adds to the merged book, none while it is stale
+    fn contribution(
+        &self,
+        side: impl Fn(&Self) -> &BTreeMap<Price, Qty>,
+    ) -> Option<&BTreeMap<Price, Qty>> {
+        (!self.stale).then(|| side(self))
+    }
+}
+
+/// Struct merging the books of one instrument from several sources (venues, markets or connections)
+/// The merged levels are kept in an [`OrderBook`] of the consolidated symbol, so everything that queries
+/// a single book (ladders, metrics, market impact, the HTTP API and the fan-out) works on it too.
+/// Every source contributes its best 1000 visible levels per side; stale sources contribute nothing until
+/// they are resynchronized. An update only applies the levels of its source that changed to the merged book.
+#[derive(Debug, Clone)]
+pub struct ConsolidatedBook {
+    // Source books by label, as of their last update
+    sources: BTreeMap<String, SourceBook>,
+
+    // The merged levels of every source
+    book: OrderBook,
+
+    // Number of merges, used as the update ID of the merged book
+    revision: u64,
+}
+
+impl ConsolidatedBook {
+    /// Constructor function to create a ConsolidatedBook without sources
+    pub fn new(symbol: String) -> Self {
+        Self {
+            sources: BTreeMap::new(),
+            book: OrderBook::new(symbol),
+            revision: 0,
+        }
+    }
+
+    /// Function to get the symbol of the consolidated book
+    pub fn symbol(&self) -> &str {
+        self.book.symbol()
+    }
+
+    /// Function to get the merged book of every source
+    pub fn book(&self) -> &OrderBook {
+        &self.book
+    }
+
+    /// Function to add a source book, or replace its levels with the current ones, and merge the changes
+    pub fn update_source(&mut self, source: &str, orderbook: &OrderBook) {
+        let current = SourceBook::from_book(orderbook);
+        let diff = self.source_diff(self.sources.get(source), Some(&current));
+        self.sources.insert(source.to_string(), current);
+        self.merge(diff);
+    }
+
+    /// Function to drop a source and take its levels out of the merged book
+    pub fn remove_source(&mut self, source: &str) -> Result<(), OrderBookError> {
+        let Some(previous) = self.sources.remove(source) else {
+            return Err(OrderBookError::UnknownSymbol(format!(
+                "{} is not a source of {}",
+                source,
+                self.symbol()
+            )));
+        };
+        let diff = self.source_diff(Some(&previous), None);
+        self.merge(diff);
+        Ok(())
+    }
+
+    /// Function to get the state of every source book
+    pub fn sources(&self) -> Vec<SourceStatus> {
+        self.sources
+            .iter()
+            .map(|(source, book)| SourceStatus {
+                source: source.clone(),
+                sync_state: book.sync_state,
+                stale: book.stale,
+                bid: book.best_bid(),
+                ask: book.best_ask(),
+            })
+            .collect()
+    }
+
+    /// Function to get the consolidated bid level at a price with the quantity of every source
+    pub fn bid_level(&self, price: Price) -> Option<AttributedLevel> {
+        let qty = *self.book.bids.get(&price)?;
+        Some(self.attribute(price, qty, |source| &source.bids))
+    }
+
+    /// Function to get the consolidated ask level at a price with the quantity of every source
+    pub fn ask_level(&self, price: Price) -> Option<AttributedLevel> {
+        let qty = *self.book.asks.get(&price)?;
+        Some(self.attribute(price, qty, |source| &source.asks))
+    }
+
+    /// Function to get the best bid and ask across the sources
+    pub fn best_bid_offer(&self) -> (Option<AttributedLevel>, Option<AttributedLevel>) {
+        let bid = self.book.bids.keys().next_back();
+        let ask = self.book.asks.keys().next();
+        (
+            bid.and_then(|&price| self.bid_level(price)),
+            ask.and_then(|&price| self.ask_level(price)),
+        )
+    }
+
+    /// Function to find the pairs of sources where one bids at or above the other's ask
+    /// Stale sources are left out like in the merged levels, their prices may be outdated.
+    pub fn crossed_markets(&self) -> Vec<CrossedMarket> {
+        let live: Vec<_> = self
+            .sources
+            .iter()
+            .filter(|(_, book)| !book.stale)
+            .collect();
+        let mut crossed = Vec::new();
+        for (bid_source, bid_book) in &live {
+            let Some(bid) = bid_book.best_bid() else {
+                continue;
+            };
+            for (ask_source, ask_book) in &live {
+                let ask = ask_book
+                    .best_ask()
+                    .filter(|ask| bid_source != ask_source && bid.price >= ask.price);
+                if let Some(ask) = ask {
+                    crossed.push(CrossedMarket {
+                        bid_source: bid_source.to_string(),
+                        bid,
+                        ask_source: ask_source.to_string(),
+                        ask,
+                    });
+                }
+            }
+        }
+        crossed
+    }
+
+    /// Function to check that no source bids at or above the ask of another source
+    pub fn check_crossed_markets(&self) -> Result<(), OrderBookError> {
+        let crossed = self.crossed_markets();
+        if crossed.is_empty() {
+            return Ok(());
+        }
+        let pairs: Vec<String> = crossed.iter().map(CrossedMarket::to_string).collect();
+        Err(OrderBookError::CrossedBook(format!(
+            "{}: {}",
+            self.symbol(),
+            pairs.join(", ")
+        )))
+    }
+
+    /// Function to build the consolidated view with the top `depth` levels per side
+    pub fn view(&self, depth: usize) -> ConsolidatedView {
+        let (best_bid, best_ask) = self.best_bid_offer();
+        ConsolidatedView {
+            symbol: self.symbol().to_string(),
+            stale: self.sources.values().any(|source| source.stale),
+            sources: self.sources(),
+            best_bid,
+            best_ask,
+            crossed: self.crossed_markets(),
+            bids: self
+                .book
+                .bids
+                .keys()
+                .rev()
+                .take(depth)
+                .filter_map(|&price| self.bid_level(price))
+                .collect(),
+            asks: self
+                .book
+                .asks
+                .keys()
+                .take(depth)
+                .filter_map(|&price| self.ask_level(price))
+                .collect(),
+        }
+    }
+
+    // Function to list the live sources quoting a price on one side, largest quantity first
+    fn attribute(
+        &self,
+        price: Price,
+        qty: Qty,
+        side: impl Fn(&SourceBook) -> &BTreeMap<Price, Qty>,
+    ) -> AttributedLevel {
+        let mut sources: Vec<SourceQty> = self
+            .sources
+            .iter()
+            .filter_map(|(source, book)| {
+                book.contribution(&side)?.get(&price).map(|qty| SourceQty {
+                    source: source.clone(),
+                    qty: *qty,
+                })
+            })
+            .collect();
+        sources.sort_by_key(|source| std::cmp::Reverse(source.qty));
+        AttributedLevel {
+            price,
+            qty,
+            sources,
+        }
+    }
+
+    // Function to build the next diff of the merged book when a source moves from `previous` to `current`
+    // Only the prices where the quantity of the source changed are in it.
+    fn source_diff(
+        &self,
+        previous: Option<&SourceBook>,
+        current: Option<&SourceBook>,
+    ) -> DepthDiff {
+        let side = |side: fn(&SourceBook) -> &BTreeMap<Price, Qty>, merged| {
+            merged_changes(
+                merged,
+                previous.and_then(|source| source.contribution(side)),
+                current.and_then(|source| source.contribution(side)),
+            )
+        };
+        let revision = self.revision + 1;
+        DepthDiff::new(
+            revision,
+            revision,
+            None,
+            side(|source| &source.bids, &self.book.bids),
+            side(|source| &source.asks, &self.book.asks),
+        )
+    }
+
+    // Function to apply the next diff to the merged book
+    fn merge(&mut self, diff: DepthDiff) {
+        // A new book, or one that had no live source, starts over from its current levels
+        if self.book.sync_state() != SyncState::Synced {
+            let snapshot = DepthUpdate::new(
+                self.revision,
+                self.book.visible_bids().map(|(p, q)| (*p, *q)).collect(),
+                self.book.visible_asks().map(|(p, q)| (*p, *q)).collect(),
+            );
+            if let Err(e) = self.book.apply_depth_snapshot(&snapshot) {
+                log::error!("Merging the sources of {} failed: {}", self.symbol(), e);
+            }
+        }
+        self.revision += 1;
+        if let Err(e) = self.book.apply_depth_diff(diff) {
+            log::error!("Merging the sources of {} failed: {}", self.symbol(), e);
+        }
+
+        // The merged levels only hold live sources; the book is outdated when none is left
+        if !self.sources.is_empty() && self.sources.values().all(|source| source.stale) {
+            self.book.mark_stale();
+        }
+        // The merged book follows the latest clock of its sources, its candles the local clock
+        if let Some(clock) = self.sources.values().map(|source| source.clock).max() {
+            self.book.advance_clock(clock);
+        }
+        self.book.refresh_metrics();
+        self.book.sample_mid(now_millis());
+    }
+}
+
+// Function to get the merged quantities at the prices where the contribution of a source changed on one side
+// A zero quantity removes the merged level.
+fn merged_changes(
+    merged: &BTreeMap<Price, Qty>,
+    previous: Option<&BTreeMap<Price, Qty>>,
+    current: Option<&BTreeMap<Price, Qty>>,
+) -> Vec<(Price, Qty)> {
+    let empty = BTreeMap::new();
+    let (previous, current) = (previous.unwrap_or(&empty), current.unwrap_or(&empty));
+    let changed = current
+        .iter()
+        .filter(|(price, qty)| previous.get(price) != Some(qty))
+        .map(|(price, _)| price)
+        .chain(previous.keys().filter(|price| !current.contains_key(price)));
+    changed
+        .map(|price| {
+            let qty = merged.get(price).copied().unwrap_or(Qty::ZERO)
+                - previous.get(price).copied().unwrap_or(Qty::ZERO)
+                + current.get(price).copied().unwrap_or(Qty::ZERO);
+            (*price, qty)
+        })
+        .collect()
+}
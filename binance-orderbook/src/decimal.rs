@@ -0,0 +1,337 @@
+use super::*;
+
+/// Price of a level in the order book
+pub type Price = Decimal;
+
+/// Quantity (base asset) of a level in the order book
+pub type Qty = Decimal;
+
+/// Struct representing an exact fixed-point decimal number with 8 fractional digits
+/// (the precision Binance uses for prices and quantities)
+/// Equality, ordering and hashing only consider the value, the scale is kept to
+/// display the number exactly like the string it was parsed from
+#[derive(Clone, Copy, Default)]
+pub struct Decimal {
+    // Value scaled by 10^MAX_SCALE
+    units: i128,
+
+    // Number of fractional digits used when displaying the value
+    scale: u8,
+}
+
+impl Decimal {
+    /// Maximum number of fractional digits that can be represented
+    pub const MAX_SCALE: u8 = 8;
+
+    // Number of units in 1.0
+    const ONE_UNITS: i128 = 100_000_000;
+
+    /// Zero value
+    pub const ZERO: Decimal = Decimal { units: 0, scale: 0 };
+
+    /// One
+    pub const ONE: Decimal = Decimal {
+        units: Self::ONE_UNITS,
+        scale: 0,
+    };
+
+    /// Constructor function to create a Decimal from a mantissa and its number of fractional digits
+    /// e.g., `Decimal::new(253519, 4)` is 25.3519; scales above `MAX_SCALE` are rejected
+    pub fn new(mantissa: i64, scale: u8) -> Result<Self, OrderBookError> {
+        if scale > Self::MAX_SCALE {
+            return Err(OrderBookError::ParseError(format!(
+                "scale {} of {} exceeds {} fractional digits",
+                scale,
+                mantissa,
+                Self::MAX_SCALE
+            )));
+        }
+        Ok(Self {
+            units: mantissa as i128 * 10i128.pow((Self::MAX_SCALE - scale) as u32),
+            scale,
+        })
+    }
+
+    /// Function to convert an `f64` into the nearest Decimal with the maximum scale
+    pub fn from_f64(value: f64) -> Self {
+        Self {
+            units: (value * Self::ONE_UNITS as f64).round() as i128,
+            scale: Self::MAX_SCALE,
+        }
+        .normalized()
+    }
+
+    /// Function to convert the value into an `f64` (lossy, for display and statistics only)
+    pub fn to_f64(self) -> f64 {
+        self.units as f64 / Self::ONE_UNITS as f64
+    }
+
+    /// Function to get the number of fractional digits used for display
+    pub fn scale(self) -> u8 {
+        self.scale
+    }
+
+    /// Function to display the value with the given number of fractional digits (e.g., a tick size's scale)
+    /// Digits beyond the new scale are truncated
+    pub fn with_scale(self, scale: u8) -> Self {
+        let scale = scale.min(Self::MAX_SCALE);
+        let step = 10i128.pow((Self::MAX_SCALE - scale) as u32);
+        Self {
+            units: self.units / step * step,
+            scale,
+        }
+    }
+
+    /// Function to check whether the value is zero
+    pub fn is_zero(self) -> bool {
+        self.units == 0
+    }
+
+    /// Function to check whether the value is strictly positive
+    pub fn is_positive(self) -> bool {
+        self.units > 0
+    }
+
+    /// Function to check whether the value is a whole multiple of `step` (always true for a zero step)
+    pub fn is_multiple_of(self, step: Self) -> bool {
+        step.units == 0 || self.units % step.units == 0
+    }
+
+    /// Function to multiply two values, truncated to the maximum scale; None if the result overflows
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        // Multiplying the integer and fractional parts separately keeps the intermediate products in range
+        let int_part = (self.units / Self::ONE_UNITS).checked_mul(rhs.units)?;
+        let frac_part = (self.units % Self::ONE_UNITS).checked_mul(rhs.units)? / Self::ONE_UNITS;
+        Some(
+            Self {
+                units: int_part.checked_add(frac_part)?,
+                scale: (self.scale + rhs.scale).min(Self::MAX_SCALE),
+            }
+            .normalized(),
+        )
+    }
+
+    /// Function to divide two values, truncated to the maximum scale; None on division by zero or overflow
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        let quotient = self.units.checked_div(rhs.units)?;
+        let remainder = self.units % rhs.units;
+        let int_part = quotient.checked_mul(Self::ONE_UNITS)?;
+        let frac_part = remainder.checked_mul(Self::ONE_UNITS)? / rhs.units;
+        Some(
+            Self {
+                units: int_part.checked_add(frac_part)?,
+                scale: Self::MAX_SCALE,
+            }
+            .normalized(),
+        )
+    }
+
+    /// Function to get the absolute value, saturating like the operators
+    pub fn abs(self) -> Self {
+        Self {
+            units: self.units.saturating_abs(),
+            scale: self.scale,
+        }
+    }
+
+    // Function to get the value of the largest magnitude with a sign, for results that overflow
+    fn saturated(negative: bool) -> Self {
+        Self {
+            units: if negative { i128::MIN } else { i128::MAX },
+            scale: Self::MAX_SCALE,
+        }
+    }
+
+    // Function to reduce the scale to the smallest one that still displays the value exactly
+    fn normalized(mut self) -> Self {
+        while self.scale > 0 {
+            let step = 10i128.pow((Self::MAX_SCALE - self.scale + 1) as u32);
+            if self.units % step != 0 {
+                break;
+            }
+            self.scale -= 1;
+        }
+        self
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.units == other.units
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.units.cmp(&other.units)
+    }
+}
+
+impl Hash for Decimal {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.units.hash(state);
+    }
+}
+
+/// Implement `FromStr` to parse exchange strings like "25.35190000" without loss
+impl FromStr for Decimal {
+    type Err = OrderBookError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || OrderBookError::ParseError(format!("invalid decimal: {:?}", value));
+
+        // Split off the sign, then the integer and fractional digits
+        let (negative, digits) = match value.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, value),
+        };
+        let (int_part, frac_part) = digits.split_once('.').unwrap_or((digits, ""));
+
+        let all_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if (int_part.is_empty() && frac_part.is_empty())
+            || !all_digits(int_part)
+            || !all_digits(frac_part)
+            || frac_part.len() > Self::MAX_SCALE as usize
+        {
+            return Err(invalid());
+        }
+
+        let int_units = if int_part.is_empty() {
+            0
+        } else {
+            int_part
+                .parse::<i128>()
+                .ok()
+                .and_then(|v| v.checked_mul(Self::ONE_UNITS))
+                .ok_or_else(invalid)?
+        };
+        let frac_units = if frac_part.is_empty() {
+            0
+        } else {
+            frac_part.parse::<i128>().map_err(|_| invalid())?
+                * 10i128.pow((Self::MAX_SCALE as usize - frac_part.len()) as u32)
+        };
+
+        let units = int_units.checked_add(frac_units).ok_or_else(invalid)?;
+        Ok(Self {
+            units: if negative { -units } else { units },
+            scale: frac_part.len() as u8,
+        })
+    }
+}
+
+/// Implement `Display` to print the value with its scale (e.g., "25.35190000")
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.units < 0 { "-" } else { "" };
+        let units = self.units.unsigned_abs();
+        let int_part = units / Self::ONE_UNITS as u128;
+
+        if self.scale == 0 {
+            return write!(f, "{}{}", sign, int_part);
+        }
+
+        let frac_part =
+            (units % Self::ONE_UNITS as u128) / 10u128.pow((Self::MAX_SCALE - self.scale) as u32);
+        write!(
+            f,
+            "{}{}.{:0width$}",
+            sign,
+            int_part,
+            frac_part,
+            width = self.scale as usize
+        )
+    }
+}
+
+/// Implement `Serialize` as a JSON string, so values keep their exact digits (e.g., "25.35190000")
+impl Serialize for Decimal {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Implement `Debug` like `Display`, so printed levels read like the exchange strings
+impl fmt::Debug for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// Addition, saturating when the result is out of range
+impl Add for Decimal {
+    type Output = Decimal;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            units: self.units.saturating_add(rhs.units),
+            scale: self.scale.max(rhs.scale),
+        }
+    }
+}
+
+impl AddAssign for Decimal {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+/// Subtraction, saturating when the result is out of range
+impl Sub for Decimal {
+    type Output = Decimal;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self {
+            units: self.units.saturating_sub(rhs.units),
+            scale: self.scale.max(rhs.scale),
+        }
+    }
+}
+
+impl SubAssign for Decimal {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+/// Multiplication (e.g., price * quantity = notional), truncated to the maximum scale
+/// Results out of range saturate; use [`Decimal::checked_mul`] to detect them
+impl Mul for Decimal {
+    type Output = Decimal;
+
+    fn mul(self, rhs: Self) -> Self {
+        self.checked_mul(rhs)
+            .unwrap_or_else(|| Self::saturated((self.units < 0) != (rhs.units < 0)))
+    }
+}
+
+/// Division (e.g., notional / quantity = average price), truncated to the maximum scale
+/// Results out of range and divisions by zero saturate (zero divided by zero is zero); use
+/// [`Decimal::checked_div`] to detect them
+impl Div for Decimal {
+    type Output = Decimal;
+
+    fn div(self, rhs: Self) -> Self {
+        self.checked_div(rhs).unwrap_or_else(|| {
+            if self.units == 0 {
+                Self::ZERO
+            } else {
+                Self::saturated((self.units < 0) != (rhs.units < 0))
+            }
+        })
+    }
+}
+
+impl Sum for Decimal {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Decimal::ZERO, |acc, value| acc + value)
+    }
+}
@@ -0,0 +1,88 @@
+use super::*;
+
+/// Struct representing the mark price and funding of a perpetual or delivery futures contract
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct MarkPrice {
+    /// Mark price
+    pub mark_price: Price,
+
+    /// Index price, if published (USD-M)
+    pub index_price: Option<Price>,
+
+    /// Estimated settle price, if published
+    pub estimated_settle_price: Option<Price>,
+
+    /// Current funding rate, None for delivery contracts
+    pub funding_rate: Option<Decimal>,
+
+    /// Next funding time in milliseconds, None for delivery contracts
+    pub next_funding_time: Option<u64>,
+
+    /// Event time in milliseconds
+    pub time: u64,
+}
+
+impl MarkPrice {
+    /// Function to construct a MarkPrice from a `markPriceUpdate` event reader (deserialized data)
+    pub fn from_reader(reader: MarkPriceReader) -> Result<Self, OrderBookError> {
+        let optional = |value: Option<String>, name: &str| -> Result<_, OrderBookError> {
+            value
+                .filter(|value| !value.is_empty())
+                .map(|value| parse_decimal(&value, name))
+                .transpose()
+        };
+        Ok(Self {
+            mark_price: parse_decimal(&reader.mark_price, "mark price")?,
+            index_price: optional(reader.index_price, "index price")?,
+            estimated_settle_price: optional(
+                reader.estimated_settle_price,
+                "estimated settle price",
+            )?,
+            funding_rate: optional(Some(reader.funding_rate), "funding rate")?,
+            next_funding_time: Some(reader.next_funding_time).filter(|&time| time > 0),
+            time: reader.event_time,
+        })
+    }
+}
+
+/// Struct representing a liquidation order of a futures contract
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Liquidation {
+    /// Side of the liquidation order: a sell closes a long position
+    pub side: Side,
+
+    /// Order price
+    pub price: Price,
+
+    /// Average fill price
+    pub average_price: Price,
+
+    /// Original quantity
+    pub qty: Qty,
+
+    /// Filled quantity
+    pub filled_qty: Qty,
+
+    /// Trade time in milliseconds
+    pub time: u64,
+}
+
+impl Liquidation {
+    /// Function to construct a Liquidation from a `forceOrder` event reader (deserialized data)
+    pub fn from_reader(reader: ForceOrderReader) -> Result<Self, OrderBookError> {
+        let order = reader.order;
+        Ok(Self {
+            side: order.side.parse()?,
+            price: parse_decimal(&order.price, "price")?,
+            average_price: parse_decimal(&order.average_price, "average price")?,
+            qty: parse_decimal(&order.qty, "qty")?,
+            filled_qty: parse_decimal(&order.filled_qty, "filled qty")?,
+            time: order.trade_time,
+        })
+    }
+
+    /// Function to get the quote notional of the filled quantity
+    pub fn notional(&self) -> Decimal {
+        self.average_price * self.filled_qty
+    }
+}
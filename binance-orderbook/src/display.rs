@@ -0,0 +1,265 @@
+use super::*;
+
+/// Function to display the top levels of the orderbook as a side-by-side price ladder
+/// Each side shows the running quantity and notional from the best level outwards
+pub fn display_depth_ladder(ladder: &DepthLadder) {
+    if ladder.bids.is_empty() && ladder.asks.is_empty() {
+        println!("{}", "Orderbook is empty.".purple());
+        return;
+    }
+
+    // Header: bid columns mirror the ask columns around the spread
+    println!(
+        "{}",
+        format!(
+            "{:>16} {:>16} {:>16} {:>16} | {:<16} {:<16} {:<16} {:<16}",
+            "Cum. Notional", "Cum. Qty", "Qty", "Bid", "Ask", "Qty", "Cum. Qty", "Cum. Notional"
+        )
+        .purple()
+        .bold()
+    );
+
+    // One row per depth, a side without a level at that depth is left blank
+    for row in 0..ladder.bids.len().max(ladder.asks.len()) {
+        let bid = ladder.bids.get(row).map_or_else(
+            || format!("{:>67}", ""),
+            |level| {
+                format!(
+                    "{:>16} {:>16} {:>16} {:>16}",
+                    level.cumulative_notional.to_string(),
+                    level.cumulative_qty.to_string(),
+                    level.qty.to_string(),
+                    level.price.to_string()
+                )
+            },
+        );
+        let ask = ladder.asks.get(row).map_or_else(String::new, |level| {
+            format!(
+                "{:<16} {:<16} {:<16} {:<16}",
+                level.price.to_string(),
+                level.qty.to_string(),
+                level.cumulative_qty.to_string(),
+                level.cumulative_notional.to_string()
+            )
+        });
+        println!("{} | {}", bid.green(), ask.red());
+    }
+}
+
+/// Function to display the microstructure metrics of the orderbook
+pub fn display_book_metrics(metrics: Option<&BookMetrics>) {
+    let Some(metrics) = metrics else {
+        println!("{}", "Orderbook is empty.".purple());
+        return;
+    };
+
+    let imbalance = metrics.imbalance.map_or_else(
+        || "n/a".to_string(),
+        |imbalance| format!("{:+.4}", imbalance),
+    );
+    let liquidity = &metrics.liquidity;
+    println!(
+        "{}",
+        format!(
+            "Mid: {}, Spread: {} ({:.2} bps), Microprice: {}, Imbalance: {}",
+            metrics.mid, metrics.spread, metrics.spread_bps, metrics.microprice, imbalance
+        )
+        .purple()
+    );
+    println!(
+        "{}",
+        format!(
+            "Liquidity near mid: bids {} ({} notional), asks {} ({} notional)",
+            liquidity.bid_qty, liquidity.bid_notional, liquidity.ask_qty, liquidity.ask_notional
+        )
+        .purple()
+    );
+}
+
+/// Function to display the rolling trade statistics of every window ending at `now`, if any trade was received
+pub fn display_trade_stats(trades: &TradeStore, now: u64) {
+    let Some(last) = trades.last_trade() else {
+        return;
+    };
+
+    println!(
+        "{}",
+        format!(
+            "Last trade: {:?} {} @ {} at {}",
+            last.aggressor,
+            last.qty,
+            last.price,
+            time_of_day(last.time)
+        )
+        .purple()
+    );
+    for stats in trades.window_stats(now) {
+        println!("{}", describe_trade_stats(&stats).purple());
+    }
+}
+
+/// Function to describe the trade statistics of a window in one line
+pub fn describe_trade_stats(stats: &TradeStats) -> String {
+    let vwap = stats
+        .vwap
+        .map_or_else(|| "n/a".to_string(), |vwap| vwap.to_string());
+    format!(
+        "{:>4}: {} trades, VWAP {}, buy {} / sell {}",
+        window_label(stats.window_ms),
+        stats.trade_count,
+        vwap,
+        stats.buy_volume,
+        stats.sell_volume
+    )
+}
+
+/// Function to display the mark price, funding and the latest liquidation of a futures book, if any
+pub fn display_futures(orderbook: &OrderBook) {
+    if let Some(mark_price) = orderbook.mark_price() {
+        println!("{}", describe_mark_price(mark_price).yellow());
+    }
+    if let Some(liquidation) = orderbook.liquidations().next() {
+        println!(
+            "{}",
+            format!("Last liquidation: {}", describe_liquidation(liquidation)).yellow()
+        );
+    }
+}
+
+/// Function to describe the mark price and funding of a futures contract in one line
+pub fn describe_mark_price(mark_price: &MarkPrice) -> String {
+    let mut description = format!("Mark price: {}", mark_price.mark_price);
+    if let Some(index_price) = mark_price.index_price {
+        description += &format!(", index {}", index_price);
+    }
+    if let (Some(rate), Some(time)) = (mark_price.funding_rate, mark_price.next_funding_time) {
+        description += &format!(
+            ", funding {:.4}% next at {}",
+            rate.to_f64() * 100.0,
+            time_of_day(time)
+        );
+    }
+    description
+}
+
+/// Function to describe a liquidation order in one line
+pub fn describe_liquidation(liquidation: &Liquidation) -> String {
+    format!(
+        "{} {:?} {} @ {}",
+        time_of_day(liquidation.time),
+        liquidation.side,
+        liquidation.filled_qty,
+        liquidation.average_price
+    )
+}
+
+// Number of closed candles printed below the candle in progress
+const PRINTED_CANDLES: usize = 3;
+
+/// Function to display the candle in progress and the most recent closed candles, if any
+pub fn display_candles(candles: &CandleStore) {
+    let Some(current) = candles.current() else {
+        return;
+    };
+
+    println!(
+        "{}",
+        format!("Candles ({}, {:?}):", candles.interval(), candles.source()).cyan()
+    );
+    for candle in std::iter::once(current).chain(candles.closed().take(PRINTED_CANDLES)) {
+        println!("{}", describe_candle(candle).cyan());
+    }
+}
+
+/// Function to describe a candle in one line; incomplete candles are marked with `*`
+pub fn describe_candle(candle: &Candle) -> String {
+    format!(
+        "{}{} O {} H {} L {} C {} V {} ({} trades)",
+        time_of_day(candle.open_time),
+        if candle.complete { " " } else { "*" },
+        candle.open,
+        candle.high,
+        candle.low,
+        candle.close,
+        candle.volume,
+        candle.trade_count
+    )
+}
+
+/// Function to label a window length (e.g., 30s, 5m, 1h)
+pub fn window_label(window_ms: u64) -> String {
+    let secs = window_ms / 1000;
+    match secs {
+        0 => format!("{}ms", window_ms),
+        s if s % 3600 == 0 => format!("{}h", s / 3600),
+        s if s % 60 == 0 => format!("{}m", s / 60),
+        s => format!("{}s", s),
+    }
+}
+
+/// Function to format a timestamp in milliseconds as the UTC time of day (HH:MM:SS)
+pub fn time_of_day(time_ms: u64) -> String {
+    let secs = time_ms / 1000 % 86_400;
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// Enum representing what is known about the source of the stream messages, shown in the status bars
+#[derive(Debug, Clone)]
+pub enum FeedStatus {
+    /// The stream client has not reported a connection event yet
+    Connecting,
+
+    /// The last connection event reported by the stream client
+    Live(ConnectionEvent),
+
+    /// A recording is being replayed
+    Replaying,
+
+    /// The replay has ended with the given stats
+    ReplayFinished(ReplayStats),
+
+    /// The replay has ended with the given error
+    ReplayFailed(String),
+}
+
+impl FeedStatus {
+    /// Function to describe the status in one line
+    pub fn describe(&self) -> String {
+        match self {
+            FeedStatus::Connecting => "Connecting to Binance".to_string(),
+            FeedStatus::Live(ConnectionEvent::Connected(streams)) => {
+                format!("Connected ({} streams)", streams.len())
+            }
+            FeedStatus::Live(ConnectionEvent::Disconnected(reason)) => {
+                format!("Disconnected: {}", reason)
+            }
+            FeedStatus::Live(ConnectionEvent::Reconnecting { attempt, delay }) => {
+                format!("Reconnecting (attempt {}) in {:?}", attempt, delay)
+            }
+            FeedStatus::Live(ConnectionEvent::RolledOver) => {
+                "Rolling the connection over".to_string()
+            }
+            FeedStatus::Replaying => "Replaying".to_string(),
+            FeedStatus::ReplayFinished(stats) => replay_summary(stats),
+            FeedStatus::ReplayFailed(e) => format!("Replay failed: {}", e),
+        }
+    }
+
+    /// Function to check whether messages are flowing (or have been replayed) without problems
+    pub fn is_healthy(&self) -> bool {
+        matches!(
+            self,
+            FeedStatus::Live(ConnectionEvent::Connected(_))
+                | FeedStatus::Replaying
+                | FeedStatus::ReplayFinished(_)
+        )
+    }
+}
+
+/// Function to describe the number of frames a replay has processed
+pub fn replay_summary(stats: &ReplayStats) -> String {
+    format!(
+        "Replay finished: {} frames, {} messages, {} snapshots, {} skipped",
+        stats.frames, stats.messages, stats.snapshots, stats.skipped
+    )
+}
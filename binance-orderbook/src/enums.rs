@@ -3,27 +3,527 @@ use super::*;
 /// Enum representing different types of messages received from Binance
 #[derive(Debug)]
 pub enum BinanceMessage {
-    // Represents a BookTicker message with a deserialized BookTickerUpdateReader
+    /// Represents a BookTicker message with a deserialized BookTickerUpdateReader
     BookTicker(BookTickerUpdateReader),
 
-    // Represents a DepthUpdate message with a deserialized DepthUpdateReader
+    /// Represents a DepthUpdate message with a deserialized DepthUpdateReader
     DepthUpdate(DepthUpdateReader),
+
+    /// Represents a diff-depth message (`depthUpdate` event) with a deserialized DepthDiffReader
+    DepthDiff(DepthDiffReader),
+
+    /// Represents a `trade` event with a deserialized TradeReader
+    Trade(TradeReader),
+
+    /// Represents an `aggTrade` event with a deserialized AggTradeReader
+    AggTrade(AggTradeReader),
+
+    /// Represents a `kline` event with a deserialized KlineReader
+    Kline(KlineReader),
+
+    /// Represents a futures `markPriceUpdate` event with a deserialized MarkPriceReader
+    MarkPrice(MarkPriceReader),
+
+    /// Represents a futures `forceOrder` (liquidation) event with a deserialized ForceOrderReader
+    ForceOrder(ForceOrderReader),
+}
+
+impl BinanceMessage {
+    /// Function to get the type of the message (e.g., as a metrics label)
+    pub fn kind(&self) -> &'static str {
+        match self {
+            BinanceMessage::BookTicker(_) => "book_ticker",
+            BinanceMessage::DepthUpdate(_) => "depth",
+            BinanceMessage::DepthDiff(_) => "depth_diff",
+            BinanceMessage::Trade(_) => "trade",
+            BinanceMessage::AggTrade(_) => "agg_trade",
+            BinanceMessage::Kline(_) => "kline",
+            BinanceMessage::MarkPrice(_) => "mark_price",
+            BinanceMessage::ForceOrder(_) => "force_order",
+        }
+    }
+
+    /// Function to get the exchange event time in milliseconds, if the message carries one
+    /// Spot book ticker and partial depth payloads have no event time.
+    pub fn event_time(&self) -> Option<u64> {
+        match self {
+            BinanceMessage::DepthDiff(diff) => Some(diff.event_time),
+            BinanceMessage::Trade(trade) => Some(trade.event_time),
+            BinanceMessage::AggTrade(trade) => Some(trade.event_time),
+            BinanceMessage::Kline(kline) => Some(kline.event_time),
+            BinanceMessage::MarkPrice(mark) => Some(mark.event_time),
+            BinanceMessage::ForceOrder(order) => Some(order.event_time),
+            BinanceMessage::BookTicker(_) | BinanceMessage::DepthUpdate(_) => None,
+        }
+    }
+}
+
+/// Enum representing the Binance market the order books are maintained for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Market {
+    /// Spot market
+    #[default]
+    Spot,
+
+    /// USD-M (USDT and USDC margined) futures
+    UsdM,
+
+    /// COIN-M (coin margined) futures
+    CoinM,
+}
+
+impl Market {
+    /// Function to get the base URL of the WebSocket streams
+    pub fn stream_url(&self) -> &'static str {
+        match self {
+            Market::Spot => "wss://stream.binance.com:9443",
+            Market::UsdM => "wss://fstream.binance.com",
+            Market::CoinM => "wss://dstream.binance.com",
+        }
+    }
+
+    /// Function to get the base URL of the REST API
+    pub fn rest_url(&self) -> &'static str {
+        match self {
+            Market::Spot => "https://api.binance.com",
+            Market::UsdM => "https://fapi.binance.com",
+            Market::CoinM => "https://dapi.binance.com",
+        }
+    }
+
+    /// Function to get the path prefix of the REST endpoints (e.g., `/api/v3` for `/api/v3/depth`)
+    pub fn api_prefix(&self) -> &'static str {
+        match self {
+            Market::Spot => "/api/v3",
+            Market::UsdM => "/fapi/v1",
+            Market::CoinM => "/dapi/v1",
+        }
+    }
+
+    /// Function to get the largest depth snapshot limit of the REST API
+    pub fn max_snapshot_limit(&self) -> u32 {
+        match self {
+            Market::Spot => 5000,
+            Market::UsdM | Market::CoinM => 1000,
+        }
+    }
+
+    /// Function to check whether the market is a futures market
+    pub fn is_futures(&self) -> bool {
+        matches!(self, Market::UsdM | Market::CoinM)
+    }
+}
+
+/// Enum representing the venues whose public book streams have a [`MarketDataFeed`] adapter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Venue {
+    /// Binance combined streams, diff books bootstrapped from the REST API
+    Binance,
+
+    /// Coinbase Advanced Trade `level2` channel
+    Coinbase,
+
+    /// Kraken WebSocket v2 `book` channel
+    Kraken,
+}
+
+impl Venue {
+    /// Function to get the lowercase name of the venue
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Venue::Binance => "binance",
+            Venue::Coinbase => "coinbase",
+            Venue::Kraken => "kraken",
+        }
+    }
+
+    /// Function to check whether diff books of the venue are bootstrapped from a REST snapshot
+    /// (the other venues send a snapshot on the stream after subscribing)
+    pub fn has_rest_snapshots(&self) -> bool {
+        matches!(self, Venue::Binance)
+    }
 }
 
-/// Enum representing different menu commands that the system can handle
-pub enum MenuCommand {
-    // Command to fetch and display the best bid and ask prices from the order book
-    BestBidAsk,
+impl fmt::Display for Venue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Enum representing which depth stream is used to maintain the order book
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepthMode {
+    /// Partial book depth stream (`@depth20@100ms`), top 20 levels per message
+    Partial,
+
+    /// Diff depth stream (`@depth@100ms`) synchronized against a REST snapshot
+    Diff,
+}
+
+impl DepthMode {
+    /// Function to build the depth stream name for the given symbol
+    pub fn stream_name(&self, symbol: &str) -> String {
+        match self {
+            DepthMode::Partial => format!("{}@depth20@100ms", symbol.to_lowercase()),
+            DepthMode::Diff => format!("{}@depth@100ms", symbol.to_lowercase()),
+        }
+    }
+}
+
+/// Enum representing which trade stream is subscribed for every symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeStream {
+    /// Raw trade stream (`@trade`), one event per executed trade
+    Trade,
+
+    /// Aggregate trade stream (`@aggTrade`), one event per taker order and price
+    AggTrade,
+}
+
+impl TradeStream {
+    /// Function to build the trade stream name for the given symbol
+    pub fn stream_name(&self, symbol: &str) -> String {
+        match self {
+            TradeStream::Trade => format!("{}@trade", symbol.to_lowercase()),
+            TradeStream::AggTrade => format!("{}@aggTrade", symbol.to_lowercase()),
+        }
+    }
+}
+
+/// Enum representing what the candles of every book are built from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CandleSource {
+    /// Candles of the exchange's kline stream (`@kline_<interval>`)
+    Kline,
+
+    /// Candles aggregated locally from the trade stream
+    Trades,
+
+    /// Candles aggregated locally from the mid price after every applied book message (no volume)
+    Mid,
+}
+
+/// Enum representing the candle intervals supported by Binance klines (except `1M`, months vary in length)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum KlineInterval {
+    /// 1 second
+    OneSecond,
+    /// 1 minute
+    OneMinute,
+    /// 3 minutes
+    ThreeMinutes,
+    /// 5 minutes
+    FiveMinutes,
+    /// 15 minutes
+    FifteenMinutes,
+    /// 30 minutes
+    ThirtyMinutes,
+    /// 1 hour
+    OneHour,
+    /// 2 hours
+    TwoHours,
+    /// 4 hours
+    FourHours,
+    /// 6 hours
+    SixHours,
+    /// 8 hours
+    EightHours,
+    /// 12 hours
+    TwelveHours,
+    /// 1 day
+    OneDay,
+    /// 3 days
+    ThreeDays,
+    /// 1 week, starting on Monday
+    OneWeek,
+}
+
+impl KlineInterval {
+    // Every interval with its Binance name
+    const NAMES: [(KlineInterval, &'static str); 15] = [
+        (KlineInterval::OneSecond, "1s"),
+        (KlineInterval::OneMinute, "1m"),
+        (KlineInterval::ThreeMinutes, "3m"),
+        (KlineInterval::FiveMinutes, "5m"),
+        (KlineInterval::FifteenMinutes, "15m"),
+        (KlineInterval::ThirtyMinutes, "30m"),
+        (KlineInterval::OneHour, "1h"),
+        (KlineInterval::TwoHours, "2h"),
+        (KlineInterval::FourHours, "4h"),
+        (KlineInterval::SixHours, "6h"),
+        (KlineInterval::EightHours, "8h"),
+        (KlineInterval::TwelveHours, "12h"),
+        (KlineInterval::OneDay, "1d"),
+        (KlineInterval::ThreeDays, "3d"),
+        (KlineInterval::OneWeek, "1w"),
+    ];
+
+    /// Function to get the Binance name of the interval (e.g., `1m`)
+    pub fn as_str(&self) -> &'static str {
+        Self::NAMES
+            .iter()
+            .find(|(interval, _)| interval == self)
+            .map_or("", |(_, name)| name)
+    }
+
+    /// Function to get the length of the interval in milliseconds
+    pub fn millis(&self) -> u64 {
+        const SECOND: u64 = 1000;
+        const MINUTE: u64 = 60 * SECOND;
+        const HOUR: u64 = 60 * MINUTE;
+        const DAY: u64 = 24 * HOUR;
+        match self {
+            KlineInterval::OneSecond => SECOND,
+            KlineInterval::OneMinute => MINUTE,
+            KlineInterval::ThreeMinutes => 3 * MINUTE,
+            KlineInterval::FiveMinutes => 5 * MINUTE,
+            KlineInterval::FifteenMinutes => 15 * MINUTE,
+            KlineInterval::ThirtyMinutes => 30 * MINUTE,
+            KlineInterval::OneHour => HOUR,
+            KlineInterval::TwoHours => 2 * HOUR,
+            KlineInterval::FourHours => 4 * HOUR,
+            KlineInterval::SixHours => 6 * HOUR,
+            KlineInterval::EightHours => 8 * HOUR,
+            KlineInterval::TwelveHours => 12 * HOUR,
+            KlineInterval::OneDay => DAY,
+            KlineInterval::ThreeDays => 3 * DAY,
+            KlineInterval::OneWeek => 7 * DAY,
+        }
+    }
+
+    /// Function to get the open time of the interval containing `time` (both in milliseconds)
+    /// Intervals are aligned to the Unix epoch (UTC) like Binance klines; weeks start on Monday,
+    /// four days after the epoch.
+    pub fn open_time(&self, time: u64) -> u64 {
+        let length = self.millis();
+        let offset = match self {
+            KlineInterval::OneWeek => 4 * KlineInterval::OneDay.millis(),
+            _ => 0,
+        };
+        time.saturating_sub((time + length - offset) % length)
+    }
+
+    /// Function to build the kline stream name for the given symbol
+    pub fn stream_name(&self, symbol: &str) -> String {
+        format!("{}@kline_{}", symbol.to_lowercase(), self.as_str())
+    }
+}
+
+impl fmt::Display for KlineInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for KlineInterval {
+    type Err = OrderBookError;
+
+    /// Parses a Binance interval name (e.g., `1s`, `15m`, `4h`, `1d`, `1w`)
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        Self::NAMES
+            .iter()
+            .find(|(_, name)| *name == value)
+            .map(|(interval, _)| *interval)
+            .ok_or_else(|| {
+                OrderBookError::ParseError(format!("invalid kline interval: {:?}", value))
+            })
+    }
+}
+
+impl Serialize for KlineInterval {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Enum representing how a partial depth message (`DepthUpdate`) is applied to the order book
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepthApplyMode {
+    /// The message is a complete picture of the top levels and replaces the bids and asks
+    Replace,
+
+    /// The message levels are merged into the existing bids and asks
+    Merge,
+}
+
+/// Enum representing the synchronization state of a locally maintained depth book
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SyncState {
+    /// Diff events are being buffered until a REST snapshot is applied
+    AwaitingSnapshot,
+
+    /// The book was bootstrapped from a snapshot and diff events are applied in sequence
+    Synced,
+}
+
+/// Enum representing live subscription changes sent to the combined stream client
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamCommand {
+    /// Subscribe to the given stream names (e.g., bnbusdt@bookTicker)
+    Subscribe(Vec<String>),
+
+    /// Unsubscribe from the given stream names
+    Unsubscribe(Vec<String>),
+}
+
+impl StreamCommand {
+    /// Function to build the JSON request Binance expects for this command
+    pub fn to_json(&self, id: u64) -> String {
+        let (method, params) = match self {
+            StreamCommand::Subscribe(streams) => ("SUBSCRIBE", streams),
+            StreamCommand::Unsubscribe(streams) => ("UNSUBSCRIBE", streams),
+        };
+        serde_json::json!({ "method": method, "params": params, "id": id }).to_string()
+    }
+}
+
+/// Enum representing events of the supervised stream connection, reported to callers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    /// A connection was established for the given streams
+    Connected(Vec<String>),
+
+    /// The connection failed or closed; the String parameter is the reason
+    Disconnected(String),
+
+    /// A new connection attempt will be made after the given delay
+    Reconnecting {
+        /// Number of consecutive attempts, starting at 1
+        attempt: u32,
+        /// Backoff delay before the attempt
+        delay: Duration,
+    },
+
+    /// The connection was closed proactively before the server's 24h limit and is re-established
+    RolledOver,
+}
+
+/// Enum representing how a single stream connection ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ConnectionEnd {
+    /// The connection failed or was closed; the String parameter is the reason
+    Closed(String),
+
+    /// The connection reached its maximum age
+    RolledOver,
+
+    /// The message receiver was dropped, so the client should stop
+    Shutdown,
+}
+
+/// Enum representing the state of the book processing task
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessingState {
+    /// Messages are applied as they arrive
+    Running,
+
+    /// Messages are queued until processing is resumed
+    Paused,
+
+    /// The task has ended and handed back the message receiver
+    Stopped,
+}
+
+/// Enum representing the side of an order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    /// Buys from the asks
+    Buy,
+
+    /// Sells into the bids
+    Sell,
+}
+
+impl FromStr for Side {
+    type Err = OrderBookError;
+
+    /// Parses `buy` or `sell` (case insensitive)
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "buy" => Ok(Side::Buy),
+            "sell" => Ok(Side::Sell),
+            _ => Err(OrderBookError::ParseError(format!(
+                "invalid side: {:?}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Enum representing the size of a market order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderSize {
+    /// Quantity of the base asset (e.g., BNB for BNBUSDT)
+    Base(Qty),
+
+    /// Notional in the quote asset (e.g., USDT for BNBUSDT)
+    Quote(Decimal),
+}
+
+impl FromStr for OrderSize {
+    type Err = OrderBookError;
+
+    /// Parses a base quantity (e.g., `1.5`) or a quote notional with a `q` suffix (e.g., `1000q`)
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim().to_lowercase();
+        match value.strip_suffix('q') {
+            Some(notional) => notional.parse().map(OrderSize::Quote),
+            None => value.parse().map(OrderSize::Base),
+        }
+    }
+}
+
+/// Enum representing the streams whose update IDs are tracked separately by the order book
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StreamKind {
+    /// Book ticker stream (`@bookTicker`), update ID `u`
+    BookTicker,
+
+    /// Partial book depth stream (`@depth20`), update ID `lastUpdateId`
+    PartialDepth,
+
+    /// Diff depth stream (`@depth`), update IDs `U`/`u`
+    DiffDepth,
+
+    /// Trade stream (`@trade`), trade ID `t`
+    Trade,
+
+    /// Aggregate trade stream (`@aggTrade`), aggregate trade ID `a`
+    AggTrade,
+
+    /// Futures mark price stream (`@markPrice`), tracked by the event time `E`
+    MarkPrice,
+}
+
+impl StreamKind {
+    /// Every stream kind, in display order
+    pub const ALL: [StreamKind; 6] = [
+        StreamKind::BookTicker,
+        StreamKind::PartialDepth,
+        StreamKind::DiffDepth,
+        StreamKind::Trade,
+        StreamKind::AggTrade,
+        StreamKind::MarkPrice,
+    ];
+}
 
-    // Command to fetch the volume at a specific price level; the f64 parameter represents the price
-    VolumeAtPrice(f64),
+/// Enum representing the outcome of checking an update ID against a stream's sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceOutcome {
+    /// The update is newer than the last applied one and continues the sequence
+    InOrder,
 
-    // Command to process a given JSON string (the String parameter contains the JSON data)
-    JsonProcessing(String),
+    /// The update ID equals the last applied one
+    Duplicate,
 
-    // Command to handle WebSocket message processing
-    WebSocketProcessing,
+    /// The update ID is older than the last applied one
+    Outdated,
 
-    // Command to exit the menu or application
-    Exit,
+    /// Updates are missing between the last applied one and this one (resync needed)
+    Gap,
 }
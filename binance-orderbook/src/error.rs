@@ -3,29 +3,115 @@ use super::*;
 /// Define a custom error type for handling different kinds of errors in the order book
 #[derive(Debug)] // Enable debug formatting for the enum
 pub enum OrderBookError {
-    // Error caused by issues in I/O operations (e.g., reading/writing from/to stdin, files, etc.)
+    /// Error caused by issues in I/O operations (e.g., reading/writing from/to stdin, files, etc.)
     IoError(std::io::Error),
 
-    // Error in parsing JSON data (deserialization failure)
+    /// Error in parsing JSON data (deserialization failure)
     JsonParseError(SerdeError),
 
-    // Error when an operation encounters a symbol mismatch in the order book
+    /// Error when an operation encounters a symbol mismatch in the order book
     DifferentSymbol(String),
 
-    // Error when the `lastUpdateId` is outdated, indicating the received update is not valid
+    /// Error when the `lastUpdateId` is outdated, indicating the received update is not valid
     UpdateIdOutdated(String),
 
-    // Error when parsing a value (e.g., price or volume) fails
+    /// Error when the update ID equals the last applied update ID of the stream
+    DuplicateUpdate(String),
+
+    /// Error when parsing a value (e.g., price or volume) fails
     ParseError(String),
 
-    // Error when a WebSocket connection fails or an issue occurs during communication
-    ConnectionError(tungstenite::Error),
+    /// Error when a WebSocket connection fails or an issue occurs during communication
+    ConnectionError(Box<tungstenite::Error>),
 
-    // Error when sending a message over the channel fails
-    SendError(TrySendError<BinanceMessage>),
+    /// Error when sending a message over the channel fails
+    SendError(Box<TrySendError<StreamMessage>>),
 
-    // Error when json data is incorrect
+    /// Error when json data is incorrect
     IncorrectJsonData,
+
+    /// Error when an HTTP request (e.g., fetching a depth snapshot) fails
+    HttpError(reqwest::Error),
+
+    /// Error when a diff depth event does not continue the local book's update sequence
+    SequenceGap(String),
+
+    /// Error when a depth snapshot is older than the buffered diff events
+    SnapshotOutdated(String),
+
+    /// Error when the best bid is at or above the best ask
+    CrossedBook(String),
+
+    /// Error when a symbol is not listed in the symbol registry
+    UnknownSymbol(String),
+
+    /// Error when an update carries prices that are not multiples of the symbol's tick size
+    OffTickPrice(String),
+
+    /// Error when an update carries quantities that are not multiples of the symbol's step size
+    OffStepQty(String),
+
+    /// Error when a SUBSCRIBE/UNSUBSCRIBE request cannot be sent to the stream client
+    SubscriptionError(String),
+
+    /// Error when no depth snapshot is available (e.g., a recording without recorded snapshots)
+    SnapshotUnavailable(String),
+
+    /// Error when a kline does not match the candle source or interval of the book
+    CandleMismatch(String),
+}
+
+impl OrderBookError {
+    /// Function to get the process exit code reported for the error
+    /// Codes start at 10, so they do not collide with 1 (generic failure) and 2 (usage error)
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            OrderBookError::IoError(_) => 10,
+            OrderBookError::JsonParseError(_) => 11,
+            OrderBookError::DifferentSymbol(_) => 12,
+            OrderBookError::UpdateIdOutdated(_) => 13,
+            OrderBookError::DuplicateUpdate(_) => 14,
+            OrderBookError::ParseError(_) => 15,
+            OrderBookError::ConnectionError(_) => 16,
+            OrderBookError::SendError(_) => 17,
+            OrderBookError::IncorrectJsonData => 18,
+            OrderBookError::HttpError(_) => 19,
+            OrderBookError::SequenceGap(_) => 20,
+            OrderBookError::SnapshotOutdated(_) => 21,
+            OrderBookError::CrossedBook(_) => 22,
+            OrderBookError::UnknownSymbol(_) => 23,
+            OrderBookError::OffTickPrice(_) => 24,
+            OrderBookError::SubscriptionError(_) => 25,
+            OrderBookError::SnapshotUnavailable(_) => 26,
+            OrderBookError::CandleMismatch(_) => 27,
+            OrderBookError::OffStepQty(_) => 28,
+        }
+    }
+
+    /// Function to get the name of the error variant (e.g., as a metrics label)
+    pub fn name(&self) -> &'static str {
+        match self {
+            OrderBookError::IoError(_) => "IoError",
+            OrderBookError::JsonParseError(_) => "JsonParseError",
+            OrderBookError::DifferentSymbol(_) => "DifferentSymbol",
+            OrderBookError::UpdateIdOutdated(_) => "UpdateIdOutdated",
+            OrderBookError::DuplicateUpdate(_) => "DuplicateUpdate",
+            OrderBookError::ParseError(_) => "ParseError",
+            OrderBookError::ConnectionError(_) => "ConnectionError",
+            OrderBookError::SendError(_) => "SendError",
+            OrderBookError::IncorrectJsonData => "IncorrectJsonData",
+            OrderBookError::HttpError(_) => "HttpError",
+            OrderBookError::SequenceGap(_) => "SequenceGap",
+            OrderBookError::SnapshotOutdated(_) => "SnapshotOutdated",
+            OrderBookError::CrossedBook(_) => "CrossedBook",
+            OrderBookError::UnknownSymbol(_) => "UnknownSymbol",
+            OrderBookError::OffTickPrice(_) => "OffTickPrice",
+            OrderBookError::OffStepQty(_) => "OffStepQty",
+            OrderBookError::SubscriptionError(_) => "SubscriptionError",
+            OrderBookError::SnapshotUnavailable(_) => "SnapshotUnavailable",
+            OrderBookError::CandleMismatch(_) => "CandleMismatch",
+        }
+    }
 }
 
 /// Implement the `Display` trait for the `OrderBookError` enum
@@ -45,6 +131,9 @@ impl fmt::Display for OrderBookError {
             // Custom message when the `lastUpdateId` is outdated
             OrderBookError::UpdateIdOutdated(e) => write!(f, "lastUpdateId is outdated: {}", e),
 
+            // Custom message when the same update is received twice
+            OrderBookError::DuplicateUpdate(e) => write!(f, "Duplicate update: {}", e),
+
             // Generic parse error with a custom message
             OrderBookError::ParseError(e) => write!(f, "Parse error: {}", e),
 
@@ -56,10 +145,43 @@ impl fmt::Display for OrderBookError {
 
             // Error when a json data is incorrect
             OrderBookError::IncorrectJsonData => write!(f, "Json data is incorrect!"),
+
+            // Error when an HTTP request fails
+            OrderBookError::HttpError(e) => write!(f, "HTTP error: {}", e),
+
+            // Custom message when a gap is detected in the diff depth stream
+            OrderBookError::SequenceGap(e) => write!(f, "Sequence gap: {}", e),
+
+            // Custom message when a depth snapshot is too old to bridge the buffered events
+            OrderBookError::SnapshotOutdated(e) => write!(f, "Snapshot is outdated: {}", e),
+
+            // Custom message when the book is crossed
+            OrderBookError::CrossedBook(e) => write!(f, "Order book is crossed: {}", e),
+
+            // Custom message when the symbol is unknown
+            OrderBookError::UnknownSymbol(e) => write!(f, "Unknown symbol: {}", e),
+
+            // Custom message when an update has off-tick prices
+            OrderBookError::OffTickPrice(e) => write!(f, "Off-tick price: {}", e),
+
+            // Custom message when an update has off-step quantities
+            OrderBookError::OffStepQty(e) => write!(f, "Off-step quantity: {}", e),
+
+            // Custom message when a subscription change cannot be requested
+            OrderBookError::SubscriptionError(e) => write!(f, "Subscription error: {}", e),
+
+            // Custom message when no depth snapshot can be served
+            OrderBookError::SnapshotUnavailable(e) => write!(f, "Snapshot unavailable: {}", e),
+
+            // Custom message when a kline cannot be added to the candles of the book
+            OrderBookError::CandleMismatch(e) => write!(f, "Candle mismatch: {}", e),
         }
     }
 }
 
+/// Implement the `Error` trait so `OrderBookError` works with error handling libraries (e.g., clap value parsers)
+impl std::error::Error for OrderBookError {}
+
 /// Implement `From` trait for automatic conversion from `std::io::Error` to `OrderBookError::IoError`
 /// This allows using the `?` operator in functions that return `Result<(), OrderBookError>`
 impl From<std::io::Error> for OrderBookError {
@@ -81,15 +203,23 @@ impl From<SerdeError> for OrderBookError {
 impl From<tungstenite::Error> for OrderBookError {
     fn from(error: tungstenite::Error) -> Self {
         // Convert `tungstenite::Error` into `OrderBookError::ConnectionError`
-        OrderBookError::ConnectionError(error)
+        OrderBookError::ConnectionError(Box::new(error))
     }
 }
 
 /// Implement `From` for converting channel send errors into `OrderBookError::SendError`
-/// This allows converting `TrySendError<BinanceMessage>` into our custom error
-impl From<TrySendError<BinanceMessage>> for OrderBookError {
-    fn from(error: TrySendError<BinanceMessage>) -> Self {
+/// This allows converting `TrySendError<StreamMessage>` into our custom error
+impl From<TrySendError<StreamMessage>> for OrderBookError {
+    fn from(error: TrySendError<StreamMessage>) -> Self {
         // Convert `TrySendError` into `OrderBookError::SendError`
-        OrderBookError::SendError(error)
+        OrderBookError::SendError(Box::new(error))
+    }
+}
+
+/// Implement `From` for converting HTTP client errors into `OrderBookError::HttpError`
+impl From<reqwest::Error> for OrderBookError {
+    fn from(error: reqwest::Error) -> Self {
+        // Convert `reqwest::Error` into `OrderBookError::HttpError`
+        OrderBookError::HttpError(error)
     }
 }
@@ -0,0 +1,604 @@
+use super::*;
+
+// Number of events buffered per client; clients further behind miss events and see a sequence gap
+const EVENT_CAPACITY: usize = 4096;
+
+// Number of levels per side published on the depth channel, the most a client can subscribe to
+const FANOUT_DEPTH: usize = 100;
+
+// Number of levels per side sent on the depth channel when the subscription has no `depth`
+const DEFAULT_FANOUT_DEPTH: usize = 10;
+
+/// Enum representing the channels a fan-out client can subscribe to for every symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FanoutChannel {
+    /// Best bid and ask, sent when either changes
+    Bbo,
+
+    /// Top levels per side, sent when any of the subscribed levels changes
+    Depth,
+
+    /// Changed top levels since the previous diff, preceded by a snapshot of the top levels
+    Diff,
+}
+
+impl fmt::Display for FanoutChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FanoutChannel::Bbo => write!(f, "bbo"),
+            FanoutChannel::Depth => write!(f, "depth"),
+            FanoutChannel::Diff => write!(f, "diff"),
+        }
+    }
+}
+
+/// Enum representing a normalized book update published by [`BookPublisher`]
+/// Every channel of a symbol has its own sequence number, incremented by one per published event,
+/// so a client detects missed events by a jump in `seq`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum BookEvent {
+    /// The best bid or ask changed (or the book became stale or resynchronized)
+    Bbo {
+        /// Symbol of the book (e.g., BNBUSDT)
+        symbol: String,
+        /// Sequence number of the bbo channel
+        seq: u64,
+        /// Whether the book may be outdated after a stream interruption
+        stale: bool,
+        /// Best bid, None while the side is empty
+        bid: Option<PriceLevel>,
+        /// Best ask, None while the side is empty
+        ask: Option<PriceLevel>,
+    },
+
+    /// The top levels changed; every event holds the complete top of the book
+    Depth {
+        /// Symbol of the book (e.g., BNBUSDT)
+        symbol: String,
+        /// Sequence number of the depth channel
+        seq: u64,
+        /// Whether the book may be outdated after a stream interruption
+        stale: bool,
+        /// Bid levels, best (highest) first
+        bids: Vec<PriceLevel>,
+        /// Ask levels, best (lowest) first
+        asks: Vec<PriceLevel>,
+    },
+
+    /// Top levels changed since diff `seq - 1`; a zero quantity removes the level (or it left the top levels)
+    Diff {
+        /// Symbol of the book (e.g., BNBUSDT)
+        symbol: String,
+        /// Sequence number of the diff channel
+        seq: u64,
+        /// Changed bid levels, best (highest) first
+        bids: Vec<PriceLevel>,
+        /// Changed ask levels, best (lowest) first
+        asks: Vec<PriceLevel>,
+    },
+
+    /// The top levels of the book as of diff `seq`, sent when subscribing to the diff channel
+    Snapshot {
+        /// Symbol of the book (e.g., BNBUSDT)
+        symbol: String,
+        /// Sequence number of the last diff included in the snapshot
+        seq: u64,
+        /// Bid levels, best (highest) first
+        bids: Vec<PriceLevel>,
+        /// Ask levels, best (lowest) first
+        asks: Vec<PriceLevel>,
+    },
+}
+
+impl BookEvent {
+    /// Function to get the symbol of the book the event belongs to
+    pub fn symbol(&self) -> &str {
+        match self {
+            BookEvent::Bbo { symbol, .. }
+            | BookEvent::Depth { symbol, .. }
+            | BookEvent::Diff { symbol, .. }
+            | BookEvent::Snapshot { symbol, .. } => symbol,
+        }
+    }
+
+    /// Function to get the sequence number of the event within its channel
+    pub fn seq(&self) -> u64 {
+        match self {
+            BookEvent::Bbo { seq, .. }
+            | BookEvent::Depth { seq, .. }
+            | BookEvent::Diff { seq, .. }
+            | BookEvent::Snapshot { seq, .. } => *seq,
+        }
+    }
+
+    /// Function to get the channel the event is sent on
+    pub fn channel(&self) -> FanoutChannel {
+        match self {
+            BookEvent::Bbo { .. } => FanoutChannel::Bbo,
+            BookEvent::Depth { .. } => FanoutChannel::Depth,
+            BookEvent::Diff { .. } | BookEvent::Snapshot { .. } => FanoutChannel::Diff,
+        }
+    }
+}
+
+/// Struct holding what was last published for a symbol, the base of the next events
+#[derive(Debug, Default)]
+struct PublishedBook {
+    // Sequence numbers of the last event per channel
+    bbo_seq: u64,
+    depth_seq: u64,
+    diff_seq: u64,
+
+    // Staleness of the book in the last bbo and depth events
+    stale: bool,
+
+    // Best bid and ask of the last bbo event
+    bbo: (Option<PriceLevel>, Option<PriceLevel>),
+
+    // Top levels of the last depth event, also the levels as of the last diff event
+    depth: (Vec<PriceLevel>, Vec<PriceLevel>),
+}
+
+/// Struct holding the published levels of a book, copied while the book is locked
+#[derive(Debug)]
+struct BookView {
+    // Whether the book may be outdated after a stream interruption
+    stale: bool,
+
+    // Top levels per side, best first
+    bids: Vec<PriceLevel>,
+    asks: Vec<PriceLevel>,
+}
+
+impl BookView {
+    // Constructor function to copy the top levels of a book
+    fn new(orderbook: &OrderBook) -> Self {
+        Self {
+            stale: orderbook.is_stale(),
+            bids: price_levels(orderbook.visible_bids().rev().take(FANOUT_DEPTH)),
+            asks: price_levels(orderbook.visible_asks().take(FANOUT_DEPTH)),
+        }
+    }
+}
+
+/// Struct publishing normalized updates of the books to any number of subscribers
+/// [`BookManager`] publishes every book after each applied message once [`BookManager::enable_publisher`] was called.
+/// Publishing only copies the top levels of the book; the events are built and sent by [`BookPublisher::run`]
+/// (driven by [`serve_fanout`]) outside the lock of the manager, merging the views published in the meantime.
+/// Nothing is computed while there are no subscribers; the next event is then derived from the last published state,
+/// so the sequence numbers stay contiguous.
+#[derive(Debug)]
+pub struct BookPublisher {
+    // Broadcast channel of the published events
+    events: broadcast::Sender<Arc<BookEvent>>,
+
+    // Last published state per symbol
+    books: Mutex<HashMap<String, PublishedBook>>,
+
+    // Latest view per symbol not yet turned into events, always locked after `books`
+    pending: Mutex<HashMap<String, BookView>>,
+
+    // Wakes up the task sending the pending views
+    notify: Notify,
+}
+
+impl Default for BookPublisher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BookPublisher {
+    /// Constructor function to create a publisher without subscribers
+    pub fn new() -> Self {
+        Self {
+            events: broadcast::channel(EVENT_CAPACITY).0,
+            books: Mutex::new(HashMap::new()),
+            pending: Mutex::new(HashMap::new()),
+            notify: Notify::new(),
+        }
+    }
+
+    /// Function to subscribe to the events of every symbol and channel
+    /// A receiver lagging more than the channel capacity misses events (`RecvError::Lagged`).
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<BookEvent>> {
+        self.events.subscribe()
+    }
+
+    /// Function to queue the top levels of a book, sent by [`BookPublisher::run`] for the channels that changed
+    pub fn publish(&self, orderbook: &OrderBook) {
+        if self.events.receiver_count() == 0 {
+            return;
+        }
+        let view = BookView::new(orderbook);
+        let mut pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+        pending.insert(orderbook.symbol().to_string(), view);
+        self.notify.notify_one();
+    }
+
+    /// Function to send the events of the queued views until the task is dropped or aborted
+    pub async fn run(&self) {
+        loop {
+            self.notify.notified().await;
+            let mut books = self.books.lock().unwrap_or_else(PoisonError::into_inner);
+            let pending =
+                std::mem::take(&mut *self.pending.lock().unwrap_or_else(PoisonError::into_inner));
+            for (symbol, view) in pending {
+                let published = books.entry(symbol.clone()).or_default();
+                self.update(&symbol, published, view);
+            }
+        }
+    }
+
+    /// Function to get the current state of a channel, publishing any pending change first
+    /// Events received afterwards with a `seq` not above the returned one are already included in it.
+    pub fn snapshot(&self, orderbook: &OrderBook, channel: FanoutChannel) -> BookEvent {
+        let symbol = orderbook.symbol().to_string();
+        let mut books = self.books.lock().unwrap_or_else(PoisonError::into_inner);
+        // A queued view is older than the book, so it is replaced rather than sent later
+        self.pending
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&symbol);
+        let published = books.entry(symbol.clone()).or_default();
+        self.update(&symbol, published, BookView::new(orderbook));
+
+        match channel {
+            FanoutChannel::Bbo => BookEvent::Bbo {
+                symbol,
+                seq: published.bbo_seq,
+                stale: published.stale,
+                bid: published.bbo.0,
+                ask: published.bbo.1,
+            },
+            FanoutChannel::Depth => BookEvent::Depth {
+                symbol,
+                seq: published.depth_seq,
+                stale: published.stale,
+                bids: published.depth.0.clone(),
+                asks: published.depth.1.clone(),
+            },
+            FanoutChannel::Diff => BookEvent::Snapshot {
+                symbol,
+                seq: published.diff_seq,
+                bids: published.depth.0.clone(),
+                asks: published.depth.1.clone(),
+            },
+        }
+    }
+
+    /// Function to forget the published state of a symbol that is no longer managed
+    pub fn remove(&self, symbol: &str) {
+        let symbol = symbol.to_uppercase();
+        let mut books = self.books.lock().unwrap_or_else(PoisonError::into_inner);
+        books.remove(&symbol);
+        self.pending
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&symbol);
+    }
+
+    // Function to publish the changes of a view since the published state and record them in it
+    fn update(&self, symbol: &str, published: &mut PublishedBook, view: BookView) {
+        let BookView { stale, bids, asks } = view;
+
+        let mut bid_changes = diff_levels(&published.depth.0, &bids);
+        bid_changes.reverse();
+        let ask_changes = diff_levels(&published.depth.1, &asks);
+        if !bid_changes.is_empty() || !ask_changes.is_empty() {
+            published.diff_seq += 1;
+            self.send(BookEvent::Diff {
+                symbol: symbol.to_string(),
+                seq: published.diff_seq,
+                bids: bid_changes,
+                asks: ask_changes,
+            });
+        }
+
+        let bbo = (bids.first().copied(), asks.first().copied());
+        if bbo != published.bbo || stale != published.stale {
+            published.bbo_seq += 1;
+            published.bbo = bbo;
+            self.send(BookEvent::Bbo {
+                symbol: symbol.to_string(),
+                seq: published.bbo_seq,
+                stale,
+                bid: bbo.0,
+                ask: bbo.1,
+            });
+        }
+
+        let depth = (bids, asks);
+        if depth != published.depth || stale != published.stale {
+            published.depth_seq += 1;
+            self.send(BookEvent::Depth {
+                symbol: symbol.to_string(),
+                seq: published.depth_seq,
+                stale,
+                bids: depth.0.clone(),
+                asks: depth.1.clone(),
+            });
+            published.depth = depth;
+        }
+
+        published.stale = stale;
+    }
+
+    // Function to send an event to the subscribers; it is dropped if there are none
+    fn send(&self, event: BookEvent) {
+        let _ = self.events.send(Arc::new(event));
+    }
+}
+
+// Function to convert (price, quantity) pairs into price levels
+fn price_levels<'a>(levels: impl Iterator<Item = (&'a Price, &'a Qty)>) -> Vec<PriceLevel> {
+    levels
+        .map(|(&price, &qty)| PriceLevel { price, qty })
+        .collect()
+}
+
+// Function to get the levels that differ between two states of the top of a side, lowest price first
+// Levels missing from `current` are reported with a zero quantity.
+fn diff_levels(previous: &[PriceLevel], current: &[PriceLevel]) -> Vec<PriceLevel> {
+    let previous: BTreeMap<Price, Qty> = previous.iter().map(|l| (l.price, l.qty)).collect();
+    let current: BTreeMap<Price, Qty> = current.iter().map(|l| (l.price, l.qty)).collect();
+    let mut changes: Vec<PriceLevel> = current
+        .iter()
+        .filter(|(price, qty)| previous.get(price) != Some(qty))
+        .map(|(&price, &qty)| PriceLevel { price, qty })
+        .collect();
+    changes.extend(
+        previous
+            .keys()
+            .filter(|price| !current.contains_key(price))
+            .map(|&price| PriceLevel {
+                price,
+                qty: Qty::ZERO,
+            }),
+    );
+    changes.sort_by_key(|level| level.price);
+    changes
+}
+
+/// Function to serve the fan-out WebSocket on the listener until the task is dropped or aborted
+/// Clients send `{"op":"subscribe","symbol":"bnbusdt","channel":"bbo"|"depth"|"diff","depth":10}`
+/// (or `"op":"unsubscribe"`) and receive the current state of the channel followed by every [`BookEvent`].
+pub async fn serve_fanout(
+    listener: TcpListener,             // The bound listener (e.g., 127.0.0.1:8081)
+    manager: Arc<RwLock<BookManager>>, // A shared, thread-safe reference to the book manager
+) -> Result<(), OrderBookError> {
+    let publisher = manager.write().await.enable_publisher();
+    let events = Arc::clone(&publisher);
+    tokio::select! {
+        () = events.run() => Ok(()),
+        result = accept_fanout_clients(listener, manager, publisher) => result,
+    }
+}
+
+// Function to serve every client connecting to the listener
+async fn accept_fanout_clients(
+    listener: TcpListener,
+    manager: Arc<RwLock<BookManager>>,
+    publisher: Arc<BookPublisher>,
+) -> Result<(), OrderBookError> {
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let manager = Arc::clone(&manager);
+        let publisher = Arc::clone(&publisher);
+        tokio::spawn(async move {
+            match serve_fanout_client(stream, manager, publisher).await {
+                Ok(()) => log::debug!("Fan-out client {} disconnected", peer),
+                Err(e) => log::debug!("Fan-out client {} disconnected: {}", peer, e),
+            }
+        });
+    }
+}
+
+/// Enum representing a request of a fan-out client
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+enum FanoutRequest {
+    // Start receiving a channel of a symbol
+    Subscribe {
+        symbol: String,
+        channel: FanoutChannel,
+        depth: Option<usize>,
+    },
+
+    // Stop receiving a channel of a symbol
+    Unsubscribe {
+        symbol: String,
+        channel: FanoutChannel,
+    },
+}
+
+/// Enum representing a reply to a fan-out client that is not a book event
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum FanoutNotice {
+    // The subscription is active; the current state of the channel follows
+    Subscribed {
+        symbol: String,
+        channel: FanoutChannel,
+    },
+
+    // The subscription was removed
+    Unsubscribed {
+        symbol: String,
+        channel: FanoutChannel,
+    },
+
+    // The request was rejected
+    Error {
+        message: String,
+    },
+
+    // The client fell behind and events were dropped
+    Lagged {
+        missed: u64,
+    },
+}
+
+/// Struct representing an active subscription of a fan-out client
+struct FanoutSubscription {
+    // Levels per side sent on the depth channel
+    depth: usize,
+
+    // Sequence number already covered by the state sent on subscribing
+    sent_seq: u64,
+
+    // Depth event last sent, cut to `depth`; events with the same levels are not sent again
+    sent_depth: Option<BookEvent>,
+}
+
+// Function to exchange requests and events with one client until it disconnects
+async fn serve_fanout_client(
+    stream: TcpStream,
+    manager: Arc<RwLock<BookManager>>,
+    publisher: Arc<BookPublisher>,
+) -> Result<(), OrderBookError> {
+    let mut socket = tokio_tungstenite::accept_async(stream).await?;
+    let mut events = publisher.subscribe();
+    let mut subscriptions: HashMap<(String, FanoutChannel), FanoutSubscription> = HashMap::new();
+
+    loop {
+        tokio::select! {
+            frame = socket.next() => {
+                let text = match frame {
+                    Some(frame) => match frame? {
+                        Message::Text(text) => text,
+                        Message::Close(_) => return Ok(()),
+                        _ => continue,
+                    },
+                    None => return Ok(()),
+                };
+                let replies = match serde_json::from_str(&text) {
+                    Ok(request) => handle_fanout_request(request, &manager, &publisher, &mut subscriptions).await,
+                    Err(e) => vec![serde_json::to_string(&FanoutNotice::Error { message: e.to_string() })?],
+                };
+                for reply in replies {
+                    socket.send(Message::text(reply)).await?;
+                }
+            }
+            event = events.recv() => {
+                match event {
+                    Ok(event) => {
+                        let key = (event.symbol().to_string(), event.channel());
+                        let Some(subscription) = subscriptions.get_mut(&key) else {
+                            continue;
+                        };
+                        if event.seq() <= subscription.sent_seq {
+                            continue;
+                        }
+                        let event = with_depth(&event, subscription.depth);
+                        if let BookEvent::Depth { .. } = event.as_ref() {
+                            // Changes below the subscribed depth leave the client's levels as they were
+                            if subscription.sent_depth.as_ref().is_some_and(|sent| same_levels(sent, &event)) {
+                                continue;
+                            }
+                            subscription.sent_depth = Some(event.clone().into_owned());
+                        }
+                        let json = serde_json::to_string(&event)?;
+                        socket.send(Message::text(json)).await?;
+                    }
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        let notice = serde_json::to_string(&FanoutNotice::Lagged { missed })?;
+                        socket.send(Message::text(notice)).await?;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                }
+            }
+        }
+    }
+}
+
+// Function to apply a request of a client, returning the JSON replies to send
+async fn handle_fanout_request(
+    request: FanoutRequest,
+    manager: &RwLock<BookManager>,
+    publisher: &BookPublisher,
+    subscriptions: &mut HashMap<(String, FanoutChannel), FanoutSubscription>,
+) -> Vec<String> {
+    let replies = match request {
+        FanoutRequest::Subscribe {
+            symbol,
+            channel,
+            depth,
+        } => {
+            let symbol = symbol.trim().to_uppercase();
+            let manager = manager.read().await;
+            match manager.book(&symbol) {
+                Some(orderbook) => {
+                    // The snapshot is taken under the read lock, so no event can be published in between
+                    let depth = depth.unwrap_or(DEFAULT_FANOUT_DEPTH).clamp(1, FANOUT_DEPTH);
+                    let state = publisher.snapshot(orderbook, channel);
+                    let subscription = FanoutSubscription {
+                        depth,
+                        sent_seq: state.seq(),
+                        sent_depth: (channel == FanoutChannel::Depth)
+                            .then(|| with_depth(&state, depth).into_owned()),
+                    };
+                    subscriptions.insert((symbol.clone(), channel), subscription);
+                    vec![
+                        serde_json::to_string(&FanoutNotice::Subscribed { symbol, channel }),
+                        serde_json::to_string(&with_depth(&state, depth)),
+                    ]
+                }
+                None => vec![serde_json::to_string(&FanoutNotice::Error {
+                    message: OrderBookError::UnknownSymbol(symbol).to_string(),
+                })],
+            }
+        }
+        FanoutRequest::Unsubscribe { symbol, channel } => {
+            let symbol = symbol.trim().to_uppercase();
+            let notice = match subscriptions.remove(&(symbol.clone(), channel)) {
+                Some(_) => FanoutNotice::Unsubscribed { symbol, channel },
+                None => FanoutNotice::Error {
+                    message: format!("not subscribed to {} {}", symbol, channel),
+                },
+            };
+            vec![serde_json::to_string(&notice)]
+        }
+    };
+    replies.into_iter().filter_map(Result::ok).collect()
+}
+
+// Function to cut a depth event down to the levels per side of a subscription
+fn with_depth(event: &BookEvent, depth: usize) -> Cow<'_, BookEvent> {
+    match event {
+        BookEvent::Depth {
+            symbol,
+            seq,
+            stale,
+            bids,
+            asks,
+        } if bids.len() > depth || asks.len() > depth => Cow::Owned(BookEvent::Depth {
+            symbol: symbol.clone(),
+            seq: *seq,
+            stale: *stale,
+            bids: bids[..bids.len().min(depth)].to_vec(),
+            asks: asks[..asks.len().min(depth)].to_vec(),
+        }),
+        _ => Cow::Borrowed(event),
+    }
+}
+
+// Function to check whether two depth events hold the same levels and staleness
+fn same_levels(sent: &BookEvent, event: &BookEvent) -> bool {
+    match (sent, event) {
+        (
+            BookEvent::Depth {
+                stale, bids, asks, ..
+            },
+            BookEvent::Depth {
+                stale: next_stale,
+                bids: next_bids,
+                asks: next_asks,
+                ..
+            },
+        ) => stale == next_stale && bids == next_bids && asks == next_asks,
+        _ => false,
+    }
+}
@@ -0,0 +1,465 @@
+use super::*;
+
+/// Enum representing a normalized change to an order book, independent of the venue it came from
+#[derive(Debug)]
+pub enum FeedUpdate {
+    /// Complete book; replaces every level and (re)synchronizes the diff sequence
+    Snapshot(DepthUpdate),
+
+    /// Complete picture of the top levels (e.g., Binance partial depth)
+    TopLevels(DepthUpdate),
+
+    /// Changed levels with absolute quantities (quantity 0 removes the level)
+    Diff {
+        /// The changed levels and their update IDs
+        diff: DepthDiff,
+        /// Levels beyond this many per side are dropped after the update, for venues that leave it to the client
+        max_depth: Option<usize>,
+    },
+
+    /// Best bid and ask
+    BestBidAsk(BookTickerUpdate),
+
+    /// Executed trade, sequenced within the given stream
+    Trade(StreamKind, Trade),
+}
+
+impl FeedUpdate {
+    /// Function to get the type of the update (e.g., as a metrics label)
+    pub fn kind(&self) -> &'static str {
+        match self {
+            FeedUpdate::Snapshot(_) => "snapshot",
+            FeedUpdate::TopLevels(_) => "depth",
+            FeedUpdate::Diff { .. } => "depth_diff",
+            FeedUpdate::BestBidAsk(_) => "book_ticker",
+            FeedUpdate::Trade(StreamKind::AggTrade, _) => "agg_trade",
+            FeedUpdate::Trade(..) => "trade",
+        }
+    }
+}
+
+/// Struct representing a normalized book event of one symbol, as decoded by a [`MarketDataFeed`]
+#[derive(Debug)]
+pub struct FeedEvent {
+    /// Venue the event was received from
+    pub venue: Venue,
+
+    /// Symbol of the book, normalized by [`MarketDataFeed::book_symbol`] (e.g., BTCUSD for BTC-USD)
+    pub symbol: String,
+
+    /// Exchange event time in milliseconds, if the venue sends one
+    pub event_time: Option<u64>,
+
+    /// The change to the book
+    pub update: FeedUpdate,
+}
+
+/// Trait for adapters turning the public book stream of a venue into normalized [`FeedEvent`]s
+/// An adapter knows the endpoint, the subscription requests and the message formats of its venue.
+/// Decoding only keeps the state the format needs (e.g., sequence numbers), so recorded frames can
+/// be decoded without a connection, and every event is applied by the same [`apply_feed_event`].
+pub trait MarketDataFeed {
+    /// Function to get the venue of the feed
+    fn venue(&self) -> Venue;
+
+    /// Function to get the WebSocket URL of the feed
+    fn url(&self) -> String;
+
+    /// Function to build the text frames subscribing to the books of the given venue symbols
+    fn subscribe_frames(&self, symbols: &[String]) -> Vec<String>;
+
+    /// Function to decode a text frame into book events; frames without book data yield none
+    /// Any error means a book message was lost (`SequenceGap`) or could not be decoded, so the
+    /// connection must be re-established for the books to resync.
+    fn decode(&mut self, frame: &str) -> Result<Vec<FeedEvent>, OrderBookError>;
+
+    /// Function to forget the decoding state before a new connection is established
+    fn reset(&mut self) {}
+
+    /// Function to get the book symbol of a venue symbol: uppercase, without separators (BTC/USD -> BTCUSD)
+    fn book_symbol(&self, symbol: &str) -> String {
+        symbol
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .collect::<String>()
+            .to_uppercase()
+    }
+}
+
+/// Struct representing the adapter of the Binance combined stream (book ticker, depth and trade streams)
+/// Kline and futures events have no venue-neutral form and are not decoded.
+#[derive(Debug, Clone)]
+pub struct BinanceFeed {
+    // Market of the streams
+    market: Market,
+
+    // Depth stream subscribed for every symbol
+    depth_mode: DepthMode,
+}
+
+impl BinanceFeed {
+    /// Constructor function to create a new BinanceFeed
+    pub fn new(market: Market, depth_mode: DepthMode) -> Self {
+        Self { market, depth_mode }
+    }
+}
+
+impl MarketDataFeed for BinanceFeed {
+    fn venue(&self) -> Venue {
+        Venue::Binance
+    }
+
+    fn url(&self) -> String {
+        format!("{}/stream", self.market.stream_url())
+    }
+
+    fn subscribe_frames(&self, symbols: &[String]) -> Vec<String> {
+        let streams = symbols
+            .iter()
+            .flat_map(|symbol| {
+                [
+                    format!("{}@bookTicker", symbol.to_lowercase()),
+                    self.depth_mode.stream_name(symbol),
+                ]
+            })
+            .collect();
+        vec![StreamCommand::Subscribe(streams).to_json(1)]
+    }
+
+    fn decode(&mut self, frame: &str) -> Result<Vec<FeedEvent>, OrderBookError> {
+        // Subscription responses and unsupported messages carry no book data; book streams always do
+        let Some(message) = parse_stream_message(frame) else {
+            return match serde_json::from_str::<CombinedStreamReader>(frame) {
+                Ok(envelope) if is_book_stream(&envelope.stream) => {
+                    Err(OrderBookError::ParseError(format!(
+                        "Error parsing {} message",
+                        envelope.stream
+                    )))
+                }
+                _ => Ok(Vec::new()),
+            };
+        };
+        let symbol = message.symbol();
+        let event_time = message.message.event_time();
+
+        let update = match message.message {
+            BinanceMessage::BookTicker(reader) => {
+                FeedUpdate::BestBidAsk(BookTickerUpdate::from_reader(reader)?)
+            }
+            BinanceMessage::DepthUpdate(reader) => {
+                FeedUpdate::TopLevels(DepthUpdate::from_reader(reader)?)
+            }
+            BinanceMessage::DepthDiff(reader) => FeedUpdate::Diff {
+                diff: DepthDiff::from_reader(reader)?,
+                max_depth: None,
+            },
+            BinanceMessage::Trade(reader) => {
+                FeedUpdate::Trade(StreamKind::Trade, Trade::from_reader(reader)?)
+            }
+            BinanceMessage::AggTrade(reader) => {
+                FeedUpdate::Trade(StreamKind::AggTrade, Trade::from_agg_reader(reader)?)
+            }
+            BinanceMessage::Kline(_)
+            | BinanceMessage::MarkPrice(_)
+            | BinanceMessage::ForceOrder(_) => return Ok(Vec::new()),
+        };
+
+        Ok(vec![FeedEvent {
+            venue: Venue::Binance,
+            symbol,
+            event_time,
+            update,
+        }])
+    }
+}
+
+/// Struct representing the adapter of the Coinbase Advanced Trade `level2` channel
+/// The channel has no per-product update IDs: levels are numbered by the adapter in arrival order,
+/// and the connection-wide `sequence_num` is checked instead, so lost messages end the connection.
+#[derive(Debug, Clone, Default)]
+pub struct CoinbaseFeed {
+    // Sequence number of the last message on the connection
+    last_sequence: Option<u64>,
+
+    // Update ID assigned to the last event of every book
+    update_ids: HashMap<String, u64>,
+}
+
+impl CoinbaseFeed {
+    /// Constructor function to create a new CoinbaseFeed
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl MarketDataFeed for CoinbaseFeed {
+    fn venue(&self) -> Venue {
+        Venue::Coinbase
+    }
+
+    fn url(&self) -> String {
+        "wss://advanced-trade-ws.coinbase.com".to_string()
+    }
+
+    fn subscribe_frames(&self, symbols: &[String]) -> Vec<String> {
+        // Heartbeats keep the connection open while the books are quiet
+        ["level2", "heartbeats"]
+            .into_iter()
+            .map(|channel| {
+                serde_json::json!({
+                    "type": "subscribe",
+                    "product_ids": symbols,
+                    "channel": channel,
+                })
+                .to_string()
+            })
+            .collect()
+    }
+
+    fn decode(&mut self, frame: &str) -> Result<Vec<FeedEvent>, OrderBookError> {
+        let message = serde_json::from_str::<CoinbaseMessageReader>(frame)?;
+
+        // The sequence number counts the messages of every channel on the connection
+        if let Some(sequence) = message.sequence_num {
+            let last = self.last_sequence.replace(sequence);
+            if let Some(last) = last.filter(|&last| sequence != last + 1) {
+                return Err(OrderBookError::SequenceGap(format!(
+                    "expected Coinbase message {}, found {}",
+                    last + 1,
+                    sequence
+                )));
+            }
+        }
+        let (Some(events), "l2_data") = (message.events, message.channel.as_str()) else {
+            return Ok(Vec::new());
+        };
+        let event_time = parse_rfc3339_millis(&message.timestamp).ok();
+
+        let events = serde_json::from_str::<Vec<CoinbaseLevel2Reader>>(events.get())?;
+        let mut decoded = Vec::with_capacity(events.len());
+        for event in events {
+            let symbol = self.book_symbol(&event.product_id);
+            let mut bids = Vec::new();
+            let mut asks = Vec::new();
+            for level in event.updates {
+                let price = parse_decimal(&level.price_level, "price_level")?;
+                let qty = parse_decimal(&level.new_quantity, "new_quantity")?;
+                match level.side.as_str() {
+                    "bid" => bids.push((price, qty)),
+                    "offer" => asks.push((price, qty)),
+                    side => {
+                        return Err(OrderBookError::ParseError(format!(
+                            "Error parsing side: {}",
+                            side
+                        )))
+                    }
+                }
+            }
+
+            let update_id = self.update_ids.entry(symbol.clone()).or_default();
+            *update_id += 1;
+            let update = match event.kind.as_str() {
+                "snapshot" => FeedUpdate::Snapshot(DepthUpdate::new(*update_id, bids, asks)),
+                _ => FeedUpdate::Diff {
+                    diff: DepthDiff::new(*update_id, *update_id, None, bids, asks),
+                    max_depth: None,
+                },
+            };
+            decoded.push(FeedEvent {
+                venue: Venue::Coinbase,
+                symbol,
+                event_time,
+                update,
+            });
+        }
+        Ok(decoded)
+    }
+
+    fn reset(&mut self) {
+        // Sequence numbers start over on every connection
+        self.last_sequence = None;
+    }
+}
+
+/// Struct representing a reader for Coinbase Advanced Trade messages of any channel
+#[derive(Debug, Deserialize)]
+pub struct CoinbaseMessageReader {
+    /// Channel of the message (e.g., l2_data, heartbeats, subscriptions)
+    #[serde(default)]
+    pub channel: String,
+
+    /// Time the message was sent, in RFC 3339
+    #[serde(default)]
+    pub timestamp: String,
+
+    /// Sequence number of the message on the connection
+    pub sequence_num: Option<u64>,
+
+    /// Raw events of the message, whose format depends on the channel
+    pub events: Option<Box<RawValue>>,
+}
+
+/// Struct representing a reader for a `level2` event (snapshot or update of one product)
+#[derive(Debug, Deserialize)]
+pub struct CoinbaseLevel2Reader {
+    /// Event type, `snapshot` or `update`
+    #[serde(rename = "type")]
+    pub kind: String,
+
+    /// Product ID (e.g., BTC-USD)
+    pub product_id: String,
+
+    /// Changed levels
+    pub updates: Vec<CoinbaseLevelReader>,
+}
+
+/// Struct representing a reader for a changed `level2` level
+#[derive(Debug, Deserialize)]
+pub struct CoinbaseLevelReader {
+    /// Side of the level, `bid` or `offer`
+    pub side: String,
+
+    /// Price in string
+    pub price_level: String,
+
+    /// New quantity in string (0 removes the level)
+    pub new_quantity: String,
+}
+
+/// Struct representing the adapter of the Kraken WebSocket v2 `book` channel
+/// Like the `level2` channel, the book channel has no update IDs, so levels are numbered in arrival
+/// order. Updates can push levels out of the subscribed depth, which are dropped after every update.
+/// The CRC32 checksums of the messages are not verified.
+#[derive(Debug, Clone)]
+pub struct KrakenFeed {
+    // Number of levels per side subscribed to (10, 25, 100, 500 or 1000)
+    depth: usize,
+
+    // Update ID assigned to the last event of every book
+    update_ids: HashMap<String, u64>,
+}
+
+impl KrakenFeed {
+    /// Constructor function to create a new KrakenFeed with the given book depth (10, 25, 100, 500 or 1000)
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth,
+            update_ids: HashMap::new(),
+        }
+    }
+}
+
+impl MarketDataFeed for KrakenFeed {
+    fn venue(&self) -> Venue {
+        Venue::Kraken
+    }
+
+    fn url(&self) -> String {
+        "wss://ws.kraken.com/v2".to_string()
+    }
+
+    fn subscribe_frames(&self, symbols: &[String]) -> Vec<String> {
+        vec![serde_json::json!({
+            "method": "subscribe",
+            "params": { "channel": "book", "symbol": symbols, "depth": self.depth },
+        })
+        .to_string()]
+    }
+
+    fn decode(&mut self, frame: &str) -> Result<Vec<FeedEvent>, OrderBookError> {
+        let message = serde_json::from_str::<KrakenMessageReader>(frame)?;
+        // Heartbeats, status messages and subscription responses carry no book data
+        let (Some(data), "book") = (message.data, message.channel.as_str()) else {
+            return Ok(Vec::new());
+        };
+
+        let books = serde_json::from_str::<Vec<KrakenBookReader>>(data.get())?;
+        let mut decoded = Vec::with_capacity(books.len());
+        for book in books {
+            let symbol = self.book_symbol(&book.symbol);
+            let event_time = book
+                .timestamp
+                .as_deref()
+                .and_then(|timestamp| parse_rfc3339_millis(timestamp).ok());
+            let bids = Self::parse_levels(book.bids, "bids")?;
+            let asks = Self::parse_levels(book.asks, "asks")?;
+
+            let update_id = self.update_ids.entry(symbol.clone()).or_default();
+            *update_id += 1;
+            let update = match message.kind.as_str() {
+                "snapshot" => FeedUpdate::Snapshot(DepthUpdate::new(*update_id, bids, asks)),
+                _ => FeedUpdate::Diff {
+                    diff: DepthDiff::new(*update_id, *update_id, None, bids, asks),
+                    max_depth: Some(self.depth),
+                },
+            };
+            decoded.push(FeedEvent {
+                venue: Venue::Kraken,
+                symbol,
+                event_time,
+                update,
+            });
+        }
+        Ok(decoded)
+    }
+}
+
+impl KrakenFeed {
+    // Function to parse levels with JSON number prices and quantities (in exponent notation for small ones) into exact decimal tuples
+    fn parse_levels(
+        levels: Vec<KrakenLevelReader>,
+        name: &str,
+    ) -> Result<Vec<(Price, Qty)>, OrderBookError> {
+        levels
+            .into_iter()
+            .map(|level| {
+                Ok((
+                    parse_json_number(level.price.get(), name)?,
+                    parse_json_number(level.qty.get(), name)?,
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Struct representing a reader for Kraken WebSocket v2 messages of any channel
+#[derive(Debug, Deserialize)]
+pub struct KrakenMessageReader {
+    /// Channel of the message (e.g., book, heartbeat, status); absent on method responses
+    #[serde(default)]
+    pub channel: String,
+
+    /// Message type, `snapshot` or `update`
+    #[serde(rename = "type", default)]
+    pub kind: String,
+
+    /// Raw data of the message, whose format depends on the channel
+    pub data: Option<Box<RawValue>>,
+}
+
+/// Struct representing a reader for the book data of one symbol
+#[derive(Debug, Deserialize)]
+pub struct KrakenBookReader {
+    /// Symbol of the book (e.g., BTC/USD)
+    pub symbol: String,
+
+    /// Changed (or, in a snapshot, all) bid levels
+    pub bids: Vec<KrakenLevelReader>,
+
+    /// Changed (or, in a snapshot, all) ask levels
+    pub asks: Vec<KrakenLevelReader>,
+
+    /// Time of the update in RFC 3339; absent on snapshots
+    pub timestamp: Option<String>,
+}
+
+/// Struct representing a reader for a Kraken book level
+/// Prices and quantities are JSON numbers, kept as raw text so they parse into exact decimals
+#[derive(Debug, Deserialize)]
+pub struct KrakenLevelReader {
+    /// Price as a raw JSON number
+    pub price: Box<RawValue>,
+
+    /// Quantity as a raw JSON number (0 removes the level)
+    pub qty: Box<RawValue>,
+}
@@ -1,38 +1,187 @@
 use super::*;
 
-/// Function to display the best bid and ask prices from the orderbook
-pub fn display_best_bid_ask<F, T>(orderbook: &T, extract_fn: F)
-where
-    // `extract_fn` is a closure that takes a reference to the orderbook (`&T`)
-    // and returns an `Option` with a tuple of ((best_bid_price, best_bid_volume), (best_ask_price, best_ask_volume))
-    F: Fn(&T) -> Option<((f64, f64), (f64, f64))>,
-{
-    // Call the provided extraction function `extract_fn` to get the best bid and ask
-    let msg = if let Some((best_bid, best_ask)) = extract_fn(orderbook) {
-        // If bid/ask values are found, format the message with the bid and ask prices
-        format!("Best Bid: {:?}, Best Ask: {:?}\n\n", best_bid, best_ask)
+/// Function to parse a string into an exact `Decimal` and handle parsing errors
+pub fn parse_decimal(value: &str, name: &str) -> Result<Decimal, OrderBookError> {
+    // Attempt to parse the input string `value` into a fixed-point decimal
+    value.parse::<Decimal>().map_err(|e| {
+        // Error message includes the name of the field and the specific parsing error
+        OrderBookError::ParseError(format!("Error parsing {}: {}", name, e))
+    })
+}
+
+/// Function to parse the text of a JSON number, which may use exponent notation (e.g., 1.5e-05), into an exact `Decimal`
+pub fn parse_json_number(value: &str, name: &str) -> Result<Decimal, OrderBookError> {
+    let Some((mantissa, exponent)) = value.split_once(['e', 'E']) else {
+        return parse_decimal(value, name);
+    };
+    let invalid = || OrderBookError::ParseError(format!("Error parsing {}: {}", name, value));
+
+    // Move the decimal point of the mantissa by the exponent; larger shifts are out of range anyway
+    let exponent = exponent
+        .parse::<i64>()
+        .ok()
+        .filter(|exponent| exponent.abs() <= 64)
+        .ok_or_else(invalid)?;
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", mantissa),
+    };
+    let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{}{}", int_part, frac_part);
+    let point = int_part.len() as i64 + exponent;
+    let text = if point <= 0 {
+        format!("{}0.{}{}", sign, "0".repeat(-point as usize), digits)
+    } else if point as usize >= digits.len() {
+        format!(
+            "{}{}{}",
+            sign,
+            digits,
+            "0".repeat(point as usize - digits.len())
+        )
     } else {
-        // If no bid/ask values are found, return a message indicating the orderbook is empty
-        "Orderbook is empty.".to_string()
+        let (int_digits, frac_digits) = digits.split_at(point as usize);
+        format!("{}{}.{}", sign, int_digits, frac_digits)
     };
+    parse_decimal(&text, name)
+}
+
+/// Function to get the current time in milliseconds since the Unix epoch
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
 
-    // Print the message
-    println!("{}", msg.purple())
-}
-
-/// Function to parse a string into a `f64` and handle parsing errors
-pub fn parse_f64(value: &str, name: &str) -> Result<f64, OrderBookError> {
-    // Attempt to parse the input string `value` into a `f64` (floating-point number)
-    match value.parse::<f64>() {
-        // If parsing succeeds, return the parsed value
-        Ok(val) => Ok(val),
-        // If parsing fails, return an error wrapped in a custom `OrderBookError`
-        Err(e) => {
-            return Err(OrderBookError::ParseError(format!(
-                // Error message includes the name of the field and the specific parsing error
-                "Error parsing {}: {}",
-                name, e
-            )));
+/// Function to parse a list of `[price, quantity]` string pairs into exact decimal tuples
+pub fn parse_levels(
+    levels: Vec<[String; 2]>,
+    name: &str,
+) -> Result<Vec<(Price, Qty)>, OrderBookError> {
+    levels
+        .into_iter()
+        .map(|[price, qty]| Ok((parse_decimal(&price, name)?, parse_decimal(&qty, name)?)))
+        .collect()
+}
+
+/// Function to parse a raw text frame from Binance into a `BinanceMessage`
+/// Returns `None` if the text does not match any of the supported message formats
+pub fn parse_binance_message(text: &str) -> Option<BinanceMessage> {
+    // Try to parse the message as a `BookTickerUpdate`
+    if let Ok(book_ticker) = serde_json::from_str::<BookTickerUpdateReader>(text) {
+        Some(BinanceMessage::BookTicker(book_ticker))
+    }
+    // Try to parse the message as a diff depth `depthUpdate` event
+    else if let Ok(depth_diff) = serde_json::from_str::<DepthDiffReader>(text) {
+        Some(BinanceMessage::DepthDiff(depth_diff))
+    }
+    // Try to parse the message as a partial `DepthUpdate`
+    else if let Ok(depth_update) = serde_json::from_str::<DepthUpdateReader>(text) {
+        Some(BinanceMessage::DepthUpdate(depth_update))
+    }
+    // Try to parse the message as an `aggTrade` event
+    else if let Ok(agg_trade) = serde_json::from_str::<AggTradeReader>(text) {
+        Some(BinanceMessage::AggTrade(agg_trade))
+    }
+    // Try to parse the message as a `trade` event
+    else if let Ok(trade) = serde_json::from_str::<TradeReader>(text) {
+        Some(BinanceMessage::Trade(trade))
+    }
+    // Try to parse the message as a `kline` event
+    else if let Ok(kline) = serde_json::from_str::<KlineReader>(text) {
+        Some(BinanceMessage::Kline(kline))
+    }
+    // Try to parse the message as a futures `markPriceUpdate` event
+    else if let Ok(mark_price) = serde_json::from_str::<MarkPriceReader>(text) {
+        Some(BinanceMessage::MarkPrice(mark_price))
+    }
+    // Try to parse the message as a futures `forceOrder` event
+    else if let Ok(force_order) = serde_json::from_str::<ForceOrderReader>(text) {
+        Some(BinanceMessage::ForceOrder(force_order))
+    } else {
+        None
+    }
+}
+
+/// Function to parse a combined stream envelope into a stream-tagged `StreamMessage`
+/// Returns `None` for other frames (e.g., SUBSCRIBE responses) and unsupported payloads
+pub fn parse_stream_message(text: &str) -> Option<StreamMessage> {
+    let envelope = serde_json::from_str::<CombinedStreamReader>(text).ok()?;
+    let message = match parse_binance_message(envelope.data.get())? {
+        // Futures partial depth streams push `depthUpdate` payloads, complete pictures of the top levels
+        BinanceMessage::DepthDiff(diff) if is_partial_depth_stream(&envelope.stream) => {
+            BinanceMessage::DepthUpdate(diff.into_partial())
         }
+        message => message,
+    };
+    Some(StreamMessage {
+        stream: envelope.stream,
+        message,
+        recv_time: None,
+    })
+}
+
+/// Function to check whether a stream name is a book stream (book ticker, diff or partial depth)
+pub fn is_book_stream(stream: &str) -> bool {
+    matches!(stream.split('@').nth(1), Some(kind) if kind == "bookTicker" || kind.starts_with("depth"))
+}
+
+// Function to check whether a stream name is a partial book depth stream (e.g., btcusdt@depth20@100ms)
+fn is_partial_depth_stream(stream: &str) -> bool {
+    matches!(
+        stream.split('@').nth(1),
+        Some("depth5" | "depth10" | "depth20")
+    )
+}
+
+/// Function to parse an RFC 3339 UTC timestamp (e.g., 2023-02-09T20:32:50.714964855Z) into milliseconds
+/// since the Unix epoch; fractions beyond milliseconds are truncated
+pub fn parse_rfc3339_millis(value: &str) -> Result<u64, OrderBookError> {
+    let invalid = || OrderBookError::ParseError(format!("Error parsing timestamp: {}", value));
+    let (date, time) = value
+        .strip_suffix('Z')
+        .and_then(|value| value.split_once('T'))
+        .ok_or_else(invalid)?;
+    let field = |part: Option<&str>| -> Result<i64, OrderBookError> {
+        part.and_then(|part| part.parse().ok()).ok_or_else(invalid)
+    };
+
+    let mut date_parts = date.splitn(3, '-');
+    let (year, month, day) = (
+        field(date_parts.next())?,
+        field(date_parts.next())?,
+        field(date_parts.next())?,
+    );
+    let (time, fraction) = time.split_once('.').unwrap_or((time, ""));
+    let mut time_parts = time.splitn(3, ':');
+    let (hour, minute, second) = (
+        field(time_parts.next())?,
+        field(time_parts.next())?,
+        field(time_parts.next())?,
+    );
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 60
+    {
+        return Err(invalid());
     }
+    let millis = match fraction.get(..3.min(fraction.len())) {
+        Some(digits) if fraction.bytes().all(|b| b.is_ascii_digit()) => format!("{:0<3}", digits)
+            .parse::<i64>()
+            .map_err(|_| invalid())?,
+        _ => return Err(invalid()),
+    };
+
+    // Days since the epoch of the proleptic Gregorian date (years starting in March)
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    let millis = ((days * 24 + hour) * 60 + minute) * 60_000 + second * 1000 + millis;
+    u64::try_from(millis).map_err(|_| invalid())
 }
@@ -0,0 +1,121 @@
+//! Binance order book engine
+//!
+//! Maintains local order books from Binance's combined WebSocket stream:
+//!
+//! - **Book types**: [`OrderBook`] with exact [`Decimal`] prices and quantities, kept in sync
+//!   with book ticker, partial depth and diff depth updates, and [`BookManager`] for many symbols.
+//! - **Message parsers**: [`parse_stream_message`] and [`parse_binance_message`] turn raw JSON
+//!   into [`StreamMessage`]s and [`BinanceMessage`]s.
+//! - **Stream client**: [`binance_websocket_client`] runs a supervised, reconnecting connection,
+//!   and [`BookProcessor`] applies every received message to the books.
+//! - **Other venues**: [`MarketDataFeed`] adapters ([`BinanceFeed`], [`CoinbaseFeed`], [`KrakenFeed`]) decode
+//!   venue streams into normalized [`FeedEvent`]s, run by [`market_data_feed_client`] and applied to the same
+//!   [`OrderBook`] by [`apply_feed_event`].
+//! - **Consolidated books**: [`ConsolidatedBook`] merges the books of one instrument from several venues,
+//!   markets or connections, with per-level source attribution and cross-venue crossed market detection.
+//! - **HTTP API**: [`api_router`] and [`serve_api`] serve the books as JSON to other processes.
+//! - **Trades**: `@trade` and `@aggTrade` events feed the [`TradeStore`] of every [`OrderBook`] with rolling
+//!   [`TradeStats`] (VWAP, aggressor volumes, trade count, last price).
+//! - **Candles**: the [`CandleStore`] of every [`OrderBook`] holds OHLCV [`Candle`]s of the `@kline_<interval>`
+//!   stream, or aggregated locally from trades or mid prices.
+//! - **Futures**: [`Market`] selects spot, USD-M or COIN-M endpoints; futures books also keep the
+//!   [`MarkPrice`] and recent [`Liquidation`]s of the `@markPrice` and `@forceOrder` streams.
+//! - **Metrics**: [`FeedMetrics`] counts received, applied and rejected messages, reconnects and latency,
+//!   exported in the Prometheus text format by `GET /metrics` of the HTTP API.
+//! - **Fan-out server**: [`serve_fanout`] pushes the [`BookEvent`]s of [`BookPublisher`] to WebSocket subscribers.
+//! - **Error type**: every fallible operation returns an [`OrderBookError`].
+//!
+//! ```
+//! use binance_orderbook::{parse_binance_message, BinanceMessage, DepthUpdate, OrderBook};
+//!
+//! let mut orderbook = OrderBook::new("BNBUSDT".to_string());
+//! let json = r#"{"lastUpdateId":160,"bids":[["25.35","10"]],"asks":[["25.36","4"]]}"#;
+//! if let Some(BinanceMessage::DepthUpdate(reader)) = parse_binance_message(json) {
+//!     orderbook.update_depth(&DepthUpdate::from_reader(reader).unwrap());
+//! }
+//!
+//! let ((bid, _), (ask, _)) = orderbook.get_best_bid_ask().unwrap();
+//! assert_eq!((bid.to_string(), ask.to_string()), ("25.35".to_string(), "25.36".to_string()));
+//! ```
+
+#![warn(missing_docs)]
+
+use axum::{
+    extract::{Path as UrlPath, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use futures::{
+    channel::mpsc::{TrySendError, UnboundedReceiver, UnboundedSender},
+    SinkExt, StreamExt,
+};
+use prometheus::{
+    Encoder, GaugeVec, Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGauge, Opts,
+    Registry, TextEncoder,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::{value::RawValue, Error as SerdeError};
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    fmt,
+    fs::File,
+    future::Future,
+    hash::{Hash, Hasher},
+    io::{BufRead, BufReader, BufWriter, Write},
+    iter::Sum,
+    ops::{Add, AddAssign, Bound, Div, Mul, Sub, SubAssign},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering as AtomicOrdering},
+        Arc, Mutex, OnceLock, PoisonError,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::{broadcast, watch, Notify, RwLock},
+    task::JoinHandle,
+    time::{sleep, Duration},
+};
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{self, client::IntoClientRequest, Message},
+};
+
+mod analytics;
+mod api;
+mod candles;
+mod consolidated;
+mod decimal;
+mod derivatives;
+mod enums;
+mod error;
+mod fanout;
+mod feeds;
+mod helper;
+mod manager;
+mod metrics;
+mod process;
+mod processor;
+mod recorder;
+mod replay;
+mod snapshot;
+mod structs;
+mod symbols;
+mod trades;
+
+#[cfg(test)]
+mod tests;
+
+pub use {
+    analytics::*, api::*, candles::*, consolidated::*, decimal::*, derivatives::*, enums::*,
+    error::*, fanout::*, feeds::*, helper::*, manager::*, metrics::*, process::*, processor::*,
+    recorder::*, replay::*, snapshot::*, structs::*, symbols::*, trades::*,
+};
@@ -1,73 +1,802 @@
+use binance_orderbook::*;
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use colored::*;
 use futures::{
-    channel::mpsc::{unbounded, TrySendError, UnboundedReceiver, UnboundedSender},
+    channel::mpsc::{unbounded, UnboundedReceiver},
     StreamExt,
 };
-use ordered_float::OrderedFloat;
-use serde::Deserialize;
-use serde_json::Error as SerdeError;
-use std::{collections::BTreeMap, fmt, sync::Arc};
+use std::{
+    collections::BTreeSet,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    str::FromStr,
+    sync::Arc,
+};
 use tokio::{
-    io::{AsyncBufReadExt, BufReader},
-    sync::Mutex,
+    net::TcpListener,
+    sync::{watch, RwLock},
     time::{sleep, Duration},
 };
-use tokio_tungstenite::{
-    connect_async,
-    tungstenite::{self, client::IntoClientRequest, Message},
-};
-
-mod enums;
-mod error;
-mod helper;
-mod menu;
-mod process;
-mod structs;
 
-#[cfg(test)]
-mod tests;
+mod cli;
+mod display;
+mod tui;
 
-use {enums::*, error::*, helper::*, menu::*, process::*, structs::*};
+use cli::*;
+use display::*;
+use tui::*;
 
 /// Main function with asynchronous runtime using Tokio
+/// Errors are printed and reported through the exit code of their `OrderBookError` variant
 #[tokio::main]
-async fn main() -> Result<(), OrderBookError> {
-    // Initialize the logger for logging purposes
-    env_logger::init();
+async fn main() -> ExitCode {
+    // Parse the command line, printing usage and exiting with code 2 on invalid arguments
+    let cli = Cli::parse();
 
-    // Create an unbounded channel for sending and receiving messages asynchronously
-    let (tx, rx) = unbounded();
+    // Initialize the logger; the terminal UI shows the log in a pane instead of on stderr
+    let logs = LogBuffer::default();
+    if cli.is_interactive() {
+        TuiLogger::install(logs.clone());
+    } else {
+        env_logger::init();
+    }
+
+    match run(cli, logs).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e.to_string().red());
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
+
+/// Function to run the subcommand given on the command line, the terminal UI if none is given
+async fn run(cli: Cli, logs: LogBuffer) -> Result<(), OrderBookError> {
+    // Start the raw stream recorder, switched on by `--record [dir]`
+    let recorder_config = RecorderConfig {
+        directory: cli
+            .record
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("recordings")),
+        compress: cli.record_gzip,
+        ..RecorderConfig::default()
+    };
+    let (recorder, _recorder_writer) = StreamRecorder::start(recorder_config, cli.record.is_some());
 
+    let servers = cli.servers;
+    let feed = cli.feed;
+    let command = cli.command.unwrap_or(Command::Tui {
+        symbols: Vec::new(),
+        depth_mode: None,
+    });
+    match command {
+        Command::Tui {
+            symbols,
+            depth_mode,
+        } => run_interactive(symbols, depth_mode, servers, &feed, recorder, logs).await,
+        Command::Watch {
+            symbol,
+            depth,
+            depth_mode,
+            interval_ms,
+        } => {
+            let interval = Duration::from_millis(interval_ms.max(1));
+            run_watch(
+                &symbol,
+                depth,
+                depth_mode.into(),
+                interval,
+                servers,
+                &feed,
+                recorder,
+            )
+            .await
+        }
+        Command::Serve {
+            symbols,
+            depth_mode,
+        } => run_serve(&symbols, depth_mode.into(), servers, &feed, recorder).await,
+        Command::Venue {
+            venue,
+            symbols,
+            kraken_depth,
+        } => match venue {
+            VenueArg::Binance => {
+                let market = feed.market();
+                let source = RestSnapshotSource::binance(market);
+                let binance = BinanceFeed::new(market, DepthMode::Diff);
+                run_venue(binance, &symbols, source, servers, &feed).await
+            }
+            VenueArg::Coinbase => {
+                let coinbase = CoinbaseFeed::new();
+                run_venue(coinbase, &symbols, NoSnapshotSource, servers, &feed).await
+            }
+            VenueArg::Kraken => {
+                let kraken = KrakenFeed::new(kraken_depth);
+                run_venue(kraken, &symbols, NoSnapshotSource, servers, &feed).await
+            }
+        },
+        Command::Consolidate {
+            symbol,
+            sources,
+            kraken_depth,
+        } => run_consolidate(&symbol, &sources, kraken_depth, servers, &feed).await,
+        Command::Snapshot {
+            symbol,
+            depth,
+            json,
+        } => run_snapshot(&symbol, depth, json, feed.market()).await,
+        Command::Replay {
+            files,
+            speed,
+            depth,
+            json,
+            tui,
+        } => {
+            let tui = tui.then_some((logs, recorder));
+            run_replay(&files, speed, depth, json, tui, servers, &feed).await
+        }
+        Command::Process {
+            file,
+            symbol,
+            depth,
+            json,
+        } => run_process(&file, &symbol, depth, json, &feed).await,
+    }
+}
+
+/// Function to run the terminal UI on live books, prompting for symbols and depth mode if not given
+async fn run_interactive(
+    mut symbols: Vec<String>,         // Symbols given with `--symbols`
+    depth_mode: Option<DepthModeArg>, // Depth mode given with `--depth-mode`
+    servers: ServerArgs,              // Addresses to serve the books on, if any
+    feed: &FeedArgs,                  // Trade stream, trade windows and candles of the books
+    recorder: Arc<StreamRecorder>,    // The raw stream recorder
+    logs: LogBuffer,                  // The lines shown in the log pane
+) -> Result<(), OrderBookError> {
     // Prepare to capture user input from stdin
     let stdin = std::io::stdin(); // Standard input
-    let mut input = String::new(); // Buffer for user input
 
-    // Prompt the user to enter a coin pair symbol (e.g., BTCUSDT, ETHUSDT)
-    println!("Enter coin pair symbol (bnbusdt / ethusdt / btcusdt / bnbbtc..etc):");
-    // Read the user input and handle potential IO errors
-    stdin
-        .read_line(&mut input)
-        .map_err(|e| OrderBookError::IoError(e))?; // If there's an error reading input, convert it to `OrderBookError::IoError`
+    if symbols.is_empty() {
+        // Prompt the user to enter one or more coin pair symbols (e.g., BTCUSDT, ETHUSDT)
+        println!(
+            "Enter coin pair symbol(s), comma separated (bnbusdt / ethusdt,btcusdt / bnbbtc..etc):"
+        );
+        let mut input = String::new(); // Buffer for user input
+        stdin
+            .read_line(&mut input)
+            .map_err(OrderBookError::IoError)?; // If there's an error reading input, convert it to `OrderBookError::IoError`
+        symbols = input.split(',').map(str::to_string).collect();
+    }
+
+    let depth_mode = match depth_mode {
+        Some(depth_mode) => depth_mode.into(),
+        None => {
+            // Prompt the user to choose how the depth of the order books is maintained
+            println!("Select depth mode (1. diff depth synced with REST snapshot [default] / 2. partial depth20):");
+            let mut mode_input = String::new();
+            stdin
+                .read_line(&mut mode_input)
+                .map_err(OrderBookError::IoError)?;
+            match mode_input.trim() {
+                "2" => DepthMode::Partial,
+                _ => DepthMode::Diff,
+            }
+        }
+    };
+
+    // Create a `BookManager` with one `OrderBook` per symbol, rejecting symbols unknown to the registry
+    let mut manager = BookManager::new(depth_mode, load_registry(false, feed.market()).await?);
+    feed.configure(&mut manager)?;
+    for symbol in symbols.iter().filter(|s| !s.trim().is_empty()) {
+        manager.add_symbol(symbol)?;
+    }
+
+    // Log the trading rules of each symbol, if known
+    for symbol in manager.symbols() {
+        if let Some(info) = manager.book(&symbol).and_then(|book| book.symbol_info()) {
+            log::info!(
+                "{} ({}/{}): status {}, tick size {}, step size {}",
+                info.symbol,
+                info.base_asset,
+                info.quote_asset,
+                info.status,
+                info.tick_size,
+                info.step_size
+            );
+        }
+    }
+
+    let (manager, rx, source, status) = connect_live(manager, &recorder);
+    spawn_servers(servers, &manager).await?;
+
+    // Start the task that applies every stream message to the order books
+    let mut processor = BookProcessor::new(Arc::clone(&manager), rx, source);
+    processor.start();
+
+    // Launch the terminal UI for interacting with the order books and WebSocket
+    run_tui(manager, processor, recorder, status, logs).await
+}
+
+/// Function to display the live book of a symbol, refreshed every `interval` until Ctrl+C is pressed
+async fn run_watch(
+    symbol: &str,                  // The symbol to watch
+    depth: usize,                  // Number of levels per side
+    depth_mode: DepthMode,         // How the depth of the book is maintained
+    interval: Duration,            // Time between two refreshes of the display
+    servers: ServerArgs,           // Addresses to serve the book on, if any
+    feed: &FeedArgs,               // Trade stream, trade windows and candles of the book
+    recorder: Arc<StreamRecorder>, // The raw stream recorder
+) -> Result<(), OrderBookError> {
+    let mut manager = BookManager::new(depth_mode, load_registry(false, feed.market()).await?);
+    feed.configure(&mut manager)?;
+    manager.add_symbol(symbol)?;
+    let symbol = symbol.trim().to_uppercase();
+
+    let (manager, rx, source, status) = connect_live(manager, &recorder);
+    spawn_servers(servers, &manager).await?;
+    let mut processor = BookProcessor::new(Arc::clone(&manager), rx, source);
+    processor.start();
+
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => break,
+            _ = sleep(interval) => {
+                let manager = manager.read().await;
+                let Some(orderbook) = manager.book(&symbol) else {
+                    break;
+                };
+                // Clear the screen and move the cursor to the top left before redrawing
+                print!("\x1B[2J\x1B[1;1H");
+                println!("{}", format!("{} (Ctrl+C to exit)", symbol).green().bold());
+                println!("{}", status.borrow().describe().cyan());
+                if orderbook.is_stale() {
+                    println!("{}", "Order book is stale, waiting for resync".yellow());
+                }
+                print_book(orderbook, depth, false)?;
+            }
+        }
+    }
+
+    processor.stop().await;
+    Ok(())
+}
+
+/// Function to keep live books and serve them over HTTP (and WebSocket, if requested) until Ctrl+C is pressed
+async fn run_serve(
+    symbols: &[String],            // The symbols to serve
+    depth_mode: DepthMode,         // How the depth of the books is maintained
+    servers: ServerArgs, // Addresses to serve the books on; HTTP defaults to 127.0.0.1:8080
+    feed: &FeedArgs,     // Trade stream, trade windows and candles of the books
+    recorder: Arc<StreamRecorder>, // The raw stream recorder
+) -> Result<(), OrderBookError> {
+    let mut manager = BookManager::new(depth_mode, load_registry(false, feed.market()).await?);
+    feed.configure(&mut manager)?;
+    for symbol in symbols.iter().filter(|s| !s.trim().is_empty()) {
+        manager.add_symbol(symbol)?;
+    }
+
+    let (manager, rx, source, _status) = connect_live(manager, &recorder);
+    let fanout = ServerArgs {
+        http: None,
+        ..servers
+    };
+    spawn_servers(fanout, &manager).await?;
+    let mut processor = BookProcessor::new(Arc::clone(&manager), rx, source);
+    processor.start();
 
-    // Trim whitespace from input and convert the coin symbol to uppercase
-    let symbol = input.trim().to_uppercase();
+    // Serve on the default address unless `--http` is given
+    let addr = servers
+        .http
+        .unwrap_or_else(|| SocketAddr::from(([127, 0, 0, 1], 8080)));
+    let listener = TcpListener::bind(addr).await?;
+    println!(
+        "{}",
+        format!("Serving the HTTP API on http://{}", listener.local_addr()?).green()
+    );
+    let result = tokio::select! {
+        result = serve_api(listener, manager) => result,
+        _ = tokio::signal::ctrl_c() => Ok(()),
+    };
 
-    // Create a new `OrderBook` instance and wrap it in an `Arc<Mutex>` to allow shared access between async tasks
-    let orderbook = Arc::new(Mutex::new(OrderBook::new(symbol.to_string())));
+    processor.stop().await;
+    result
+}
+
+/// Function to keep the books of a venue's public feed and serve them like `serve` until Ctrl+C is pressed
+async fn run_venue<F, S>(
+    venue: F,            // The adapter of the venue
+    symbols: &[String],  // The venue symbols to serve
+    source: S,           // The source of depth snapshots, for venues without stream snapshots
+    servers: ServerArgs, // Addresses to serve the books on; HTTP defaults to 127.0.0.1:8080
+    feed: &FeedArgs,     // Trade windows and candles of the books
+) -> Result<(), OrderBookError>
+where
+    F: MarketDataFeed + Send + 'static,
+    S: SnapshotSource + Clone + Send + Sync + 'static,
+{
+    let symbols: Vec<String> = symbols
+        .iter()
+        .map(|symbol| symbol.trim().to_string())
+        .filter(|symbol| !symbol.is_empty())
+        .collect();
+    let mut manager = BookManager::new(DepthMode::Diff, None);
+    feed.configure(&mut manager)?;
+    for symbol in &symbols {
+        manager.add_symbol(&venue.book_symbol(symbol))?;
+    }
+    let manager = Arc::new(RwLock::new(manager));
 
-    // Spawn an asynchronous task to handle WebSocket communication for the specified coin pair
+    // Decode the venue's stream in the background; interruptions mark the books stale
+    let (tx, mut rx) = unbounded();
+    let (event_tx, event_rx) = unbounded();
+    tokio::spawn(async move {
+        if let Err(e) =
+            market_data_feed_client(venue, symbols, tx, event_tx, ReconnectPolicy::default()).await
+        {
+            log::error!("{}", e);
+        }
+    });
+    let (status_tx, _status) = watch::channel(FeedStatus::Connecting);
+    tokio::spawn(handle_connection_events(
+        Arc::clone(&manager),
+        event_rx,
+        status_tx,
+    ));
+
+    // Apply the decoded events to the books; snapshots are fetched outside the lock
+    let shared = Arc::clone(&manager);
+    tokio::spawn(async move {
+        while let Some(event) = rx.next().await {
+            let symbol = event.symbol.clone();
+            let rest_snapshots = event.venue.has_rest_snapshots();
+            let mut books = shared.write().await;
+            if let Err(e) = books.apply_event(event) {
+                log::warn!("{}", e);
+            }
+            let fetch = rest_snapshots && books.request_snapshot(&symbol);
+            drop(books);
+            if fetch {
+                tokio::spawn(sync_shared_book(
+                    Arc::clone(&shared),
+                    symbol,
+                    source.clone(),
+                ));
+            }
+        }
+    });
+
+    let fanout = ServerArgs {
+        http: None,
+        ..servers
+    };
+    spawn_servers(fanout, &manager).await?;
+    let addr = servers
+        .http
+        .unwrap_or_else(|| SocketAddr::from(([127, 0, 0, 1], 8080)));
+    let listener = TcpListener::bind(addr).await?;
+    println!(
+        "{}",
+        format!("Serving the HTTP API on http://{}", listener.local_addr()?).green()
+    );
+    tokio::select! {
+        result = serve_api(listener, manager) => result,
+        _ = tokio::signal::ctrl_c() => Ok(()),
+    }
+}
+
+/// Function to merge the books of several sources into a consolidated book and serve it like `serve`
+/// until Ctrl+C is pressed
+async fn run_consolidate(
+    symbol: &str,           // Symbol of the consolidated book
+    sources: &[SourceSpec], // The sources of the consolidated book
+    kraken_depth: usize,    // Number of levels per side subscribed to on Kraken
+    servers: ServerArgs,    // Addresses to serve the book on; HTTP defaults to 127.0.0.1:8080
+    feed: &FeedArgs,        // Trade windows and candles of the book
+) -> Result<(), OrderBookError> {
+    let mut manager = BookManager::new(DepthMode::Diff, None);
+    feed.configure(&mut manager)?;
+    manager.add_consolidated(symbol)?;
+    let manager = Arc::new(RwLock::new(manager));
+
+    // Levels are attributed to the labels of the sources, which must be unique
+    let mut labels = BTreeSet::new();
+    if let Some(spec) = sources.iter().find(|spec| !labels.insert(spec.label())) {
+        return Err(OrderBookError::ParseError(format!(
+            "Duplicate source: {}",
+            spec.label()
+        )));
+    }
+
+    // Every source keeps its own book on its own connection
+    for spec in sources {
+        let (manager, symbol) = (Arc::clone(&manager), symbol.to_string());
+        match spec.venue {
+            Venue::Binance => {
+                let binance = BinanceFeed::new(spec.market, DepthMode::Diff);
+                let source = RestSnapshotSource::binance(spec.market);
+                tokio::spawn(maintain_source(
+                    binance,
+                    spec.clone(),
+                    source,
+                    manager,
+                    symbol,
+                ))
+            }
+            Venue::Coinbase => {
+                let coinbase = CoinbaseFeed::new();
+                tokio::spawn(maintain_source(
+                    coinbase,
+                    spec.clone(),
+                    NoSnapshotSource,
+                    manager,
+                    symbol,
+                ))
+            }
+            Venue::Kraken => {
+                let kraken = KrakenFeed::new(kraken_depth);
+                tokio::spawn(maintain_source(
+                    kraken,
+                    spec.clone(),
+                    NoSnapshotSource,
+                    manager,
+                    symbol,
+                ))
+            }
+        };
+    }
+
+    let fanout = ServerArgs {
+        http: None,
+        ..servers
+    };
+    spawn_servers(fanout, &manager).await?;
+    let addr = servers
+        .http
+        .unwrap_or_else(|| SocketAddr::from(([127, 0, 0, 1], 8080)));
+    let listener = TcpListener::bind(addr).await?;
+    println!(
+        "{}",
+        format!(
+            "Serving the consolidated book on http://{}/consolidated/{}",
+            listener.local_addr()?,
+            symbol.trim().to_uppercase()
+        )
+        .green()
+    );
+    tokio::select! {
+        result = serve_api(listener, manager) => result,
+        _ = tokio::signal::ctrl_c() => Ok(()),
+    }
+}
+
+/// Function to maintain the book of one source of a consolidated book and merge it after every change
+/// Interruptions of the source's connection mark its book stale; crossings with other sources are logged
+/// when they change.
+async fn maintain_source<F, S>(
+    venue: F,                          // The adapter of the source's venue
+    spec: SourceSpec,                  // The source
+    source: S, // The source of depth snapshots, for venues without stream snapshots
+    manager: Arc<RwLock<BookManager>>, // The book manager holding the consolidated book
+    symbol: String, // Symbol of the consolidated book
+) where
+    F: MarketDataFeed + Send + 'static,
+    S: SnapshotSource,
+{
+    let label = spec.label();
+    let mut orderbook = OrderBook::new(venue.book_symbol(&spec.symbol));
+    let (tx, mut rx) = unbounded();
+    let (event_tx, mut events) = unbounded();
+    let symbols = vec![spec.symbol];
+    tokio::spawn(async move {
+        if let Err(e) =
+            market_data_feed_client(venue, symbols, tx, event_tx, ReconnectPolicy::default()).await
+        {
+            log::error!("{}", e);
+        }
+    });
+
+    // The last crossing reported, so it is only logged once
+    let mut crossing = None;
+    loop {
+        tokio::select! {
+            Some(event) = rx.next() => {
+                if let Err(e) = apply_feed_event(&mut orderbook, event, &source).await {
+                    log::warn!("{}: {}", label, e);
+                }
+            }
+            Some(event) = events.next() => {
+                log::info!("{}: {:?}", label, event);
+                if matches!(event, ConnectionEvent::Disconnected(_) | ConnectionEvent::RolledOver) {
+                    orderbook.mark_stale();
+                }
+            }
+            else => break,
+        }
+
+        let result = manager
+            .write()
+            .await
+            .update_consolidated(&symbol, &label, &orderbook);
+        let crossed = result.err().map(|e| e.to_string());
+        if crossed != crossing {
+            if let Some(crossed) = &crossed {
+                log::warn!("{}", crossed);
+            }
+            crossing = crossed;
+        }
+    }
+}
+
+/// Function to fetch the current book of a symbol from the REST API, print it and exit
+async fn run_snapshot(
+    symbol: &str,   // Symbol to print
+    depth: usize,   // Number of levels per side
+    json: bool,     // Whether the book is printed as JSON
+    market: Market, // Market of the REST API
+) -> Result<(), OrderBookError> {
+    // Request the smallest snapshot limit supported by the endpoint that covers the depth
+    const LIMITS: [u32; 8] = [5, 10, 20, 50, 100, 500, 1000, 5000];
+    let max_limit = market.max_snapshot_limit();
+    let limit = LIMITS
+        .into_iter()
+        .find(|&limit| limit as usize >= depth || limit == max_limit)
+        .unwrap_or(max_limit);
+
+    let symbol = symbol.trim().to_uppercase();
+    let source = RestSnapshotSource::new(market.rest_url(), limit).with_market(market);
+    let snapshot = source.fetch_depth_snapshot(&symbol).await?;
+
+    let mut orderbook = OrderBook::new(symbol);
+    orderbook.update_depth(&DepthUpdate::from_reader(snapshot)?);
+    orderbook.refresh_metrics();
+    print_book(&orderbook, depth, json)
+}
+
+/// Function to replay recording files into the books and print them, or open the terminal UI on them
+async fn run_replay(
+    files: &[PathBuf],  // Recording files, replayed in the given order
+    speed: ReplaySpeed, // How fast the frames are replayed
+    depth: usize,       // Number of levels per side printed for every book
+    json: bool,         // Whether the books are printed as JSON
+    tui: Option<(LogBuffer, Arc<StreamRecorder>)>, // Log pane lines and recorder if the terminal UI is opened
+    servers: ServerArgs, // Addresses to serve the books on while the terminal UI is open, if any
+    feed: &FeedArgs,     // Trade windows and candles of the books
+) -> Result<(), OrderBookError> {
+    // Replays run offline, so only a registry from the `EXCHANGE_INFO` file is used
+    let mut manager = BookManager::new(DepthMode::Diff, load_registry(true, feed.market()).await?);
+    feed.configure(&mut manager)?;
+    for symbol in recording_symbols(files)? {
+        manager.add_symbol(&symbol)?;
+    }
+    let manager = Arc::new(RwLock::new(manager));
+    let source = ReplaySnapshotSource::new();
+
+    // Spawn an asynchronous task feeding the recorded frames into the processing channel
+    // The channel closes when the replay ends, which lets the processing task finish
+    let (tx, rx) = unbounded();
+    let replay = tokio::spawn({
+        let files = files.to_vec();
+        let source = source.clone();
+        async move { replay_recording(&files, tx, speed, &source).await }
+    });
+
+    // Start the task that applies every replayed message to the order books
+    let mut processor = BookProcessor::new(Arc::clone(&manager), rx, source);
+    processor.start();
+
+    if let Some((logs, recorder)) = tui {
+        // Report the end of the replay in the status bar while the terminal UI is running
+        let (status_tx, status) = watch::channel(FeedStatus::Replaying);
+        tokio::spawn(async move {
+            let status = match replay.await.map_err(std::io::Error::from) {
+                Ok(Ok(stats)) => FeedStatus::ReplayFinished(stats),
+                Ok(Err(e)) => FeedStatus::ReplayFailed(e.to_string()),
+                Err(e) => FeedStatus::ReplayFailed(e.to_string()),
+            };
+            status_tx.send_replace(status);
+        });
+        spawn_servers(servers, &manager).await?;
+        return run_tui(manager, processor, recorder, status, logs).await;
+    }
+
+    // Wait for the replay to end and every replayed message to be applied
+    let stats = replay.await.map_err(std::io::Error::from)??;
+    processor.wait().await;
+    log::info!("{}", replay_summary(&stats));
+
+    let manager = manager.read().await;
+    let books: Vec<&OrderBook> = manager
+        .symbols()
+        .iter()
+        .filter_map(|symbol| manager.book(symbol))
+        .collect();
+    if json {
+        let books: Vec<_> = books.iter().map(|book| book.view(depth)).collect();
+        println!("{}", serde_json::to_string_pretty(&books)?);
+    } else {
+        for orderbook in books {
+            println!("{}", orderbook.symbol().green().bold());
+            print_book(orderbook, depth, false)?;
+        }
+    }
+    Ok(())
+}
+
+/// Function to apply the JSON messages of a file (one per line) to a fresh book, print it and exit
+/// The first message that cannot be parsed or applied ends the run with its error
+async fn run_process(
+    file: &Path,     // File with one (combined stream) message per line
+    symbol: &str,    // Symbol of the book the messages are applied to
+    depth: usize,    // Number of levels per side
+    json: bool,      // Whether the book is printed as JSON
+    feed: &FeedArgs, // Trade windows and candles of the book
+) -> Result<(), OrderBookError> {
+    let content = std::fs::read_to_string(file)?;
+    let source = RestSnapshotSource::binance(feed.market());
+    let mut orderbook = OrderBook::new(symbol.trim().to_uppercase());
+    feed.configure_book(&mut orderbook)?;
+
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        // Accept combined stream envelopes as well as raw payloads
+        let message = parse_stream_message(line)
+            .map(|message| message.message)
+            .or_else(|| parse_binance_message(line))
+            .ok_or(OrderBookError::IncorrectJsonData)?;
+        apply_binance_message(&mut orderbook, message, &source).await?;
+    }
+
+    print_book(&orderbook, depth, json)
+}
+
+/// Function to load the symbol registry from the `EXCHANGE_INFO` file if set, otherwise from the REST API
+/// of the market. Offline runs only use the file; an unavailable REST API disables symbol validation
+async fn load_registry(
+    offline: bool,  // Whether the REST API must not be used
+    market: Market, // Market of the REST API
+) -> Result<Option<SymbolRegistry>, OrderBookError> {
+    Ok(match std::env::var("EXCHANGE_INFO") {
+        Ok(path) => Some(SymbolRegistry::from_file(path)?),
+        Err(_) if offline => None,
+        Err(_) => match SymbolRegistry::from_market_url(market.rest_url(), market).await {
+            Ok(registry) => Some(registry),
+            Err(e) => {
+                // Continue without symbol validation if exchangeInfo is unavailable
+                log::warn!("Symbol registry unavailable: {}", e);
+                None
+            }
+        },
+    })
+}
+
+/// Function to connect the books of the manager to the Binance combined stream
+/// Returns the shared manager, the channel the live messages arrive on, the snapshot source to sync with
+/// and the status of the connection
+fn connect_live(
+    mut manager: BookManager, // The book manager with the symbols to stream
+    recorder: &Arc<StreamRecorder>, // The raw stream recorder
+) -> (
+    Arc<RwLock<BookManager>>,
+    UnboundedReceiver<StreamMessage>,
+    RecordingSnapshotSource<RestSnapshotSource>,
+    watch::Receiver<FeedStatus>,
+) {
+    // Create an unbounded channel for sending and receiving messages asynchronously
+    let (tx, rx) = unbounded();
+
+    // Connect the manager to the stream client so symbols can be added or removed at runtime
+    let (command_tx, command_rx) = unbounded();
+    let streams = manager.streams();
+    let market = manager.market();
+    manager.set_command_sender(command_tx);
+
+    // Spawn an asynchronous task to handle the supervised combined stream WebSocket communication
+    let (event_tx, event_rx) = unbounded();
+    let client_recorder = Arc::clone(recorder);
     tokio::spawn(async move {
         // Call the WebSocket client for Binance. If there's an error, it gets logged.
-        if let Err(e) = binance_websocket_client(&symbol, tx).await {
-            eprintln!("{}", e.to_string().red()); // Log the error
+        if let Err(e) = binance_websocket_client(
+            market.stream_url(),
+            streams,
+            tx,
+            command_rx,
+            event_tx,
+            ReconnectPolicy::default(),
+            Some(client_recorder),
+        )
+        .await
+        {
+            log::error!("{}", e); // Log the error
         }
     });
 
-    // Wrap the receiver in `Arc<Mutex>` for shared access
-    let rx = Arc::new(Mutex::new(rx));
+    // Wrap the `BookManager` in an `Arc<RwLock>` so queries can run while the books are maintained
+    let manager = Arc::new(RwLock::new(manager));
 
-    // Launch the user menu interface for interacting with the orderbook and WebSocket
-    menu_interface(orderbook, rx).await?;
+    // Spawn an asynchronous task to report connection events and mark books stale on interruptions
+    let (status_tx, status) = watch::channel(FeedStatus::Connecting);
+    tokio::spawn(handle_connection_events(
+        Arc::clone(&manager),
+        event_rx,
+        status_tx,
+    ));
 
+    // Depth snapshots are recorded along with the stream, so recordings can be replayed offline
+    let source =
+        RecordingSnapshotSource::new(RestSnapshotSource::binance(market), Arc::clone(recorder));
+    (manager, rx, source, status)
+}
+
+/// Function to serve the books over HTTP and WebSocket in the background on the given addresses
+async fn spawn_servers(
+    servers: ServerArgs,                // Addresses to serve the books on, if any
+    manager: &Arc<RwLock<BookManager>>, // A shared, thread-safe reference to the book manager
+) -> Result<(), OrderBookError> {
+    if let Some(addr) = servers.http {
+        let listener = TcpListener::bind(addr).await?;
+        log::info!("Serving the HTTP API on http://{}", listener.local_addr()?);
+        let manager = Arc::clone(manager);
+        tokio::spawn(async move {
+            if let Err(e) = serve_api(listener, manager).await {
+                log::error!("HTTP API stopped: {}", e);
+            }
+        });
+    }
+    if let Some(addr) = servers.ws {
+        let listener = TcpListener::bind(addr).await?;
+        log::info!("Serving book updates on ws://{}", listener.local_addr()?);
+        let manager = Arc::clone(manager);
+        tokio::spawn(async move {
+            if let Err(e) = serve_fanout(listener, manager).await {
+                log::error!("Fan-out server stopped: {}", e);
+            }
+        });
+    }
     Ok(())
 }
+
+/// Function to print the top levels, metrics, trade statistics, candles and futures data of a book, as a price ladder or as JSON
+fn print_book(orderbook: &OrderBook, depth: usize, json: bool) -> Result<(), OrderBookError> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(&orderbook.view(depth))?);
+    } else {
+        display_depth_ladder(&orderbook.top_levels(depth));
+        display_book_metrics(orderbook.metrics());
+        display_trade_stats(orderbook.trades(), orderbook.clock());
+        display_candles(orderbook.candles());
+        display_futures(orderbook);
+    }
+    Ok(())
+}
+
+/// Function to log connection events, publish them as the feed status and mark the order books stale
+/// whenever the stream was interrupted
+async fn handle_connection_events(
+    manager: Arc<RwLock<BookManager>>, // A shared, thread-safe reference to the book manager
+    mut events: UnboundedReceiver<ConnectionEvent>, // The channel connection events are reported on
+    status: watch::Sender<FeedStatus>, // The status shown by the terminal UI and `watch`
+) {
+    while let Some(event) = events.next().await {
+        match &event {
+            ConnectionEvent::Connected(streams) => {
+                log::info!(
+                    "Connected to the market data stream: {}",
+                    streams.join(", ")
+                )
+            }
+            ConnectionEvent::Disconnected(reason) => {
+                log::warn!("{}", reason);
+                manager.write().await.mark_all_stale();
+            }
+            ConnectionEvent::Reconnecting { attempt, delay } => {
+                log::info!("Reconnecting (attempt {}) in {:?}", attempt, delay)
+            }
+            ConnectionEvent::RolledOver => {
+                log::info!("Rolling the connection over");
+                manager.write().await.mark_all_stale();
+            }
+        }
+        status.send_replace(FeedStatus::Live(event));
+    }
+}
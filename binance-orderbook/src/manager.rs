@@ -0,0 +1,401 @@
+use super::*;
+
+/// Struct holding the order books of many symbols fed by one combined stream connection
+#[derive(Debug)]
+pub struct BookManager {
+    // Order books keyed by the uppercase symbol
+    books: BTreeMap<String, OrderBook>,
+
+    // Consolidated books of several sources keyed by the uppercase symbol, updated by their callers
+    consolidated: BTreeMap<String, ConsolidatedBook>,
+
+    // Market of the books, futures books also subscribe to the mark price and liquidation streams
+    market: Market,
+
+    // Which depth stream (partial or diff) is subscribed for every symbol
+    depth_mode: DepthMode,
+
+    // Which trade stream is subscribed for every symbol, if any
+    trade_stream: Option<TradeStream>,
+
+    // Rolling windows of the trade statistics of every book
+    trade_windows: Vec<Duration>,
+
+    // Interval and source of the candles of every book
+    candles: (KlineInterval, CandleSource),
+
+    // Symbol metadata used to validate added symbols, if available
+    registry: Option<SymbolRegistry>,
+
+    // Channel to the stream client for SUBSCRIBE/UNSUBSCRIBE requests, once connected
+    commands: Option<UnboundedSender<StreamCommand>>,
+
+    // Publisher of normalized book updates, once a fan-out server is running
+    publisher: Option<Arc<BookPublisher>>,
+}
+
+impl BookManager {
+    /// Constructor function to create a new, empty BookManager
+    pub fn new(depth_mode: DepthMode, registry: Option<SymbolRegistry>) -> Self {
+        Self {
+            books: BTreeMap::new(),
+            consolidated: BTreeMap::new(),
+            market: Market::Spot,
+            depth_mode,
+            trade_stream: None,
+            trade_windows: TradeStore::default().windows().to_vec(),
+            candles: {
+                let store = CandleStore::default();
+                (store.interval(), store.source())
+            },
+            registry,
+            commands: None,
+            publisher: None,
+        }
+    }
+
+    /// Function to get the depth stream the books are maintained with
+    pub fn depth_mode(&self) -> DepthMode {
+        self.depth_mode
+    }
+
+    /// Function to connect the manager to a stream client, so symbol changes are (un)subscribed at runtime
+    pub fn set_command_sender(&mut self, commands: UnboundedSender<StreamCommand>) {
+        self.commands = Some(commands);
+    }
+
+    /// Function to publish every book after each applied message, returning the publisher to subscribe to
+    pub fn enable_publisher(&mut self) -> Arc<BookPublisher> {
+        Arc::clone(self.publisher.get_or_insert_with(Arc::default))
+    }
+
+    /// Function to select the market of the books, before the first symbol is added
+    /// The market must match the stream connection, so it cannot change once symbols are subscribed.
+    pub fn set_market(&mut self, market: Market) -> Result<(), OrderBookError> {
+        if market != self.market && !self.books.is_empty() {
+            return Err(OrderBookError::SubscriptionError(format!(
+                "cannot switch to {:?} with subscribed symbols",
+                market
+            )));
+        }
+        self.market = market;
+        Ok(())
+    }
+
+    /// Function to get the market of the books
+    pub fn market(&self) -> Market {
+        self.market
+    }
+
+    /// Function to subscribe every symbol to a trade stream (or none), switching streams of managed symbols
+    pub fn set_trade_stream(
+        &mut self,
+        trade_stream: Option<TradeStream>,
+    ) -> Result<(), OrderBookError> {
+        if trade_stream == self.trade_stream {
+            return Ok(());
+        }
+
+        let streams = |trade_stream: Option<TradeStream>| -> Vec<String> {
+            self.books
+                .keys()
+                .filter_map(|symbol| trade_stream.map(|stream| stream.stream_name(symbol)))
+                .collect()
+        };
+        let (previous, next) = (streams(self.trade_stream), streams(trade_stream));
+        if !previous.is_empty() {
+            self.send_command(StreamCommand::Unsubscribe(previous))?;
+        }
+        if !next.is_empty() {
+            self.send_command(StreamCommand::Subscribe(next))?;
+        }
+        self.trade_stream = trade_stream;
+        Ok(())
+    }
+
+    /// Function to change the rolling windows of the trade statistics of every current and future book
+    /// An empty list is rejected and changes nothing.
+    pub fn set_trade_windows(&mut self, windows: Vec<Duration>) -> Result<(), OrderBookError> {
+        TradeStore::check_windows(&windows)?;
+        for orderbook in self.books.values_mut() {
+            orderbook.trades_mut().set_windows(windows.clone())?;
+        }
+        self.trade_windows = windows;
+        Ok(())
+    }
+
+    /// Function to change the interval and source of the candles of every current and future book
+    /// Kline candles subscribe every symbol to `@kline_<interval>`; changing either setting drops the
+    /// candles built so far.
+    pub fn set_candles(
+        &mut self,
+        interval: KlineInterval,
+        source: CandleSource,
+    ) -> Result<(), OrderBookError> {
+        if (interval, source) == self.candles {
+            return Ok(());
+        }
+
+        let streams = |(interval, source): (KlineInterval, CandleSource)| -> Vec<String> {
+            match source {
+                CandleSource::Kline => self
+                    .books
+                    .keys()
+                    .map(|symbol| interval.stream_name(symbol))
+                    .collect(),
+                CandleSource::Trades | CandleSource::Mid => Vec::new(),
+            }
+        };
+        let (previous, next) = (streams(self.candles), streams((interval, source)));
+        if !previous.is_empty() {
+            self.send_command(StreamCommand::Unsubscribe(previous))?;
+        }
+        if !next.is_empty() {
+            self.send_command(StreamCommand::Subscribe(next))?;
+        }
+        for orderbook in self.books.values_mut() {
+            orderbook.candles_mut().configure(interval, source);
+        }
+        self.candles = (interval, source);
+        Ok(())
+    }
+
+    /// Function to get the stream names subscribed for a symbol
+    pub fn stream_names(&self, symbol: &str) -> Vec<String> {
+        let mut names = vec![
+            format!("{}@bookTicker", symbol.to_lowercase()),
+            self.depth_mode.stream_name(symbol),
+        ];
+        names.extend(self.trade_stream.map(|stream| stream.stream_name(symbol)));
+        let (interval, source) = self.candles;
+        if source == CandleSource::Kline {
+            names.push(interval.stream_name(symbol));
+        }
+        if self.market.is_futures() {
+            names.push(format!("{}@markPrice@1s", symbol.to_lowercase()));
+            names.push(format!("{}@forceOrder", symbol.to_lowercase()));
+        }
+        names
+    }
+
+    /// Function to get the stream names of every managed symbol
+    pub fn streams(&self) -> Vec<String> {
+        self.books
+            .keys()
+            .flat_map(|symbol| self.stream_names(symbol))
+            .collect()
+    }
+
+    /// Function to start tracking a symbol and subscribe to its streams
+    pub fn add_symbol(&mut self, symbol: &str) -> Result<(), OrderBookError> {
+        let symbol = symbol.trim().to_uppercase();
+        if self.books.contains_key(&symbol) {
+            return Ok(());
+        }
+        // A book and a consolidated book of the same symbol would be served at the same paths
+        if self.consolidated.contains_key(&symbol) {
+            return Err(OrderBookError::ParseError(format!(
+                "{} is already a consolidated book",
+                symbol
+            )));
+        }
+
+        // Unknown symbols are rejected when a registry is available
+        let mut orderbook = match &self.registry {
+            Some(registry) => OrderBook::from_registry(registry, &symbol)?,
+            None => OrderBook::new(symbol.clone()),
+        };
+        orderbook
+            .trades_mut()
+            .set_windows(self.trade_windows.clone())?;
+        let (interval, source) = self.candles;
+        orderbook.candles_mut().configure(interval, source);
+
+        self.send_command(StreamCommand::Subscribe(self.stream_names(&symbol)))?;
+        self.books.insert(symbol, orderbook);
+        Ok(())
+    }
+
+    /// Function to start serving a consolidated book, fed by [`BookManager::update_consolidated`]
+    /// The symbol must not be taken by a book or another consolidated book.
+    pub fn add_consolidated(&mut self, symbol: &str) -> Result<(), OrderBookError> {
+        let symbol = symbol.trim().to_uppercase();
+        if self.books.contains_key(&symbol) || self.consolidated.contains_key(&symbol) {
+            return Err(OrderBookError::ParseError(format!(
+                "{} is already a book",
+                symbol
+            )));
+        }
+        self.consolidated
+            .insert(symbol.clone(), ConsolidatedBook::new(symbol));
+        Ok(())
+    }
+
+    /// Function to replace the levels of a source of a consolidated book with those of its current book
+    /// The consolidated book is updated either way; a `CrossedBook` error reports sources crossing each other.
+    pub fn update_consolidated(
+        &mut self,
+        symbol: &str,
+        source: &str,
+        orderbook: &OrderBook,
+    ) -> Result<(), OrderBookError> {
+        let Some(consolidated) = self.consolidated.get_mut(&symbol.to_uppercase()) else {
+            return Err(OrderBookError::UnknownSymbol(format!(
+                "{} is not a consolidated book",
+                symbol
+            )));
+        };
+        consolidated.update_source(source, orderbook);
+        if let Some(publisher) = &self.publisher {
+            publisher.publish(consolidated.book());
+        }
+        consolidated.check_crossed_markets()
+    }
+
+    /// Function to get the consolidated book of a symbol
+    pub fn consolidated(&self, symbol: &str) -> Option<&ConsolidatedBook> {
+        self.consolidated.get(&symbol.to_uppercase())
+    }
+
+    /// Function to stop tracking a symbol and unsubscribe from its streams
+    pub fn remove_symbol(&mut self, symbol: &str) -> Result<(), OrderBookError> {
+        let symbol = symbol.trim().to_uppercase();
+        if self.consolidated.remove(&symbol).is_some() {
+            if let Some(publisher) = &self.publisher {
+                publisher.remove(&symbol);
+            }
+            return Ok(());
+        }
+        if self.books.remove(&symbol).is_none() {
+            return Err(OrderBookError::UnknownSymbol(format!(
+                "{} is not managed",
+                symbol
+            )));
+        }
+        if let Some(publisher) = &self.publisher {
+            publisher.remove(&symbol);
+        }
+
+        self.send_command(StreamCommand::Unsubscribe(self.stream_names(&symbol)))
+    }
+
+    // Function to send a subscription request to the stream client, if connected
+    fn send_command(&self, command: StreamCommand) -> Result<(), OrderBookError> {
+        match &self.commands {
+            Some(commands) => commands
+                .unbounded_send(command)
+                .map_err(|e| OrderBookError::SubscriptionError(e.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// Function to get the managed symbols, consolidated ones included, in alphabetical order
+    pub fn symbols(&self) -> Vec<String> {
+        let symbols: BTreeSet<&String> =
+            self.books.keys().chain(self.consolidated.keys()).collect();
+        symbols.into_iter().cloned().collect()
+    }
+
+    /// Function to get the order book of a symbol, or the merged book of a consolidated symbol
+    pub fn book(&self, symbol: &str) -> Option<&OrderBook> {
+        let symbol = symbol.to_uppercase();
+        self.books
+            .get(&symbol)
+            .or_else(|| self.consolidated.get(&symbol).map(ConsolidatedBook::book))
+    }
+
+    /// Function to get the mutable order book of a symbol
+    pub fn book_mut(&mut self, symbol: &str) -> Option<&mut OrderBook> {
+        self.books.get_mut(&symbol.to_uppercase())
+    }
+
+    /// Function to mark every order book stale until it is resynchronized (e.g., after a reconnect)
+    pub fn mark_all_stale(&mut self) {
+        self.books.values_mut().for_each(OrderBook::mark_stale);
+        if let Some(publisher) = &self.publisher {
+            self.books
+                .values()
+                .for_each(|orderbook| publisher.publish(orderbook));
+        }
+    }
+
+    /// Function to route a message from the combined stream to the order book of its symbol
+    /// The outcome is counted in [`FeedMetrics::global`]. Nothing is fetched: a diff book waiting for a
+    /// snapshot is bootstrapped once its request is claimed with [`BookManager::request_snapshot`].
+    pub fn apply(&mut self, message: StreamMessage) -> Result<(), OrderBookError> {
+        let kind = message.message.kind();
+        let event_time = message.message.event_time();
+        let result = self.apply_to_book(message);
+        FeedMetrics::global().record_applied(kind, event_time, &result);
+        result
+    }
+
+    /// Function to claim the snapshot request of the diff book of a symbol, see [`OrderBook::request_snapshot`]
+    pub fn request_snapshot(&mut self, symbol: &str) -> bool {
+        self.book_mut(symbol)
+            .is_some_and(|orderbook| orderbook.request_snapshot(now_millis()))
+    }
+
+    /// Function to apply the outcome of a claimed snapshot request to the diff book of a symbol and publish the book
+    pub fn apply_snapshot(
+        &mut self,
+        symbol: &str,
+        snapshot: Result<DepthUpdateReader, OrderBookError>,
+    ) -> Result<(), OrderBookError> {
+        let Some(orderbook) = self.books.get_mut(&symbol.to_uppercase()) else {
+            return Err(OrderBookError::UnknownSymbol(format!(
+                "no order book for {}",
+                symbol
+            )));
+        };
+
+        let result = apply_fetched_snapshot(orderbook, snapshot);
+        if let Some(publisher) = &self.publisher {
+            publisher.publish(orderbook);
+        }
+        result
+    }
+
+    /// Function to route a normalized feed event of any venue to the order book of its symbol
+    /// The outcome is counted in [`FeedMetrics::global`].
+    /// Snapshots of venues without stream snapshots are fetched by the caller, see [`BookManager::request_snapshot`].
+    pub fn apply_event(&mut self, event: FeedEvent) -> Result<(), OrderBookError> {
+        let kind = event.update.kind();
+        let event_time = event.event_time;
+        let Some(orderbook) = self.books.get_mut(&event.symbol) else {
+            let result = Err(OrderBookError::UnknownSymbol(format!(
+                "no order book for {} {}",
+                event.venue, event.symbol
+            )));
+            FeedMetrics::global().record_applied(kind, event_time, &result);
+            return result;
+        };
+
+        let result = apply_feed_update(orderbook, event);
+        if let Some(publisher) = &self.publisher {
+            publisher.publish(orderbook);
+        }
+        FeedMetrics::global().record_applied(kind, event_time, &result);
+        result
+    }
+
+    // Function to apply a message to the order book of its symbol and publish the book
+    fn apply_to_book(&mut self, message: StreamMessage) -> Result<(), OrderBookError> {
+        let symbol = message.symbol();
+        let Some(orderbook) = self.books.get_mut(&symbol) else {
+            // Messages can still arrive for a symbol shortly after it was removed
+            return Err(OrderBookError::UnknownSymbol(format!(
+                "no order book for stream {}",
+                message.stream
+            )));
+        };
+
+        let result = apply_binance_update(orderbook, message.message, message.recv_time);
+
+        // Rejected messages can still change the book (e.g., a gap resets it), so it is published either way
+        if let Some(publisher) = &self.publisher {
+            publisher.publish(orderbook);
+        }
+        result
+    }
+}
@@ -0,0 +1,242 @@
+use super::*;
+
+// Upper bounds, in seconds, of the buckets of the latency histogram
+const LATENCY_BUCKETS: [f64; 12] = [
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0,
+];
+
+// The metrics of the process, created on first use
+static FEED_METRICS: OnceLock<FeedMetrics> = OnceLock::new();
+
+/// Struct holding the Prometheus metrics of the feed and of the books
+/// The stream client, the replay, the processor and the book manager record into the process-wide
+/// instance returned by [`FeedMetrics::global`], which `GET /metrics` of the HTTP API exports.
+pub struct FeedMetrics {
+    // Registry of every metric below
+    registry: Registry,
+
+    // Messages forwarded to the processing channel, by message type
+    messages_received: IntCounterVec,
+
+    // Messages applied to a book, by message type
+    messages_applied: IntCounterVec,
+
+    // Messages rejected by a book, by message type and error variant
+    messages_rejected: IntCounterVec,
+
+    // Stream payloads that could not be parsed
+    parse_errors: IntCounter,
+
+    // Reconnects of the stream connection, by reason (failure or rollover)
+    reconnects: IntCounterVec,
+
+    // Messages waiting in the processing channel
+    queue_depth: IntGauge,
+
+    // Time from the exchange event time to the message being applied
+    apply_latency: Histogram,
+
+    // Top of every managed book, refreshed on every export
+    best_bid: GaugeVec,
+    best_ask: GaugeVec,
+    spread: GaugeVec,
+}
+
+impl Default for FeedMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FeedMetrics {
+    /// Constructor function to create the metrics, registered in a registry of their own
+    pub fn new() -> Self {
+        let registry = Registry::new();
+        let messages_received = IntCounterVec::new(
+            Opts::new(
+                "orderbook_messages_received_total",
+                "Stream messages received, by message type",
+            ),
+            &["type"],
+        )
+        .expect("valid metric");
+        let messages_applied = IntCounterVec::new(
+            Opts::new(
+                "orderbook_messages_applied_total",
+                "Stream messages applied to a book, by message type",
+            ),
+            &["type"],
+        )
+        .expect("valid metric");
+        let messages_rejected = IntCounterVec::new(
+            Opts::new(
+                "orderbook_messages_rejected_total",
+                "Stream messages rejected by a book, by message type and error",
+            ),
+            &["type", "reason"],
+        )
+        .expect("valid metric");
+        let parse_errors = IntCounter::new(
+            "orderbook_parse_errors_total",
+            "Stream payloads that could not be parsed",
+        )
+        .expect("valid metric");
+        let reconnects = IntCounterVec::new(
+            Opts::new(
+                "orderbook_reconnects_total",
+                "Reconnects of the stream connection, by reason",
+            ),
+            &["reason"],
+        )
+        .expect("valid metric");
+        let queue_depth = IntGauge::new(
+            "orderbook_queue_depth",
+            "Stream messages waiting to be applied",
+        )
+        .expect("valid metric");
+        let apply_latency = Histogram::with_opts(
+            HistogramOpts::new(
+                "orderbook_apply_latency_seconds",
+                "Time from the exchange event time to the message being applied",
+            )
+            .buckets(LATENCY_BUCKETS.to_vec()),
+        )
+        .expect("valid metric");
+        let best_bid = GaugeVec::new(
+            Opts::new("orderbook_best_bid", "Best bid price, by symbol"),
+            &["symbol"],
+        )
+        .expect("valid metric");
+        let best_ask = GaugeVec::new(
+            Opts::new("orderbook_best_ask", "Best ask price, by symbol"),
+            &["symbol"],
+        )
+        .expect("valid metric");
+        let spread = GaugeVec::new(
+            Opts::new("orderbook_spread", "Best ask minus best bid, by symbol"),
+            &["symbol"],
+        )
+        .expect("valid metric");
+
+        let collectors: [Box<dyn prometheus::core::Collector>; 10] = [
+            Box::new(messages_received.clone()),
+            Box::new(messages_applied.clone()),
+            Box::new(messages_rejected.clone()),
+            Box::new(parse_errors.clone()),
+            Box::new(reconnects.clone()),
+            Box::new(queue_depth.clone()),
+            Box::new(apply_latency.clone()),
+            Box::new(best_bid.clone()),
+            Box::new(best_ask.clone()),
+            Box::new(spread.clone()),
+        ];
+        for collector in collectors {
+            registry.register(collector).expect("unique metric");
+        }
+
+        Self {
+            registry,
+            messages_received,
+            messages_applied,
+            messages_rejected,
+            parse_errors,
+            reconnects,
+            queue_depth,
+            apply_latency,
+            best_bid,
+            best_ask,
+            spread,
+        }
+    }
+
+    /// Function to get the metrics of the process
+    pub fn global() -> &'static FeedMetrics {
+        FEED_METRICS.get_or_init(FeedMetrics::new)
+    }
+
+    /// Function to count a message forwarded to the processing channel
+    pub fn record_received(&self, message: &StreamMessage) {
+        self.messages_received
+            .with_label_values(&[message.message.kind()])
+            .inc();
+        self.queue_depth.inc();
+    }
+
+    /// Function to count a message taken from the processing channel
+    pub fn record_dequeued(&self) {
+        self.queue_depth.dec();
+    }
+
+    /// Function to count a stream payload that could not be parsed
+    pub fn record_parse_error(&self) {
+        self.parse_errors.inc();
+    }
+
+    /// Function to count a reconnect of the stream connection (e.g., `failure` or `rollover`)
+    pub fn record_reconnect(&self, reason: &str) {
+        self.reconnects.with_label_values(&[reason]).inc();
+    }
+
+    /// Function to count the outcome of applying a message of type `kind`
+    /// The latency is observed for applied messages carrying an event time (in milliseconds).
+    pub fn record_applied(
+        &self,
+        kind: &str,
+        event_time: Option<u64>,
+        result: &Result<(), OrderBookError>,
+    ) {
+        match result {
+            Ok(()) => {
+                self.messages_applied.with_label_values(&[kind]).inc();
+                if let Some(event_time) = event_time {
+                    let latency = now_millis().saturating_sub(event_time) as f64 / 1000.0;
+                    self.apply_latency.observe(latency);
+                }
+            }
+            Err(e) => {
+                self.messages_rejected
+                    .with_label_values(&[kind, e.name()])
+                    .inc();
+            }
+        }
+    }
+
+    /// Function to set the best prices and spread gauges to the current top of every managed book
+    /// Symbols that are no longer managed, or have an empty side, are dropped from the gauges.
+    pub fn observe_books(&self, manager: &BookManager) {
+        self.best_bid.reset();
+        self.best_ask.reset();
+        self.spread.reset();
+        for symbol in manager.symbols() {
+            let Some(orderbook) = manager.book(&symbol) else {
+                continue;
+            };
+            let best_bid = orderbook.visible_bids().next_back();
+            let best_ask = orderbook.visible_asks().next();
+            if let Some((bid, _)) = best_bid {
+                self.best_bid
+                    .with_label_values(&[&symbol])
+                    .set(bid.to_f64());
+            }
+            if let Some((ask, _)) = best_ask {
+                self.best_ask
+                    .with_label_values(&[&symbol])
+                    .set(ask.to_f64());
+            }
+            if let Some(spread) = orderbook.spread() {
+                self.spread
+                    .with_label_values(&[&symbol])
+                    .set(spread.to_f64());
+            }
+        }
+    }
+
+    /// Function to export every metric in the Prometheus text format
+    pub fn encode(&self) -> Result<String, OrderBookError> {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .map_err(|e| OrderBookError::IoError(std::io::Error::other(e)))?;
+        String::from_utf8(buffer).map_err(|e| OrderBookError::ParseError(e.to_string()))
+    }
+}
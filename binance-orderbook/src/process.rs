@@ -1,112 +1,494 @@
 use super::*;
 
-/// Function to establish a WebSocket connection to Binance and process incoming messages
+/// Function to run a supervised combined stream connection to Binance
+/// The connection is re-established with exponential backoff and jitter whenever it fails or closes,
+/// and proactively rolled over before Binance's 24h disconnect. Connection events are reported on `events`.
 pub async fn binance_websocket_client(
-    symbol: &str,                        // The trading symbol (e.g., BTCUSDT)
-    tx: UnboundedSender<BinanceMessage>, // The channel to send processed Binance messages to the orderbook
+    base_url: &str,       // The WebSocket base URL (e.g., wss://stream.binance.com:9443)
+    streams: Vec<String>, // The initial stream names (e.g., bnbusdt@bookTicker)
+    tx: UnboundedSender<StreamMessage>, // The channel to send processed Binance messages to the book manager
+    mut commands: UnboundedReceiver<StreamCommand>, // The channel to receive subscription changes from
+    events: UnboundedSender<ConnectionEvent>,       // The channel to report connection events on
+    policy: ReconnectPolicy,                        // Backoff and rollover settings
+    recorder: Option<Arc<StreamRecorder>>, // Records every received text frame while switched on
 ) -> Result<(), OrderBookError> {
-    // WebSocket URL for both book ticker and depth stream for the given symbol
-    let ws_url = format!(
-        "wss://stream.binance.com:9443/ws/{}@bookTicker/{}@depth20@100ms",
-        symbol.to_lowercase(),
-        symbol.to_lowercase()
-    )
+    // Streams currently subscribed, used to resubscribe after reconnecting
+    let mut streams: BTreeSet<String> = streams.into_iter().collect();
+    // Number of consecutive failed connection attempts
+    let mut attempt = 0;
+
+    loop {
+        let reason = match run_stream_connection(
+            base_url,
+            &mut streams,
+            &tx,
+            &mut commands,
+            &events,
+            &policy,
+            recorder.as_deref(),
+        )
+        .await
+        {
+            // Nobody is receiving messages anymore, stop the client
+            Ok(ConnectionEnd::Shutdown) => return Ok(()),
+            // Reconnect right away before the server drops the connection
+            Ok(ConnectionEnd::RolledOver) => {
+                attempt = 0;
+                FeedMetrics::global().record_reconnect("rollover");
+                let _ = events.unbounded_send(ConnectionEvent::RolledOver);
+                continue;
+            }
+            // The connection was established before it closed, start the backoff over
+            Ok(ConnectionEnd::Closed(reason)) => {
+                attempt = 0;
+                reason
+            }
+            // The connection could not be established
+            Err(e) => e.to_string(),
+        };
+
+        attempt += 1;
+        FeedMetrics::global().record_reconnect("failure");
+        let delay = policy.backoff(attempt);
+        let _ = events.unbounded_send(ConnectionEvent::Disconnected(reason));
+        let _ = events.unbounded_send(ConnectionEvent::Reconnecting { attempt, delay });
+        sleep(delay).await;
+    }
+}
+
+/// Function to run a single combined stream connection until it closes, fails or has to be rolled over
+async fn run_stream_connection(
+    base_url: &str,
+    streams: &mut BTreeSet<String>,
+    tx: &UnboundedSender<StreamMessage>,
+    commands: &mut UnboundedReceiver<StreamCommand>,
+    events: &UnboundedSender<ConnectionEvent>,
+    policy: &ReconnectPolicy,
+    recorder: Option<&StreamRecorder>,
+) -> Result<ConnectionEnd, OrderBookError> {
+    // Combined stream URL for all currently subscribed streams
+    let ws_url = if streams.is_empty() {
+        format!("{}/stream", base_url)
+    } else {
+        let streams: Vec<_> = streams.iter().map(String::as_str).collect();
+        format!("{}/stream?streams={}", base_url, streams.join("/"))
+    }
     .into_client_request()?; // Convert the formatted URL string into a client request
 
     // Connect to the Binance WebSocket asynchronously
     let (ws_stream, _) = connect_async(ws_url).await?;
-    // Split the WebSocket stream into a writer (unused here) and a reader (used to receive messages)
-    let (_, mut read) = ws_stream.split();
-
-    // Print a confirmation message indicating that the WebSocket connection was successful
-    println!(
-        "{}",
-        format!("Connected to Binance stream for symbol: {}", symbol)
-            .green()
-            .bold()
-    );
-
-    // Asynchronously read messages from the WebSocket
-    while let Some(msg) = read.next().await {
-        match msg {
-            // Handle text messages (JSON format) from the WebSocket
-            Ok(Message::Text(text)) => {
-                // Try to parse the message as a `BookTickerUpdate`
-                if let Ok(book_ticker) = serde_json::from_str::<BookTickerUpdateReader>(&text) {
-                    // If parsing succeeds, send the BookTicker message through the channel
-                    tx.unbounded_send(BinanceMessage::BookTicker(book_ticker))?;
+    // Split the WebSocket stream into a writer (used for pongs and subscription requests) and a reader
+    let (mut write, mut read) = ws_stream.split();
+    let _ = events.unbounded_send(ConnectionEvent::Connected(
+        streams.iter().cloned().collect(),
+    ));
+
+    // Deadline to roll the connection over before the server's forced disconnect
+    let rollover = sleep(policy.max_connection_age);
+    tokio::pin!(rollover);
+
+    // Request ID for SUBSCRIBE/UNSUBSCRIBE messages
+    let mut request_id = 0;
+
+    loop {
+        tokio::select! {
+            // Asynchronously read messages from the WebSocket
+            msg = read.next() => match msg {
+                // Handle text messages (JSON format) from the WebSocket
+                Some(Ok(Message::Text(text))) => {
+                    // Stamp the frame as early as possible, before parsing
+                    let recv_ts = SystemTime::now();
+                    let message = parse_stream_message(&text);
+                    if let Some(recorder) = recorder.filter(|recorder| recorder.is_enabled()) {
+                        let stream = message.as_ref().map(|message| message.stream.clone());
+                        recorder.record(RecordedFrame::new(recv_ts, stream, text.clone()));
+                    }
+
+                    // If the envelope was parsed, send it through the channel
+                    if let Some(message) = message {
+                        FeedMetrics::global().record_received(&message);
+                        if tx.unbounded_send(message).is_err() {
+                            return Ok(ConnectionEnd::Shutdown);
+                        }
+                    } else {
+                        // Subscription responses and unsupported messages end up here
+                        // Envelopes whose payload does not parse are counted as parse errors
+                        if let Ok(envelope) = serde_json::from_str::<CombinedStreamReader>(&text) {
+                            FeedMetrics::global().record_parse_error();
+                            // A lost book message can only be recovered from the snapshots of a new connection
+                            if is_book_stream(&envelope.stream) {
+                                let _ = write.send(Message::Close(None)).await;
+                                return Ok(ConnectionEnd::Closed(format!("Error parsing {} message", envelope.stream)));
+                            }
+                        }
+                        log::debug!("Ignoring message: {}", text);
+                    }
                 }
-                // Try to parse the message as a `DepthUpdate`
-                else if let Ok(depth_update) = serde_json::from_str::<DepthUpdateReader>(&text) {
-                    // If parsing succeeds, send the DepthUpdate message through the channel
-                    tx.unbounded_send(BinanceMessage::DepthUpdate(depth_update))?;
+                // Answer server pings so the connection is kept alive
+                Some(Ok(Message::Ping(payload))) => {
+                    if let Err(e) = write.send(Message::Pong(payload)).await {
+                        return Ok(ConnectionEnd::Closed(OrderBookError::from(e).to_string()));
+                    }
+                }
+                // Handle WebSocket close message
+                Some(Ok(Message::Close(frame))) => {
+                    let reason = frame.map_or_else(String::new, |frame| frame.reason.to_string());
+                    return Ok(ConnectionEnd::Closed(format!("WebSocket connection closed. {}", reason)));
+                }
+                None => return Ok(ConnectionEnd::Closed("WebSocket connection closed.".to_string())),
+                // Handle any error that occurs while receiving a WebSocket message
+                Some(Err(e)) => return Ok(ConnectionEnd::Closed(OrderBookError::from(e).to_string())),
+                // Ignore other types of messages (e.g., binary)
+                Some(Ok(_)) => {}
+            },
+            // Forward subscription changes to Binance and remember them for reconnects
+            Some(command) = commands.next() => {
+                match &command {
+                    StreamCommand::Subscribe(names) => streams.extend(names.iter().cloned()),
+                    StreamCommand::Unsubscribe(names) => streams.retain(|name| !names.contains(name)),
+                }
+                request_id += 1;
+                if let Err(e) = write.send(Message::Text(command.to_json(request_id))).await {
+                    return Ok(ConnectionEnd::Closed(OrderBookError::from(e).to_string()));
                 }
             }
-            // Handle WebSocket close message
-            Ok(Message::Close(_)) => {
-                // Print a message indicating that the WebSocket connection has been closed
-                println!("WebSocket connection closed.");
-                break;
-            }
-            // Handle any error that occurs while receiving a WebSocket message
-            Err(e) => {
-                // Print an error message
-                eprintln!("{}", OrderBookError::ConnectionError(e).to_string().red());
-                break;
+            // Roll the connection over before it gets too old
+            _ = &mut rollover => {
+                let _ = write.send(Message::Close(None)).await;
+                return Ok(ConnectionEnd::RolledOver);
             }
-            // Ignore other types of messages (e.g., binary)
-            _ => {}
         }
     }
-
-    Ok(())
 }
 
-/// Function to process Binance WebSocket messages and update the orderbook accordingly
-pub async fn process_binance_messages(
-    orderbook: &Arc<Mutex<OrderBook>>, // A shared, thread-safe reference to the orderbook
-    rx: &Arc<Mutex<UnboundedReceiver<BinanceMessage>>>, // A shared, thread-safe reference to the receiver channel for Binance messages
+/// Function to run a supervised connection to the public book stream of any venue through its adapter
+/// The adapter is reset and the symbols are resubscribed on every connection, which makes the venue send
+/// fresh snapshots. Failed connections, lost messages and connections older than the policy's maximum
+/// age are re-established with the same backoff as the Binance client.
+pub async fn market_data_feed_client<F: MarketDataFeed>(
+    mut feed: F,                              // The adapter of the venue
+    symbols: Vec<String>,                     // The venue symbols to subscribe to (e.g., BTC-USD)
+    tx: UnboundedSender<FeedEvent>,           // The channel to send the decoded events to
+    events: UnboundedSender<ConnectionEvent>, // The channel to report connection events on
+    policy: ReconnectPolicy,                  // Backoff and rollover settings
 ) -> Result<(), OrderBookError> {
-    // Lock the orderbook and receiver to ensure thread-safe access
-    let mut orderbook = orderbook.lock().await;
-    let mut rx_locked = rx.lock().await;
-
-    // Check if there are any messages received from the WebSocket
-    if let Some(message) = rx_locked.next().await {
-        // Match the type of Binance message (either BookTicker or DepthUpdate)
-        match message {
-            // Handle `BookTicker` update messages
-            BinanceMessage::BookTicker(update) => {
-                // Print the BookTicker update to the console (for debugging)
-                println!("{}", format!("Book Ticker Update: {:#?}", update).blue());
-
-                // Ensure the symbol in the update matches the symbol in the orderbook
-                orderbook.is_symbol_same(&update.symbol)?;
-
-                // Ensure the update is sequential based on `lastUpdateId`
-                orderbook.is_update_sequential(update.last_update_id)?;
-
-                // Convert the update to a `BookTickerUpdate` and apply it to the orderbook
-                let book_ticker_update = BookTickerUpdate::from_reader(update)?;
-                orderbook.update_book_ticker(&book_ticker_update);
+    // Number of consecutive failed connection attempts
+    let mut attempt = 0;
+
+    loop {
+        feed.reset();
+        let reason = match run_feed_connection(&mut feed, &symbols, &tx, &events, &policy).await {
+            // Nobody is receiving events anymore, stop the client
+            Ok(ConnectionEnd::Shutdown) => return Ok(()),
+            Ok(ConnectionEnd::RolledOver) => {
+                attempt = 0;
+                FeedMetrics::global().record_reconnect("rollover");
+                let _ = events.unbounded_send(ConnectionEvent::RolledOver);
+                continue;
             }
-            // Handle `DepthUpdate` update messages
-            BinanceMessage::DepthUpdate(update) => {
-                // Print the DepthUpdate to the console (for debugging)
-                println!("{}", format!("Depth Update: {:#?}", update).yellow());
+            Ok(ConnectionEnd::Closed(reason)) => {
+                attempt = 0;
+                reason
+            }
+            Err(e) => e.to_string(),
+        };
+
+        attempt += 1;
+        FeedMetrics::global().record_reconnect("failure");
+        let delay = policy.backoff(attempt);
+        let _ = events.unbounded_send(ConnectionEvent::Disconnected(reason));
+        let _ = events.unbounded_send(ConnectionEvent::Reconnecting { attempt, delay });
+        sleep(delay).await;
+    }
+}
 
-                // Ensure the update is sequential based on `lastUpdateId`
-                orderbook.is_update_sequential(update.last_update_id)?;
+/// Function to run a single connection of a venue adapter until it closes, fails, loses messages or has to be rolled over
+async fn run_feed_connection<F: MarketDataFeed>(
+    feed: &mut F,
+    symbols: &[String],
+    tx: &UnboundedSender<FeedEvent>,
+    events: &UnboundedSender<ConnectionEvent>,
+    policy: &ReconnectPolicy,
+) -> Result<ConnectionEnd, OrderBookError> {
+    let (ws_stream, _) = connect_async(feed.url().into_client_request()?).await?;
+    let (mut write, mut read) = ws_stream.split();
+    for frame in feed.subscribe_frames(symbols) {
+        write.send(Message::Text(frame)).await?;
+    }
+    let _ = events.unbounded_send(ConnectionEvent::Connected(symbols.to_vec()));
 
-                // Convert the update to a `DepthUpdate` and apply it to the orderbook
-                let depth_update = DepthUpdate::from_reader(update);
-                orderbook.update_depth(&depth_update);
+    // Deadline to roll the connection over
+    let rollover = sleep(policy.max_connection_age);
+    tokio::pin!(rollover);
+
+    loop {
+        tokio::select! {
+            msg = read.next() => match msg {
+                Some(Ok(Message::Text(text))) => match feed.decode(&text) {
+                    Ok(decoded) => {
+                        for event in decoded {
+                            if tx.unbounded_send(event).is_err() {
+                                return Ok(ConnectionEnd::Shutdown);
+                            }
+                        }
+                    }
+                    // Lost or undecodable book messages can only be recovered from the snapshots of a new connection
+                    Err(e) => {
+                        if !matches!(e, OrderBookError::SequenceGap(_)) {
+                            FeedMetrics::global().record_parse_error();
+                            log::warn!("Dropping connection after a book message failed to decode ({}): {}", e, text);
+                        }
+                        let _ = write.send(Message::Close(None)).await;
+                        return Ok(ConnectionEnd::Closed(e.to_string()));
+                    }
+                },
+                Some(Ok(Message::Ping(payload))) => {
+                    if let Err(e) = write.send(Message::Pong(payload)).await {
+                        return Ok(ConnectionEnd::Closed(OrderBookError::from(e).to_string()));
+                    }
+                }
+                Some(Ok(Message::Close(frame))) => {
+                    let reason = frame.map_or_else(String::new, |frame| frame.reason.to_string());
+                    return Ok(ConnectionEnd::Closed(format!("WebSocket connection closed. {}", reason)));
+                }
+                None => return Ok(ConnectionEnd::Closed("WebSocket connection closed.".to_string())),
+                Some(Err(e)) => return Ok(ConnectionEnd::Closed(OrderBookError::from(e).to_string())),
+                Some(Ok(_)) => {}
+            },
+            _ = &mut rollover => {
+                let _ = write.send(Message::Close(None)).await;
+                return Ok(ConnectionEnd::RolledOver);
             }
         }
+    }
+}
+
+/// Function to bootstrap a diff book from snapshots fetched from `source` while it has a snapshot request to make
+/// The book is borrowed during the fetch, so books shared with other tasks are bootstrapped by
+/// [`sync_shared_book`] instead, which does not hold their lock while fetching.
+pub async fn sync_from_snapshot<S: SnapshotSource>(
+    orderbook: &mut OrderBook, // The orderbook buffering diff events
+    source: &S,                // The source to fetch depth snapshots from
+) -> Result<(), OrderBookError> {
+    let mut result = Ok(());
+    while orderbook.request_snapshot(now_millis()) {
+        let snapshot = source.fetch_depth_snapshot(orderbook.symbol()).await;
+        result = apply_fetched_snapshot(orderbook, snapshot);
+    }
+    result
+}
+
+/// Function to apply the outcome of a claimed snapshot request to the diff book that made it
+/// A failed fetch delays the book's next request; an outdated snapshot is refetched right away a few times.
+pub fn apply_fetched_snapshot(
+    orderbook: &mut OrderBook, // The orderbook buffering diff events
+    snapshot: Result<DepthUpdateReader, OrderBookError>, // The fetched snapshot, or why it could not be fetched
+) -> Result<(), OrderBookError> {
+    let result = snapshot
+        .and_then(DepthUpdate::from_reader)
+        .and_then(|snapshot| orderbook.apply_depth_snapshot(&snapshot));
+    orderbook.complete_snapshot_request(&result, now_millis());
+    result?;
+
+    // Snapshots carry no event time; they are sampled on the clock of the buffered events they bridge
+    orderbook.refresh_metrics();
+    orderbook.sample_mid(orderbook.clock());
+    orderbook.check_crossed()
+}
 
-        // After processing the message, display the current best bid and ask prices
-        display_best_bid_ask(&orderbook, |orderbook| orderbook.get_best_bid_ask());
+// Function to count and log the prices and quantities of an update that break the symbol's filters
+// The update is applied anyway: dropping it would break the stream's update sequence and force a resync
+fn flag_filters(orderbook: &mut OrderBook, levels: &[(Price, Qty)]) {
+    let prices: Vec<Price> = levels.iter().map(|(price, _)| *price).collect();
+    let qtys: Vec<Qty> = levels.iter().map(|(_, qty)| *qty).collect();
+    let results = [
+        orderbook.check_tick_size(&prices),
+        orderbook.check_step_size(&qtys),
+    ];
+    for e in results.into_iter().filter_map(Result::err) {
+        log::warn!("{}: {}", orderbook.symbol(), e);
     }
+}
+
+/// Function to validate a Binance message and apply it to the orderbook
+/// Diff depth events trigger a snapshot fetch from `source` while the book is not yet synced; failed
+/// fetches are retried with a backoff on later events
+pub async fn apply_binance_message<S: SnapshotSource>(
+    orderbook: &mut OrderBook, // The orderbook to update
+    message: BinanceMessage,   // The message to apply
+    source: &S,                // The source to fetch depth snapshots from
+) -> Result<(), OrderBookError> {
+    let result = apply_binance_update(orderbook, message, None);
+    let synced = sync_from_snapshot(orderbook, source).await;
+    result.and(synced)
+}
 
-    Ok(())
+/// Function to validate a Binance message and apply it to the orderbook without any I/O
+/// Diff depth events are buffered while the book is not yet synced; [`OrderBook::request_snapshot`]
+/// tells whether a snapshot has to be fetched for it.
+pub fn apply_binance_update(
+    orderbook: &mut OrderBook, // The orderbook to update
+    message: BinanceMessage,   // The message to apply
+    recv_time: Option<u64>, // Recorded receive time of a replayed message, None for live messages
+) -> Result<(), OrderBookError> {
+    let event_time = message.event_time();
+
+    // Match the type of Binance message (book, trade, kline or futures events)
+    match message {
+        // Handle `BookTicker` update messages
+        BinanceMessage::BookTicker(update) => {
+            // Log the BookTicker update (for debugging)
+            log::debug!("Book Ticker Update: {:#?}", update);
+
+            // Ensure the symbol in the update matches the symbol in the orderbook
+            orderbook.is_symbol_same(&update.symbol)?;
+
+            // Ensure the update is sequential within the book ticker stream
+            orderbook.check_update_sequence(StreamKind::BookTicker, update.last_update_id)?;
+
+            // Convert the update to a `BookTickerUpdate` and apply it to the orderbook
+            let book_ticker_update = BookTickerUpdate::from_reader(update)?;
+            flag_filters(orderbook, &book_ticker_update.levels());
+            orderbook.update_book_ticker(&book_ticker_update);
+        }
+        // Handle `DepthUpdate` update messages
+        BinanceMessage::DepthUpdate(update) => {
+            // Log the DepthUpdate (for debugging)
+            log::debug!("Depth Update: {:#?}", update);
+
+            // Ensure the update is sequential within the partial depth stream
+            orderbook.check_update_sequence(StreamKind::PartialDepth, update.last_update_id)?;
+
+            // Convert the update to a `DepthUpdate` and apply it to the orderbook
+            let depth_update = DepthUpdate::from_reader(update)?;
+            flag_filters(orderbook, &depth_update.levels());
+            orderbook.update_depth(&depth_update);
+        }
+        // Handle diff depth `depthUpdate` events
+        BinanceMessage::DepthDiff(update) => {
+            // Log the DepthDiff (for debugging)
+            log::debug!("Depth Diff: {:#?}", update);
+
+            // Ensure the symbol in the update matches the symbol in the orderbook
+            orderbook.is_symbol_same(&update.symbol)?;
+
+            // Buffer or apply the event
+            let depth_diff = DepthDiff::from_reader(update)?;
+            flag_filters(orderbook, &depth_diff.levels());
+            orderbook.apply_depth_diff(depth_diff)?;
+        }
+        // Handle `trade` events; trades do not change the book, so its checks are skipped
+        BinanceMessage::Trade(trade) => {
+            orderbook.is_symbol_same(&trade.symbol)?;
+            orderbook.check_update_sequence(StreamKind::Trade, trade.trade_id)?;
+            orderbook.record_trade(StreamKind::Trade, Trade::from_reader(trade)?);
+            return Ok(());
+        }
+        // Handle `aggTrade` events
+        BinanceMessage::AggTrade(trade) => {
+            orderbook.is_symbol_same(&trade.symbol)?;
+            orderbook.check_update_sequence(StreamKind::AggTrade, trade.agg_trade_id)?;
+            orderbook.record_trade(StreamKind::AggTrade, Trade::from_agg_reader(trade)?);
+            return Ok(());
+        }
+        // Handle `kline` events
+        BinanceMessage::Kline(kline) => {
+            orderbook.is_symbol_same(&kline.symbol)?;
+            orderbook.record_kline(Candle::from_kline(kline.kline)?)?;
+            return Ok(());
+        }
+        // Handle futures `markPriceUpdate` events, sequenced by their event time
+        BinanceMessage::MarkPrice(mark_price) => {
+            orderbook.is_symbol_same(&mark_price.symbol)?;
+            orderbook.check_update_sequence(StreamKind::MarkPrice, mark_price.event_time)?;
+            orderbook.update_mark_price(MarkPrice::from_reader(mark_price)?);
+            return Ok(());
+        }
+        // Handle futures `forceOrder` (liquidation) events
+        BinanceMessage::ForceOrder(force_order) => {
+            orderbook.is_symbol_same(&force_order.order.symbol)?;
+            orderbook.record_liquidation(Liquidation::from_reader(force_order)?);
+            return Ok(());
+        }
+    }
+
+    // Keep the microstructure metrics up to date with the applied message
+    orderbook.refresh_metrics();
+
+    // Sample the mid price on the exchange clock; messages without an event time use their recorded
+    // receive time when replayed, or the local clock, which does not move the book's clock
+    let time = event_time.or(recv_time);
+    if let Some(time) = time {
+        orderbook.advance_clock(time);
+    }
+    orderbook.sample_mid(time.unwrap_or_else(now_millis));
+
+    // Report the book as being in an error state if the update left it crossed
+    orderbook.check_crossed()
+}
+
+/// Function to apply a normalized feed event of any venue to the orderbook
+/// Diff events of venues without stream snapshots (Binance) trigger a snapshot fetch from `source`
+/// while the book is not yet synced; the other venues' diff events are buffered until their snapshot arrives.
+pub async fn apply_feed_event<S: SnapshotSource>(
+    orderbook: &mut OrderBook, // The orderbook to update
+    event: FeedEvent,          // The event to apply
+    source: &S,                // The source to fetch depth snapshots from
+) -> Result<(), OrderBookError> {
+    let rest_snapshots = event.venue.has_rest_snapshots();
+    let result = apply_feed_update(orderbook, event);
+    if !rest_snapshots {
+        return result;
+    }
+    let synced = sync_from_snapshot(orderbook, source).await;
+    result.and(synced)
+}
+
+/// Function to apply a normalized feed event of any venue to the orderbook without any I/O
+/// Diff events are buffered while the book is not yet synced; for venues without stream snapshots,
+/// [`OrderBook::request_snapshot`] tells whether a snapshot has to be fetched for them.
+pub fn apply_feed_update(
+    orderbook: &mut OrderBook, // The orderbook to update
+    event: FeedEvent,          // The event to apply
+) -> Result<(), OrderBookError> {
+    // Ensure the symbol of the event matches the symbol in the orderbook
+    orderbook.is_symbol_same(&event.symbol)?;
+
+    match event.update {
+        // A snapshot received on the stream resynchronizes the book
+        FeedUpdate::Snapshot(snapshot) => {
+            flag_filters(orderbook, &snapshot.levels());
+            orderbook.apply_depth_snapshot(&snapshot)?;
+        }
+        FeedUpdate::TopLevels(depth) => {
+            orderbook.check_update_sequence(StreamKind::PartialDepth, depth.last_update_id())?;
+            flag_filters(orderbook, &depth.levels());
+            orderbook.update_depth(&depth);
+        }
+        FeedUpdate::Diff { diff, max_depth } => {
+            flag_filters(orderbook, &diff.levels());
+            orderbook.apply_depth_diff(diff)?;
+            if let Some(depth) = max_depth {
+                orderbook.truncate_depth(depth);
+            }
+        }
+        FeedUpdate::BestBidAsk(ticker) => {
+            orderbook.check_update_sequence(StreamKind::BookTicker, ticker.last_update_id())?;
+            flag_filters(orderbook, &ticker.levels());
+            orderbook.update_book_ticker(&ticker);
+        }
+        // Trades do not change the book, so its checks are skipped
+        FeedUpdate::Trade(stream, trade) => {
+            orderbook.check_update_sequence(stream, trade.id)?;
+            orderbook.record_trade(stream, trade);
+            return Ok(());
+        }
+    }
+
+    orderbook.refresh_metrics();
+    if let Some(time) = event.event_time {
+        orderbook.advance_clock(time);
+    }
+    orderbook.sample_mid(event.event_time.unwrap_or_else(now_millis));
+    orderbook.check_crossed()
 }
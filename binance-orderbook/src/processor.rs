@@ -0,0 +1,167 @@
+use super::*;
+
+/// Struct controlling the long-running task that applies every stream message to the order books
+/// The task owns the message receiver; readers share the books through a `RwLock` and only wait
+/// for the message currently being applied. Depth snapshots are fetched by separate tasks without the lock.
+pub struct BookProcessor<S> {
+    // The order books updated by the task
+    manager: Arc<RwLock<BookManager>>,
+
+    // The source to fetch depth snapshots from when bootstrapping a diff depth book
+    source: S,
+
+    // Channel to tell the task whether to run, pause or stop
+    control: watch::Sender<ProcessingState>,
+
+    // The running (or paused) task, which hands the receiver back when it stops
+    task: Option<JoinHandle<UnboundedReceiver<StreamMessage>>>,
+
+    // The receiver while no task is running
+    rx: Option<UnboundedReceiver<StreamMessage>>,
+}
+
+impl<S> BookProcessor<S>
+where
+    S: SnapshotSource + Clone + Send + Sync + 'static,
+{
+    /// Constructor function to create a stopped BookProcessor
+    pub fn new(
+        manager: Arc<RwLock<BookManager>>,
+        rx: UnboundedReceiver<StreamMessage>,
+        source: S,
+    ) -> Self {
+        Self {
+            manager,
+            source,
+            control: watch::channel(ProcessingState::Stopped).0,
+            task: None,
+            rx: Some(rx),
+        }
+    }
+
+    /// Function to get the snapshot source used by the task
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// Function to get the current processing state
+    pub fn state(&self) -> ProcessingState {
+        *self.control.borrow()
+    }
+
+    /// Function to start processing, or resume it if it is paused
+    pub fn start(&mut self) {
+        self.control.send_replace(ProcessingState::Running);
+        if let Some(rx) = self.rx.take() {
+            self.task = Some(tokio::spawn(maintain_books(
+                Arc::clone(&self.manager),
+                rx,
+                self.control.subscribe(),
+                self.source.clone(),
+            )));
+        }
+    }
+
+    /// Function to pause processing; incoming messages are queued until it is resumed
+    pub fn pause(&mut self) {
+        if self.task.is_some() {
+            self.control.send_replace(ProcessingState::Paused);
+        }
+    }
+
+    /// Function to wait until the task ends on its own because the stream has ended (e.g., a finished replay)
+    pub async fn wait(&mut self) {
+        if let Some(task) = self.task.take() {
+            match task.await {
+                Ok(rx) => self.rx = Some(rx),
+                Err(e) => log::error!("Processing task failed: {}", e),
+            }
+        }
+        self.control.send_replace(ProcessingState::Stopped);
+    }
+
+    /// Function to stop processing and take the receiver back from the task
+    pub async fn stop(&mut self) {
+        self.control.send_replace(ProcessingState::Stopped);
+        if let Some(task) = self.task.take() {
+            match task.await {
+                Ok(rx) => self.rx = Some(rx),
+                Err(e) => log::error!("Processing task failed: {}", e),
+            }
+        }
+    }
+}
+
+/// Function to apply every stream message to the order books until stopped or the stream ends
+/// Messages are left in the channel while processing is paused. Returns the receiver so processing can be restarted.
+/// A diff book waiting for a snapshot gets it from a [`sync_shared_book`] task, so the lock is never held
+/// during the fetch.
+pub async fn maintain_books<S>(
+    manager: Arc<RwLock<BookManager>>, // A shared, thread-safe reference to the book manager
+    mut rx: UnboundedReceiver<StreamMessage>, // The receiver channel for Binance messages
+    mut control: watch::Receiver<ProcessingState>, // The channel telling the task to run, pause or stop
+    source: S,                                     // The source to fetch depth snapshots from
+) -> UnboundedReceiver<StreamMessage>
+where
+    S: SnapshotSource + Clone + Send + Sync + 'static,
+{
+    loop {
+        let state = *control.borrow_and_update();
+        match state {
+            ProcessingState::Stopped => break,
+            // Wait for the next state change while paused
+            ProcessingState::Paused => {
+                if control.changed().await.is_err() {
+                    break;
+                }
+            }
+            ProcessingState::Running => tokio::select! {
+                // Re-evaluate the state as soon as it changes
+                changed = control.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                }
+                message = rx.next() => {
+                    // The stream client has shut down
+                    let Some(message) = message else {
+                        break;
+                    };
+                    FeedMetrics::global().record_dequeued();
+                    let symbol = message.symbol();
+                    let mut books = manager.write().await;
+                    if let Err(e) = books.apply(message) {
+                        log::warn!("{}: {}", symbol, e);
+                    }
+                    let fetch = books.request_snapshot(&symbol);
+                    drop(books);
+                    if fetch {
+                        tokio::spawn(sync_shared_book(Arc::clone(&manager), symbol, source.clone()));
+                    }
+                }
+            },
+        }
+    }
+
+    rx
+}
+
+/// Function to bootstrap a shared diff book whose snapshot request was claimed
+/// The snapshot is fetched without holding the manager's lock, which is only taken to apply it, so
+/// readers and the other books never wait on the network. Outdated snapshots are refetched right away.
+pub async fn sync_shared_book<S: SnapshotSource>(
+    manager: Arc<RwLock<BookManager>>, // A shared, thread-safe reference to the book manager
+    symbol: String,                    // Symbol of the book that claimed the snapshot request
+    source: S,                         // The source to fetch depth snapshots from
+) {
+    loop {
+        let snapshot = source.fetch_depth_snapshot(&symbol).await;
+        let mut books = manager.write().await;
+        if let Err(e) = books.apply_snapshot(&symbol, snapshot) {
+            log::warn!("{}: {}", symbol, e);
+        }
+        if !books.request_snapshot(&symbol) {
+            break;
+        }
+    }
+}
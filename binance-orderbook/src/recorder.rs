@@ -0,0 +1,227 @@
+use super::*;
+
+/// Struct holding the settings of the raw stream recorder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecorderConfig {
+    /// Directory the recording files are written to (created if missing)
+    pub directory: PathBuf,
+
+    /// File name prefix; files are named `{prefix}.{first receive timestamp}.jsonl[.gz]`
+    pub prefix: String,
+
+    /// Size after which a new file is started (uncompressed bytes)
+    pub max_file_bytes: u64,
+
+    /// Whether the files are gzip compressed
+    pub compress: bool,
+}
+
+impl Default for RecorderConfig {
+    fn default() -> Self {
+        Self {
+            directory: PathBuf::from("recordings"),
+            prefix: "binance".to_string(),
+            max_file_bytes: 256 * 1024 * 1024,
+            compress: false,
+        }
+    }
+}
+
+/// Struct representing one recorded text frame, written as one JSON line
+/// Format (version 1): `{"recv_ts_ns":<u64>,"stream":<string or null>,"frame":<string>}`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    /// Local receive time in nanoseconds since the UNIX epoch
+    pub recv_ts_ns: u64,
+
+    /// Stream name of a combined stream envelope, None for other frames (e.g., subscription responses)
+    pub stream: Option<String>,
+
+    /// The text frame exactly as it was received
+    pub frame: String,
+}
+
+impl RecordedFrame {
+    /// Function to create a frame record stamped with the given local receive time
+    pub fn new(recv_ts: SystemTime, stream: Option<String>, frame: String) -> Self {
+        let recv_ts_ns = recv_ts
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+        Self {
+            recv_ts_ns,
+            stream,
+            frame,
+        }
+    }
+}
+
+/// Function to read the frames of a recording file (plain or `.gz`) in the order they were received
+pub fn read_recording(
+    path: impl AsRef<Path>,
+) -> Result<impl Iterator<Item = Result<RecordedFrame, OrderBookError>> + Send, OrderBookError> {
+    let path = path.as_ref();
+    let file = File::open(path)?;
+    let reader: Box<dyn BufRead + Send> = if path.extension().is_some_and(|ext| ext == "gz") {
+        Box::new(BufReader::new(GzDecoder::new(file)))
+    } else {
+        Box::new(BufReader::new(file))
+    };
+
+    Ok(reader
+        .lines()
+        .filter(|line| !line.as_ref().is_ok_and(|line| line.trim().is_empty()))
+        .map(|line| Ok(serde_json::from_str(&line?)?)))
+}
+
+/// Struct handing received frames to a background writer while recording is switched on
+#[derive(Debug)]
+pub struct StreamRecorder {
+    // Whether incoming frames are currently recorded
+    enabled: AtomicBool,
+
+    // Channel to the writer thread
+    frames: std::sync::mpsc::Sender<RecordedFrame>,
+}
+
+impl StreamRecorder {
+    /// Function to start the background writer; recording starts switched on if `enabled` is set
+    /// The writer ends with the last recorder handle and returns the first write error, if any
+    pub fn start(
+        config: RecorderConfig,
+        enabled: bool,
+    ) -> (Arc<Self>, JoinHandle<Result<(), OrderBookError>>) {
+        let (frames, rx) = std::sync::mpsc::channel::<RecordedFrame>();
+        let writer = tokio::task::spawn_blocking(move || {
+            let mut writer = RollingWriter::new(config);
+            while let Ok(frame) = rx.recv() {
+                writer.write(&frame)?;
+                // Write everything already queued, then flush so a crash loses as little as possible
+                for frame in rx.try_iter() {
+                    writer.write(&frame)?;
+                }
+                writer.flush()?;
+            }
+            writer.finish()
+        });
+
+        let recorder = Arc::new(Self {
+            enabled: AtomicBool::new(enabled),
+            frames,
+        });
+        (recorder, writer)
+    }
+
+    /// Function to switch recording on or off
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, AtomicOrdering::Relaxed);
+    }
+
+    /// Function to check whether recording is switched on
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(AtomicOrdering::Relaxed)
+    }
+
+    /// Function to record a frame if recording is switched on
+    pub fn record(&self, frame: RecordedFrame) {
+        if self.is_enabled() && self.frames.send(frame).is_err() {
+            // The writer stopped after an error, there is nothing left to record to
+            log::warn!("Stream recorder is no longer running");
+            self.set_enabled(false);
+        }
+    }
+}
+
+/// Enum representing an open recording file
+enum RecordingFile {
+    // Plain JSON lines
+    Plain(BufWriter<File>),
+
+    // Gzip compressed JSON lines
+    Gzip(GzEncoder<BufWriter<File>>),
+}
+
+/// Struct writing frames as JSON lines, starting a new file whenever the current one is full
+struct RollingWriter {
+    // Settings of the recorder
+    config: RecorderConfig,
+
+    // The file currently written to, opened on the first frame
+    file: Option<RecordingFile>,
+
+    // Uncompressed bytes written to the current file
+    written: u64,
+}
+
+impl RollingWriter {
+    // Constructor function to create a writer without an open file
+    fn new(config: RecorderConfig) -> Self {
+        Self {
+            config,
+            file: None,
+            written: 0,
+        }
+    }
+
+    // Function to append a frame, rotating the file first if it is full
+    fn write(&mut self, frame: &RecordedFrame) -> Result<(), OrderBookError> {
+        let mut line = serde_json::to_vec(frame)?;
+        line.push(b'\n');
+
+        if self.file.is_some() && self.written + line.len() as u64 > self.config.max_file_bytes {
+            self.finish()?;
+        }
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => self.file.insert(self.open(frame.recv_ts_ns)?),
+        };
+
+        match file {
+            RecordingFile::Plain(writer) => writer.write_all(&line)?,
+            RecordingFile::Gzip(writer) => writer.write_all(&line)?,
+        }
+        self.written += line.len() as u64;
+        Ok(())
+    }
+
+    // Function to open a new file named after the receive timestamp of its first frame
+    fn open(&self, recv_ts_ns: u64) -> Result<RecordingFile, OrderBookError> {
+        std::fs::create_dir_all(&self.config.directory)?;
+        let extension = if self.config.compress {
+            "jsonl.gz"
+        } else {
+            "jsonl"
+        };
+        let path = self.config.directory.join(format!(
+            "{}.{}.{}",
+            self.config.prefix, recv_ts_ns, extension
+        ));
+
+        let writer = BufWriter::new(File::create(path)?);
+        Ok(if self.config.compress {
+            RecordingFile::Gzip(GzEncoder::new(writer, Compression::default()))
+        } else {
+            RecordingFile::Plain(writer)
+        })
+    }
+
+    // Function to flush the buffered frames to the current file
+    fn flush(&mut self) -> Result<(), OrderBookError> {
+        match &mut self.file {
+            Some(RecordingFile::Plain(writer)) => writer.flush()?,
+            Some(RecordingFile::Gzip(writer)) => writer.flush()?,
+            None => {}
+        }
+        Ok(())
+    }
+
+    // Function to flush and close the current file
+    fn finish(&mut self) -> Result<(), OrderBookError> {
+        match self.file.take() {
+            Some(RecordingFile::Plain(mut writer)) => writer.flush()?,
+            Some(RecordingFile::Gzip(writer)) => writer.finish()?.flush()?,
+            None => {}
+        }
+        self.written = 0;
+        Ok(())
+    }
+}
@@ -0,0 +1,295 @@
+use super::*;
+
+/// Suffix of the pseudo stream name depth snapshots are recorded under (e.g., `bnbusdt@depthSnapshot`)
+pub const SNAPSHOT_STREAM_SUFFIX: &str = "@depthSnapshot";
+
+/// Enum representing how fast a recording is replayed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    /// Frames are sent without waiting
+    AsFastAsPossible,
+
+    /// Frames are sent with the gaps between their receive timestamps
+    RealTime,
+
+    /// Frames are sent with the gaps between their receive timestamps divided by the factor
+    Multiplier(f64),
+}
+
+impl FromStr for ReplaySpeed {
+    type Err = OrderBookError;
+
+    /// Parses `max`, `realtime` or a factor such as `10` or `10x`
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "max" => Ok(ReplaySpeed::AsFastAsPossible),
+            "realtime" | "1" | "1x" => Ok(ReplaySpeed::RealTime),
+            factor => factor
+                .trim_end_matches('x')
+                .parse::<f64>()
+                .ok()
+                .filter(|factor| *factor > 0.0 && factor.is_finite())
+                .map(ReplaySpeed::Multiplier)
+                .ok_or_else(|| {
+                    OrderBookError::ParseError(format!("invalid replay speed: {:?}", value))
+                }),
+        }
+    }
+}
+
+impl ReplaySpeed {
+    // Function to get how long to wait between two frames received `gap` apart
+    fn delay(&self, gap: Duration) -> Duration {
+        match self {
+            ReplaySpeed::AsFastAsPossible => Duration::ZERO,
+            ReplaySpeed::RealTime => gap,
+            ReplaySpeed::Multiplier(factor) => gap.div_f64(*factor),
+        }
+    }
+}
+
+/// Struct holding the number of frames a replay has processed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReplayStats {
+    /// Frames read from the recording
+    pub frames: u64,
+
+    /// Stream messages sent to the processing channel
+    pub messages: u64,
+
+    /// Depth snapshots handed to the snapshot source
+    pub snapshots: u64,
+
+    /// Frames that were neither stream messages nor snapshots (e.g., subscription responses)
+    pub skipped: u64,
+}
+
+/// Struct holding the recorded snapshots that have not been fetched yet
+#[derive(Debug, Default)]
+struct RecordedSnapshots {
+    // Raw snapshot JSON per uppercase symbol, in recording order
+    pending: HashMap<String, Vec<String>>,
+
+    // Whether the replay has ended, so no further snapshots will arrive
+    finished: bool,
+}
+
+/// Struct representing a snapshot source serving the depth snapshots of a recording
+/// A fetch waits until the replay reaches the next recorded snapshot of the symbol, so diff books
+/// are bootstrapped exactly like they were during the recorded session.
+#[derive(Debug, Clone)]
+pub struct ReplaySnapshotSource {
+    // Snapshots published by the replay
+    snapshots: Arc<watch::Sender<RecordedSnapshots>>,
+}
+
+impl Default for ReplaySnapshotSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReplaySnapshotSource {
+    /// Constructor function to create an empty ReplaySnapshotSource
+    pub fn new() -> Self {
+        Self {
+            snapshots: Arc::new(watch::channel(RecordedSnapshots::default()).0),
+        }
+    }
+
+    /// Function to make a recorded snapshot available for the given symbol
+    pub fn publish(&self, symbol: &str, snapshot: String) {
+        self.snapshots.send_modify(|snapshots| {
+            snapshots
+                .pending
+                .entry(symbol.to_uppercase())
+                .or_default()
+                .push(snapshot)
+        });
+    }
+
+    /// Function to signal that the replay has ended; pending and later fetches fail once no snapshot is left
+    pub fn finish(&self) {
+        self.snapshots
+            .send_modify(|snapshots| snapshots.finished = true);
+    }
+}
+
+impl SnapshotSource for ReplaySnapshotSource {
+    async fn fetch_depth_snapshot(
+        &self,
+        symbol: &str,
+    ) -> Result<DepthUpdateReader, OrderBookError> {
+        let symbol = symbol.to_uppercase();
+        let has_snapshot = |snapshots: &RecordedSnapshots| {
+            snapshots
+                .pending
+                .get(&symbol)
+                .is_some_and(|pending| !pending.is_empty())
+        };
+
+        // Wait until the replay reaches a snapshot of the symbol, or ends
+        let mut receiver = self.snapshots.subscribe();
+        let _ = receiver
+            .wait_for(|snapshots| snapshots.finished || has_snapshot(snapshots))
+            .await;
+
+        // Take the oldest pending snapshot, each recorded snapshot is served once
+        let mut snapshot = None;
+        self.snapshots.send_if_modified(|snapshots| {
+            snapshot = snapshots
+                .pending
+                .get_mut(&symbol)
+                .filter(|pending| !pending.is_empty())
+                .map(|pending| pending.remove(0));
+            snapshot.is_some()
+        });
+
+        match snapshot {
+            Some(snapshot) => Ok(serde_json::from_str(&snapshot)?),
+            None => Err(OrderBookError::SnapshotUnavailable(format!(
+                "no recorded depth snapshot left for {}",
+                symbol
+            ))),
+        }
+    }
+}
+
+/// Struct representing a snapshot source that records every fetched snapshot while recording is switched on
+/// Recorded snapshots let a replay bootstrap diff books without network access.
+#[derive(Debug, Clone)]
+pub struct RecordingSnapshotSource<S> {
+    // The source the snapshots are fetched from
+    inner: S,
+
+    // The recorder the snapshots are written to
+    recorder: Arc<StreamRecorder>,
+}
+
+impl<S> RecordingSnapshotSource<S> {
+    /// Constructor function to record the snapshots of `inner` with `recorder`
+    pub fn new(inner: S, recorder: Arc<StreamRecorder>) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+impl<S: SnapshotSource + Sync> SnapshotSource for RecordingSnapshotSource<S> {
+    async fn fetch_depth_snapshot(
+        &self,
+        symbol: &str,
+    ) -> Result<DepthUpdateReader, OrderBookError> {
+        let snapshot = self.inner.fetch_depth_snapshot(symbol).await?;
+        if self.recorder.is_enabled() {
+            let stream = format!("{}{}", symbol.to_lowercase(), SNAPSHOT_STREAM_SUFFIX);
+            self.recorder.record(RecordedFrame::new(
+                SystemTime::now(),
+                Some(stream),
+                serde_json::to_string(&snapshot)?,
+            ));
+        }
+        Ok(snapshot)
+    }
+}
+
+/// Function to replay recording files in order into the processing channel, like the live stream client
+/// Recorded depth snapshots are handed to `snapshots`, which is finished when the replay ends
+pub async fn replay_recording(
+    paths: &[PathBuf],                  // Recording files, replayed one after the other
+    tx: UnboundedSender<StreamMessage>, // The channel to send the recorded messages to the book processor
+    speed: ReplaySpeed,                 // How fast the frames are replayed
+    snapshots: &ReplaySnapshotSource,   // The source serving the recorded depth snapshots
+) -> Result<ReplayStats, OrderBookError> {
+    let result = replay_frames(paths, &tx, speed, snapshots).await;
+    snapshots.finish();
+    result
+}
+
+// Function to send the frames of the recording files, pacing them by their receive timestamps
+async fn replay_frames(
+    paths: &[PathBuf],
+    tx: &UnboundedSender<StreamMessage>,
+    speed: ReplaySpeed,
+    snapshots: &ReplaySnapshotSource,
+) -> Result<ReplayStats, OrderBookError> {
+    let mut stats = ReplayStats::default();
+    let mut previous_ts_ns = None;
+
+    for path in paths {
+        for frame in read_recording(path)? {
+            let frame = frame?;
+            stats.frames += 1;
+
+            // Wait for the recorded gap to the previous frame, scaled by the replay speed
+            if let Some(previous_ts_ns) = previous_ts_ns {
+                let gap = Duration::from_nanos(frame.recv_ts_ns.saturating_sub(previous_ts_ns));
+                let delay = speed.delay(gap);
+                if !delay.is_zero() {
+                    sleep(delay).await;
+                }
+            }
+            previous_ts_ns = Some(frame.recv_ts_ns);
+
+            // Recorded snapshots are served to the book processor on request
+            if let Some(symbol) = frame
+                .stream
+                .as_deref()
+                .and_then(|stream| stream.strip_suffix(SNAPSHOT_STREAM_SUFFIX))
+            {
+                snapshots.publish(symbol, frame.frame);
+                stats.snapshots += 1;
+                continue;
+            }
+
+            match recorded_stream_message(&frame) {
+                Some(message) => {
+                    FeedMetrics::global().record_received(&message);
+                    if tx.unbounded_send(message).is_err() {
+                        // Nobody is processing the messages anymore
+                        break;
+                    }
+                    stats.messages += 1;
+                }
+                None => stats.skipped += 1,
+            }
+        }
+    }
+
+    Ok(stats)
+}
+
+/// Function to collect the symbols of the stream messages and snapshots in recording files
+pub fn recording_symbols(paths: &[PathBuf]) -> Result<BTreeSet<String>, OrderBookError> {
+    let mut symbols = BTreeSet::new();
+    for path in paths {
+        for frame in read_recording(path)? {
+            let frame = frame?;
+            let snapshot_symbol = frame
+                .stream
+                .as_deref()
+                .and_then(|stream| stream.strip_suffix(SNAPSHOT_STREAM_SUFFIX))
+                .map(str::to_uppercase);
+            if let Some(symbol) =
+                snapshot_symbol.or_else(|| recorded_stream_message(&frame).map(|m| m.symbol()))
+            {
+                symbols.insert(symbol);
+            }
+        }
+    }
+    Ok(symbols)
+}
+
+/// Function to turn a recorded frame into a stream message stamped with the frame's receive time
+/// Frames are either combined stream envelopes or raw payloads (like `test-bed/*.json`) with a stream name
+pub fn recorded_stream_message(frame: &RecordedFrame) -> Option<StreamMessage> {
+    let message = parse_stream_message(&frame.frame).or_else(|| {
+        Some(StreamMessage {
+            stream: frame.stream.clone()?,
+            message: parse_binance_message(&frame.frame)?,
+            recv_time: None,
+        })
+    })?;
+    Some(StreamMessage {
+        recv_time: Some(frame.recv_ts_ns / 1_000_000),
+        ..message
+    })
+}
@@ -0,0 +1,97 @@
+use super::*;
+
+// Time after which a snapshot request is given up
+const SNAPSHOT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Trait for sources of order book depth snapshots used to bootstrap a diff depth book
+pub trait SnapshotSource {
+    /// Function to fetch a depth snapshot (`lastUpdateId`, `bids`, `asks`) for the given symbol
+    fn fetch_depth_snapshot(
+        &self,
+        symbol: &str,
+    ) -> impl Future<Output = Result<DepthUpdateReader, OrderBookError>> + Send;
+}
+
+/// Struct representing a snapshot source backed by the Binance REST depth endpoint of a market
+/// (`/api/v3/depth`, `/fapi/v1/depth` or `/dapi/v1/depth`)
+#[derive(Debug, Clone)]
+pub struct RestSnapshotSource {
+    // Base URL of the REST API (e.g., https://api.binance.com)
+    base_url: String,
+
+    // Market of the REST API, which selects the path of the depth endpoint
+    market: Market,
+
+    // Number of price levels requested per side
+    limit: u32,
+
+    // HTTP client used for the requests
+    client: reqwest::Client,
+}
+
+impl RestSnapshotSource {
+    /// Constructor function to create a new RestSnapshotSource for the spot REST API at the given base URL
+    pub fn new(base_url: &str, limit: u32) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            market: Market::Spot,
+            limit,
+            // A hung request would hold the book's only snapshot request
+            client: reqwest::Client::builder()
+                .timeout(SNAPSHOT_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Function to request the depth endpoint of another market at the same base URL
+    pub fn with_market(mut self, market: Market) -> Self {
+        self.market = market;
+        self
+    }
+
+    /// Constructor function for the Binance REST API of a market with its maximum snapshot depth
+    pub fn binance(market: Market) -> Self {
+        Self::new(market.rest_url(), market.max_snapshot_limit()).with_market(market)
+    }
+
+    /// Constructor function for the Binance spot REST API with the maximum snapshot depth
+    pub fn binance_spot() -> Self {
+        Self::binance(Market::Spot)
+    }
+}
+
+impl SnapshotSource for RestSnapshotSource {
+    async fn fetch_depth_snapshot(
+        &self,
+        symbol: &str,
+    ) -> Result<DepthUpdateReader, OrderBookError> {
+        let url = format!(
+            "{}{}/depth?symbol={}&limit={}",
+            self.base_url,
+            self.market.api_prefix(),
+            symbol.to_uppercase(),
+            self.limit
+        );
+
+        // Fetch the snapshot and fail on non-success HTTP status codes
+        let response = self.client.get(url).send().await?.error_for_status()?;
+        Ok(response.json::<DepthUpdateReader>().await?)
+    }
+}
+
+/// Struct representing the lack of a REST snapshot source, for venues that send their snapshots on the stream
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoSnapshotSource;
+
+impl SnapshotSource for NoSnapshotSource {
+    async fn fetch_depth_snapshot(
+        &self,
+        symbol: &str,
+    ) -> Result<DepthUpdateReader, OrderBookError> {
+        Err(OrderBookError::SnapshotUnavailable(format!(
+            "the snapshot of {} arrives on the stream",
+            symbol
+        )))
+    }
+}
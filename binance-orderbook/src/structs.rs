@@ -1,104 +1,777 @@
 use super::*;
 
-/// Struct representing the order book with bids, asks, symbol, and the last update ID
+// Number of liquidations kept per futures book
+const LIQUIDATION_HISTORY: usize = 100;
+
+// Number of consecutive outdated snapshots refetched right away before backing off
+const OUTDATED_SNAPSHOT_RETRIES: u32 = 2;
+
+/// Struct representing the order book with bids, asks, symbol, and per-stream update sequences
 #[derive(Debug, Clone)]
 pub struct OrderBook {
     // Trading pair symbol (e.g., BTCUSDT)
     symbol: String,
 
-    // Last update ID for tracking sequential updates
-    last_update_id: u64,
+    // Update sequence state for each stream, the update IDs of different streams are not comparable
+    sequences: BTreeMap<StreamKind, SequenceTracker>,
+
+    /// Map to store bids (price -> quantity), fixed-point keys compare exactly
+    pub bids: BTreeMap<Price, Qty>,
+
+    /// Map to store asks (price -> quantity)
+    pub asks: BTreeMap<Price, Qty>,
+
+    // Synchronization state of the diff depth book
+    sync_state: SyncState,
+
+    // Diff events buffered while waiting for a depth snapshot
+    depth_buffer: Vec<DepthDiff>,
+
+    // Whether at least one diff event has been applied on top of the last snapshot
+    depth_bridged: bool,
+
+    // Whether a depth snapshot has been requested and its outcome not yet recorded
+    snapshot_pending: bool,
+
+    // Number of consecutive failed snapshot requests
+    snapshot_failures: u32,
+
+    // Time (in milliseconds) before which no snapshot is requested after a failure
+    snapshot_retry_at: u64,
+
+    // How partial depth messages are applied to the bids and asks
+    depth_apply_mode: DepthApplyMode,
+
+    // Price window (lowest bid, highest ask) covered by the last replaced partial depth message
+    authoritative_bounds: Option<(Price, Price)>,
 
-    // Map to store bids (price -> quantity), OrderedFloat ensures correct float comparison
-    pub bids: BTreeMap<OrderedFloat<f64>, f64>,
+    // Best bid and ask of the last book ticker, overlaid on the depth levels until depth data moves past them
+    top_of_book: Option<(PriceLevel, PriceLevel)>,
 
-    // Map to store asks (price -> quantity)
-    pub asks: BTreeMap<OrderedFloat<f64>, f64>,
+    // Trading rules of the symbol, if the book was created from a symbol registry
+    symbol_info: Option<SymbolInfo>,
+
+    // Number of incoming prices that were not multiples of the tick size
+    off_tick_prices: u64,
+
+    // Number of incoming quantities that were not multiples of the step size
+    off_step_qtys: u64,
+
+    // Whether the stream was interrupted and the book has not been resynchronized since
+    stale: bool,
+
+    // Settings of the metrics refreshed after every applied message
+    analytics_config: AnalyticsConfig,
+
+    // Microstructure metrics as of the last applied message
+    metrics: Option<BookMetrics>,
+
+    // Latest exchange time (or recorded receive time of a replayed message) seen, in milliseconds
+    clock: u64,
+
+    // Recent trades of the symbol and their rolling statistics
+    trades: TradeStore,
+
+    // Closed candles and the candle in progress
+    candles: CandleStore,
+
+    // Mark price and funding of a futures contract
+    mark_price: Option<MarkPrice>,
+
+    // Recent liquidations of a futures contract, oldest first
+    liquidations: VecDeque<Liquidation>,
 }
 
 impl OrderBook {
-    // Constructor function to create a new OrderBook
+    /// Constructor function to create a new OrderBook
     pub fn new(symbol: String) -> Self {
         Self {
-            symbol,                // Initialize the symbol for the order book
-            last_update_id: 0,     // Set the initial update ID to 0
-            bids: BTreeMap::new(), // Initialize empty bids map
-            asks: BTreeMap::new(), // Initialize empty asks map
+            symbol,                                  // Initialize the symbol for the order book
+            sequences: BTreeMap::new(),              // No stream has been seen yet
+            bids: BTreeMap::new(),                   // Initialize empty bids map
+            asks: BTreeMap::new(),                   // Initialize empty asks map
+            sync_state: SyncState::AwaitingSnapshot, // Diff events wait for a snapshot first
+            depth_buffer: Vec::new(),
+            depth_bridged: false,
+            snapshot_pending: false,
+            snapshot_failures: 0,
+            snapshot_retry_at: 0,
+            depth_apply_mode: DepthApplyMode::Replace, // Partial depth messages are complete pictures
+            authoritative_bounds: None,
+            top_of_book: None,
+            symbol_info: None,
+            off_tick_prices: 0,
+            off_step_qtys: 0,
+            stale: false,
+            analytics_config: AnalyticsConfig::default(),
+            metrics: None,
+            clock: 0,
+            trades: TradeStore::default(),
+            candles: CandleStore::default(),
+            mark_price: None,
+            liquidations: VecDeque::new(),
+        }
+    }
+
+    /// Constructor function to create a new OrderBook for a symbol listed in the registry
+    /// Unknown symbols are rejected up front
+    pub fn from_registry(registry: &SymbolRegistry, symbol: &str) -> Result<Self, OrderBookError> {
+        let info = registry.require(symbol)?.clone();
+        let mut orderbook = Self::new(info.symbol.clone());
+        orderbook.symbol_info = Some(info);
+        Ok(orderbook)
+    }
+
+    /// Function to get the trading rules of the symbol, if known
+    pub fn symbol_info(&self) -> Option<&SymbolInfo> {
+        self.symbol_info.as_ref()
+    }
+
+    /// Function to get the number of off-tick prices seen in incoming updates
+    pub fn off_tick_prices(&self) -> u64 {
+        self.off_tick_prices
+    }
+
+    /// Function to get the number of off-step quantities seen in incoming updates
+    pub fn off_step_qtys(&self) -> u64 {
+        self.off_step_qtys
+    }
+
+    /// Function to count incoming prices that are not multiples of the symbol's tick size
+    /// The error only flags them: the update is still applied, as dropping it would break the stream's sequence
+    pub fn check_tick_size(&mut self, prices: &[Price]) -> Result<(), OrderBookError> {
+        let Some(info) = &self.symbol_info else {
+            return Ok(());
+        };
+
+        let off_tick: Vec<_> = prices
+            .iter()
+            .filter(|price| !info.is_on_tick(**price))
+            .collect();
+        if off_tick.is_empty() {
+            return Ok(());
         }
+
+        let msg = format!("{:?} with tick size {}", off_tick, info.tick_size);
+        self.off_tick_prices += off_tick.len() as u64;
+        Err(OrderBookError::OffTickPrice(msg))
     }
 
-    // Function to update the book ticker (best bid and ask)
+    /// Function to count incoming quantities that are not multiples of the symbol's step size (LOT_SIZE)
+    /// Like off-tick prices, the update is still applied
+    pub fn check_step_size(&mut self, qtys: &[Qty]) -> Result<(), OrderBookError> {
+        let Some(info) = &self.symbol_info else {
+            return Ok(());
+        };
+
+        let off_step: Vec<_> = qtys.iter().filter(|qty| !info.is_on_step(**qty)).collect();
+        if off_step.is_empty() {
+            return Ok(());
+        }
+
+        let msg = format!("{:?} with step size {}", off_step, info.step_size);
+        self.off_step_qtys += off_step.len() as u64;
+        Err(OrderBookError::OffStepQty(msg))
+    }
+
+    /// Function to get the trading pair symbol of the order book
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    /// Function to update the book ticker (best bid and ask)
+    /// The ticker is an authoritative top-of-book overlay kept apart from the depth levels: it replaces
+    /// the depth quantity at its prices and hides depth levels better than it, until newer depth data
+    /// moves past it or replaces the levels.
     pub fn update_book_ticker(&mut self, data: &BookTickerUpdate) {
-        // Update the last_update_id with the new data's update ID
-        self.last_update_id = data.last_update_id;
+        // Advance the book ticker sequence with the new data's update ID
+        self.tracker_mut(StreamKind::BookTicker)
+            .advance(data.last_update_id);
+        self.top_of_book = Some((
+            PriceLevel {
+                price: data.bid_price,
+                qty: data.bid_qty,
+            },
+            PriceLevel {
+                price: data.ask_price,
+                qty: data.ask_qty,
+            },
+        ));
+    }
 
-        // Update bids: if the bid quantity is positive, add/update the bid; otherwise, remove it
-        if data.bid_qty > 0.0 {
-            self.bids.insert(OrderedFloat(data.bid_price), data.bid_qty);
-        } else {
-            self.bids.remove(&OrderedFloat(data.bid_price));
+    /// Function to record a trade of the `trade` or `aggTrade` stream (the book itself is unchanged)
+    pub fn record_trade(&mut self, stream: StreamKind, trade: Trade) {
+        // Advance the trade stream sequence with the trade ID
+        self.tracker_mut(stream).advance(trade.id);
+        self.candles.record_trade(&trade);
+        self.clock = self.clock.max(trade.time);
+        self.trades.record(trade);
+    }
+
+    /// Function to record a candle of the kline stream
+    pub fn record_kline(&mut self, candle: Candle) -> Result<(), OrderBookError> {
+        self.candles.record_kline(candle)
+    }
+
+    /// Function to update the mark price and funding of a futures contract
+    pub fn update_mark_price(&mut self, mark_price: MarkPrice) {
+        // Advance the mark price sequence with the event time
+        self.tracker_mut(StreamKind::MarkPrice)
+            .advance(mark_price.time);
+        self.mark_price = Some(mark_price);
+    }
+
+    /// Function to get the mark price and funding of a futures contract, if received
+    pub fn mark_price(&self) -> Option<&MarkPrice> {
+        self.mark_price.as_ref()
+    }
+
+    /// Function to record a liquidation order of a futures contract
+    pub fn record_liquidation(&mut self, liquidation: Liquidation) {
+        self.liquidations.push_back(liquidation);
+        if self.liquidations.len() > LIQUIDATION_HISTORY {
+            self.liquidations.pop_front();
         }
+    }
 
-        // Update asks: if the ask quantity is positive, add/update the ask; otherwise, remove it
-        if data.ask_qty > 0.0 {
-            self.asks.insert(OrderedFloat(data.ask_price), data.ask_qty);
-        } else {
-            self.asks.remove(&OrderedFloat(data.ask_price));
+    /// Function to iterate over the recent liquidations, most recent first
+    pub fn liquidations(&self) -> impl Iterator<Item = &Liquidation> {
+        self.liquidations.iter().rev()
+    }
+
+    /// Function to sample the mid price at `time` (in milliseconds) into candles built from mid prices
+    pub fn sample_mid(&mut self, time: u64) {
+        if let Some(mid) = self.metrics.as_ref().map(|metrics| metrics.mid) {
+            self.candles.sample_mid(time, mid);
         }
     }
 
-    // Function to update the depth of the order book (multiple bid/ask updates)
+    /// Function to move the clock of the book forward to an exchange event time or a recorded receive time
+    /// (in milliseconds), ageing the rolling trade windows; the local clock never moves it
+    pub fn advance_clock(&mut self, time: u64) {
+        self.clock = self.clock.max(time);
+        self.trades.evict(self.clock);
+    }
+
+    /// Function to get the latest exchange time (in milliseconds) of the applied messages, where the trade windows end
+    /// Replayed messages without an event time count with their recorded receive time, so replays keep their clock.
+    pub fn clock(&self) -> u64 {
+        self.clock
+    }
+
+    /// Function to update the depth of the order book (multiple bid/ask updates)
     pub fn update_depth(&mut self, data: &DepthUpdate) {
-        // Update the last_update_id with the new data's update ID
-        self.last_update_id = data.last_update_id;
+        // Advance the partial depth sequence with the new data's update ID
+        self.tracker_mut(StreamKind::PartialDepth)
+            .advance(data.last_update_id);
 
-        // Update bids: process all price levels from the update
-        for (price, qty) in &data.bids {
-            if *qty > 0.0 {
-                self.bids.insert(OrderedFloat(*price), *qty);
+        match self.depth_apply_mode {
+            // Rebuild bids and asks from the message, levels outside of it are stale
+            DepthApplyMode::Replace => {
+                self.stale = false;
+                self.replace_levels(&data.bids, &data.asks);
+                self.authoritative_bounds = Self::depth_bounds(&data.bids, &data.asks);
+            }
+            // Update bids and asks: process all price levels from the update
+            DepthApplyMode::Merge => {
+                self.update_depth_levels(&data.bids, &data.asks);
+                self.authoritative_bounds = None;
+            }
+        }
+    }
+
+    /// Function to set how partial depth messages are applied to the order book
+    pub fn set_depth_apply_mode(&mut self, mode: DepthApplyMode) {
+        self.depth_apply_mode = mode;
+    }
+
+    // Function to compute the price window (lowest bid, highest ask) covered by depth levels
+    fn depth_bounds(bids: &[(Price, Qty)], asks: &[(Price, Qty)]) -> Option<(Price, Price)> {
+        let lowest_bid = bids.iter().map(|(price, _)| *price).min();
+        let highest_ask = asks.iter().map(|(price, _)| *price).max();
+
+        match (lowest_bid, highest_ask) {
+            (Some(low), Some(high)) => Some((low, high)),
+            (Some(low), None) => Some((low, low)),
+            (None, Some(high)) => Some((high, high)),
+            (None, None) => None,
+        }
+    }
+
+    /// Function to check whether the book state at a price is confirmed by the last depth data
+    /// A synced diff book is authoritative everywhere, a partial book only within its top-N window
+    pub fn is_level_authoritative(&self, price: Price) -> bool {
+        if self.sync_state == SyncState::Synced {
+            return true;
+        }
+
+        self.authoritative_bounds
+            .is_some_and(|(low, high)| price >= low && price <= high)
+    }
+
+    // Function to replace all bids and asks with the levels of a depth snapshot
+    fn replace_levels(&mut self, bids: &[(Price, Qty)], asks: &[(Price, Qty)]) {
+        // The new levels are newer than the last book ticker
+        self.top_of_book = None;
+        self.bids = bids
+            .iter()
+            .filter(|(_, qty)| *qty > Qty::ZERO)
+            .copied()
+            .collect();
+        self.asks = asks
+            .iter()
+            .filter(|(_, qty)| *qty > Qty::ZERO)
+            .copied()
+            .collect();
+    }
+
+    // Function to apply the price levels of a diff event without any sequence checks
+    fn apply_diff_levels(&mut self, diff: &DepthDiff) {
+        self.tracker_mut(StreamKind::DiffDepth)
+            .advance(diff.final_update_id);
+        self.depth_bridged = true;
+        self.update_depth_levels(&diff.bids, &diff.asks);
+    }
+
+    // Function to upsert or remove (quantity 0) the given bid and ask levels
+    fn update_depth_levels(&mut self, bids: &[(Price, Qty)], asks: &[(Price, Qty)]) {
+        for (price, qty) in bids {
+            if *qty > Qty::ZERO {
+                self.bids.insert(*price, *qty);
             } else {
-                self.bids.remove(&OrderedFloat(*price));
+                self.bids.remove(price);
             }
         }
 
-        // Update asks: process all price levels from the update
-        for (price, qty) in &data.asks {
-            if *qty > 0.0 {
-                self.asks.insert(OrderedFloat(*price), *qty);
+        for (price, qty) in asks {
+            if *qty > Qty::ZERO {
+                self.asks.insert(*price, *qty);
             } else {
-                self.asks.remove(&OrderedFloat(*price));
+                self.asks.remove(price);
+            }
+        }
+
+        // Depth levels better than the book ticker are newer than it, so the overlay is dropped
+        if let Some((bid, ask)) = &self.top_of_book {
+            let bid_passed = self.bids.keys().next_back().is_some_and(|&p| p > bid.price);
+            let ask_passed = self.asks.keys().next().is_some_and(|&p| p < ask.price);
+            if bid_passed || ask_passed {
+                self.top_of_book = None;
+            }
+        }
+    }
+
+    // Function to classify a diff event against the local book's update sequence
+    fn classify_diff(&self, diff: &DepthDiff) -> SequenceOutcome {
+        let last_update_id = self.last_update_id(StreamKind::DiffDepth);
+        let next_update_id = last_update_id + 1;
+
+        // Events already covered by the local book are duplicates or outdated
+        match diff.final_update_id.cmp(&last_update_id) {
+            Ordering::Less => return SequenceOutcome::Outdated,
+            Ordering::Equal => return SequenceOutcome::Duplicate,
+            Ordering::Greater => {}
+        }
+
+        let continuous = match diff.prev_final_update_id {
+            // Futures streams carry `pu`, which must match the previous event's `u`
+            Some(prev) if self.depth_bridged => prev == last_update_id,
+            // Futures update IDs are not contiguous: the first event after a snapshot must not start
+            // after it, i.e., its previous event must not be newer than the snapshot
+            Some(prev) => prev <= last_update_id,
+            // Otherwise the event's [U; u] range must cover the next expected update ID
+            None => diff.first_update_id <= next_update_id,
+        };
+
+        if continuous {
+            SequenceOutcome::InOrder
+        } else {
+            SequenceOutcome::Gap
+        }
+    }
+
+    // Function to drop the local depth book and start buffering for a new snapshot
+    fn reset_depth_sync(&mut self) {
+        self.sync_state = SyncState::AwaitingSnapshot;
+        self.depth_buffer.clear();
+        self.tracker_mut(StreamKind::DiffDepth).reset();
+        self.depth_bridged = false;
+        self.top_of_book = None;
+        self.bids.clear();
+        self.asks.clear();
+    }
+
+    /// Function to apply a diff depth event, buffering it while the book awaits a snapshot
+    pub fn apply_depth_diff(&mut self, diff: DepthDiff) -> Result<SyncState, OrderBookError> {
+        if self.sync_state == SyncState::AwaitingSnapshot {
+            self.depth_buffer.push(diff);
+            return Ok(SyncState::AwaitingSnapshot);
+        }
+
+        let outcome = self.classify_diff(&diff);
+        self.tracker_mut(StreamKind::DiffDepth).record(outcome);
+
+        match outcome {
+            SequenceOutcome::InOrder => self.apply_diff_levels(&diff),
+            // Events already covered by the local book are ignored
+            SequenceOutcome::Duplicate | SequenceOutcome::Outdated => {}
+            // A gap invalidates the local book; buffer this event and wait for a new snapshot
+            SequenceOutcome::Gap => {
+                let msg = format!(
+                    "expected update after {}, found [{}; {}]",
+                    self.last_update_id(StreamKind::DiffDepth),
+                    diff.first_update_id,
+                    diff.final_update_id
+                );
+                self.reset_depth_sync();
+                self.depth_buffer.push(diff);
+                return Err(OrderBookError::SequenceGap(msg));
+            }
+        }
+
+        Ok(SyncState::Synced)
+    }
+
+    /// Function to bootstrap the local book from a REST snapshot and replay buffered diff events
+    pub fn apply_depth_snapshot(
+        &mut self,
+        snapshot: &DepthUpdate,
+    ) -> Result<SyncState, OrderBookError> {
+        // Discard buffered events that are already contained in the snapshot
+        let snapshot_id = snapshot.last_update_id;
+        self.depth_buffer
+            .retain(|diff| diff.final_update_id > snapshot_id);
+
+        // The first remaining event must bridge the snapshot, otherwise a newer snapshot is needed
+        if let Some(first) = self.depth_buffer.first() {
+            if first.first_update_id > snapshot_id + 1 {
+                return Err(OrderBookError::SnapshotOutdated(format!(
+                    "snapshot lastUpdateId: {}, first buffered U: {}",
+                    snapshot_id, first.first_update_id
+                )));
             }
         }
+
+        self.replace_levels(&snapshot.bids, &snapshot.asks);
+        self.tracker_mut(StreamKind::DiffDepth).advance(snapshot_id);
+        self.depth_bridged = false;
+        self.sync_state = SyncState::Synced;
+        self.stale = false;
+
+        // Replay the buffered events on top of the snapshot
+        let mut buffered = std::mem::take(&mut self.depth_buffer).into_iter();
+        while let Some(diff) = buffered.next() {
+            if let Err(e) = self.apply_depth_diff(diff) {
+                // A gap starts buffering again; the events after it are needed for the next snapshot
+                self.depth_buffer.extend(buffered);
+                return Err(e);
+            }
+        }
+
+        Ok(self.sync_state)
+    }
+
+    /// Function to claim the snapshot request of a diff book with buffered events
+    /// Only one request per book is in flight, and failed requests are retried with an exponential
+    /// backoff, so a burst of buffered events leads to a single REST request.
+    pub fn request_snapshot(&mut self, now: u64) -> bool {
+        if self.sync_state == SyncState::Synced
+            || self.depth_buffer.is_empty()
+            || self.snapshot_pending
+            || now < self.snapshot_retry_at
+        {
+            return false;
+        }
+        self.snapshot_pending = true;
+        true
+    }
+
+    /// Function to record the outcome of a claimed snapshot request at `now` (in milliseconds)
+    pub fn complete_snapshot_request(
+        &mut self,
+        result: &Result<SyncState, OrderBookError>,
+        now: u64,
+    ) {
+        self.snapshot_pending = false;
+        match result {
+            Ok(_) => {
+                self.snapshot_failures = 0;
+                self.snapshot_retry_at = 0;
+            }
+            // A snapshot that cannot bridge the buffered events is refetched right away a few times
+            Err(OrderBookError::SnapshotOutdated(_))
+                if self.snapshot_failures < OUTDATED_SNAPSHOT_RETRIES =>
+            {
+                self.snapshot_failures += 1;
+            }
+            Err(_) => {
+                self.snapshot_failures += 1;
+                let delay = ReconnectPolicy::default().backoff(self.snapshot_failures);
+                self.snapshot_retry_at = now + delay.as_millis() as u64;
+            }
+        }
+    }
+
+    /// Function to keep only the best `depth` levels per side, for venues whose streams leave levels
+    /// pushed out of the subscribed depth to the client
+    pub fn truncate_depth(&mut self, depth: usize) {
+        while self.bids.len() > depth {
+            self.bids.pop_first();
+        }
+        while self.asks.len() > depth {
+            self.asks.pop_last();
+        }
     }
 
-    // Function to get the best bid (highest) and best ask (lowest) from the order book
-    pub fn get_best_bid_ask(&self) -> Option<((f64, f64), (f64, f64))> {
-        // Get the highest bid (last entry in the map)
-        let best_bid = self.bids.iter().next_back();
-        // Get the lowest ask (first entry in the map)
-        let best_ask = self.asks.iter().next();
+    /// Function to mark the book stale after the stream was interrupted
+    /// The levels are kept for display, but a diff book waits for a new snapshot before applying events
+    pub fn mark_stale(&mut self) {
+        self.stale = true;
+        self.top_of_book = None;
+        self.sync_state = SyncState::AwaitingSnapshot;
+        self.depth_buffer.clear();
+        self.tracker_mut(StreamKind::DiffDepth).reset();
+        self.depth_bridged = false;
+        self.candles.mark_interrupted();
+    }
+
+    /// Function to change the settings of the metrics refreshed after every applied message
+    pub fn set_analytics_config(&mut self, config: AnalyticsConfig) {
+        self.analytics_config = config;
+        self.refresh_metrics();
+    }
+
+    /// Function to recompute the microstructure metrics from the current book state
+    pub fn refresh_metrics(&mut self) {
+        self.metrics = BookMetrics::compute(self, &self.analytics_config);
+    }
+
+    /// Function to get the microstructure metrics as of the last applied message
+    pub fn metrics(&self) -> Option<&BookMetrics> {
+        self.metrics.as_ref()
+    }
+
+    /// Function to get the recent trades of the symbol and their rolling statistics
+    pub fn trades(&self) -> &TradeStore {
+        &self.trades
+    }
+
+    /// Function to get the mutable trade store of the symbol (e.g., to change its windows)
+    pub fn trades_mut(&mut self) -> &mut TradeStore {
+        &mut self.trades
+    }
+
+    /// Function to get the closed candles and the candle in progress of the symbol
+    pub fn candles(&self) -> &CandleStore {
+        &self.candles
+    }
+
+    /// Function to get the mutable candle store of the symbol (e.g., to change its interval)
+    pub fn candles_mut(&mut self) -> &mut CandleStore {
+        &mut self.candles
+    }
+
+    /// Function to check whether the book is stale (interrupted and not yet resynchronized)
+    pub fn is_stale(&self) -> bool {
+        self.stale
+    }
+
+    /// Function to get the synchronization state of the diff depth book
+    pub fn sync_state(&self) -> SyncState {
+        self.sync_state
+    }
+
+    /// Function to iterate over the bids (lowest to highest) with the book ticker overlay on top
+    pub fn visible_bids(&self) -> impl DoubleEndedIterator<Item = (&Price, &Qty)> + '_ {
+        let (depth, top) = match &self.top_of_book {
+            Some((bid, _)) => (self.bids.range(..bid.price), Some(bid)),
+            None => (self.bids.range(..), None),
+        };
+        let top = top
+            .filter(|bid| bid.qty > Qty::ZERO)
+            .map(|bid| (&bid.price, &bid.qty));
+        depth.chain(top)
+    }
+
+    /// Function to iterate over the asks (lowest to highest) with the book ticker overlay on top
+    pub fn visible_asks(&self) -> impl DoubleEndedIterator<Item = (&Price, &Qty)> + '_ {
+        let (top, depth) = match &self.top_of_book {
+            Some((_, ask)) => (
+                Some(ask),
+                self.asks
+                    .range((Bound::Excluded(ask.price), Bound::Unbounded)),
+            ),
+            None => (None, self.asks.range(..)),
+        };
+        let top = top
+            .filter(|ask| ask.qty > Qty::ZERO)
+            .map(|ask| (&ask.price, &ask.qty));
+        top.into_iter().chain(depth)
+    }
+
+    /// Function to get the best bid (highest) and best ask (lowest) from the order book
+    pub fn get_best_bid_ask(&self) -> Option<((Price, Qty), (Price, Qty))> {
+        // Get the highest visible bid (last entry in the range)
+        let best_bid = self.visible_bids().next_back();
+        // Get the lowest visible ask (first entry in the range)
+        let best_ask = self.visible_asks().next();
 
         // If both best bid and best ask exist, return them; otherwise, return None
         if let (Some(bid), Some(ask)) = (best_bid, best_ask) {
-            Some(((bid.0.into_inner(), *bid.1), (ask.0.into_inner(), *ask.1)))
+            Some(((*bid.0, *bid.1), (*ask.0, *ask.1)))
         } else {
             None
         }
     }
 
-    // Function to get the volume at a specific price level in the order book
-    pub fn get_volume_at_price(&self, price: f64) -> f64 {
-        // Check if the price exists in bids or asks, and return the quantity
-        if let Some(&qty) = self.bids.get(&OrderedFloat(price)) {
-            qty
-        } else if let Some(&qty) = self.asks.get(&OrderedFloat(price)) {
-            qty
-        } else {
-            0.0 // If the price is not found, return 0
+    /// Function to get the best `n` visible levels per side with running totals
+    /// Bids are ordered from the highest price down, asks from the lowest price up
+    pub fn top_levels(&self, n: usize) -> DepthLadder {
+        DepthLadder {
+            bids: LadderLevel::accumulate(self.visible_bids().rev().take(n)),
+            asks: LadderLevel::accumulate(self.visible_asks().take(n)),
         }
     }
 
-    // Function to check if the symbol matches the current order book's symbol
+    /// Function to get a serializable view of the book: its top `depth` levels per side, sync state, metrics,
+    /// trade statistics, the candle in progress and the mark price
+    pub fn view(&self, depth: usize) -> BookView {
+        let ladder = self.top_levels(depth);
+        BookView {
+            symbol: self.symbol.clone(),
+            sync_state: self.sync_state(),
+            stale: self.is_stale(),
+            bids: ladder.bids,
+            asks: ladder.asks,
+            metrics: self.metrics().cloned(),
+            trades: self.trades.window_stats(self.clock),
+            candle: self.candles.current().copied(),
+            mark_price: self.mark_price,
+        }
+    }
+
+    /// Function to estimate the execution of a market order against the visible levels
+    /// Buys walk the asks upwards and sells walk the bids downwards until the size is filled
+    pub fn simulate_market_order(&self, side: Side, size: OrderSize) -> ExecutionEstimate {
+        let levels: Box<dyn Iterator<Item = (&Price, &Qty)>> = match side {
+            Side::Buy => Box::new(self.visible_asks()),
+            Side::Sell => Box::new(self.visible_bids().rev()),
+        };
+
+        // Remaining size in the units of the order (base quantity or quote notional)
+        let mut remaining = match size {
+            OrderSize::Base(qty) => qty,
+            OrderSize::Quote(notional) => notional,
+        };
+        let mut filled_qty = Qty::ZERO;
+        let mut notional = Decimal::ZERO;
+        let mut worst_price = None;
+        let mut levels_consumed = 0;
+
+        for (&price, &qty) in levels {
+            if !remaining.is_positive() {
+                break;
+            }
+
+            // Take the whole level, or the part of it that completes the order
+            let take = match size {
+                OrderSize::Base(_) => qty.min(remaining),
+                OrderSize::Quote(_) => qty.min(remaining / price),
+            };
+            let level_notional = price * take;
+            let full_level = take == qty;
+
+            filled_qty += take;
+            notional += level_notional;
+            worst_price = Some(price);
+            levels_consumed += 1;
+            remaining = match (size, full_level) {
+                (OrderSize::Base(_), _) => remaining - take,
+                (OrderSize::Quote(_), true) => remaining - level_notional,
+                // Less than one quantity increment of quote notional may be left over
+                (OrderSize::Quote(_), false) => Decimal::ZERO,
+            };
+        }
+
+        // Average fill price and its slippage against the mid price (positive is a cost)
+        let average_price = filled_qty.is_positive().then(|| notional / filled_qty);
+        let slippage_bps = average_price.zip(self.mid_price()).map(|(average, mid)| {
+            let slippage = (average.to_f64() - mid.to_f64()) / mid.to_f64() * 10_000.0;
+            match side {
+                Side::Buy => slippage,
+                Side::Sell => -slippage,
+            }
+        });
+
+        ExecutionEstimate {
+            side,
+            filled_qty,
+            notional,
+            average_price,
+            worst_price,
+            slippage_bps,
+            levels_consumed,
+            unfilled: match size {
+                OrderSize::Base(_) => OrderSize::Base(remaining),
+                OrderSize::Quote(_) => OrderSize::Quote(remaining),
+            },
+        }
+    }
+
+    /// Function to get the mid price between the best bid and the best ask
+    pub fn mid_price(&self) -> Option<Price> {
+        self.get_best_bid_ask()
+            .map(|((bid, _), (ask, _))| (bid + ask) / (Decimal::ONE + Decimal::ONE))
+    }
+
+    /// Function to get the volume at a specific price level in the order book
+    pub fn get_volume_at_price(&self, price: Price) -> Qty {
+        // The book ticker overlay replaces the depth quantity at its prices
+        if let Some((bid, ask)) = &self.top_of_book {
+            if price == bid.price {
+                return bid.qty;
+            }
+            if price == ask.price {
+                return ask.qty;
+            }
+        }
+
+        // Levels better than the book ticker overlay are hidden
+        let bid_visible = self
+            .top_of_book
+            .as_ref()
+            .is_none_or(|(bid, _)| price < bid.price);
+        let ask_visible = self
+            .top_of_book
+            .as_ref()
+            .is_none_or(|(_, ask)| price > ask.price);
+
+        // Check if the price exists in the visible bids or asks, and return the quantity
+        match (self.bids.get(&price), self.asks.get(&price)) {
+            (Some(&qty), _) if bid_visible => qty,
+            (_, Some(&qty)) if ask_visible => qty,
+            _ => Qty::ZERO, // If the price is not found or hidden, return 0
+        }
+    }
+
+    /// Function to check whether the best bid is at or above the best ask
+    pub fn is_crossed(&self) -> bool {
+        self.get_best_bid_ask()
+            .is_some_and(|((bid, _), (ask, _))| bid >= ask)
+    }
+
+    /// Function to report a crossed book as an error state
+    pub fn check_crossed(&self) -> Result<(), OrderBookError> {
+        match self.get_best_bid_ask() {
+            Some(((bid, _), (ask, _))) if bid >= ask => Err(OrderBookError::CrossedBook(format!(
+                "best bid {} is not below best ask {}",
+                bid, ask
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Function to check if the symbol matches the current order book's symbol
     pub fn is_symbol_same(&self, symbol: &str) -> Result<(), OrderBookError> {
         // If the symbols don't match, return a DifferentSymbol error
         if !self.symbol.eq(&symbol) {
@@ -111,17 +784,229 @@ impl OrderBook {
         Ok(())
     }
 
-    // Function to ensure that updates are sequential by comparing the last_update_id
-    pub fn is_update_sequential(&self, last_update_id: u64) -> Result<(), OrderBookError> {
-        // If the update ID is outdated, return an UpdateIdOutdated error
-        if self.last_update_id >= last_update_id {
-            return Err(OrderBookError::UpdateIdOutdated(format!(
-                "Skipping outdated update: {}",
-                last_update_id
-            )));
+    // Function to get the sequence tracker of a stream, creating it on first use
+    fn tracker_mut(&mut self, stream: StreamKind) -> &mut SequenceTracker {
+        self.sequences.entry(stream).or_default()
+    }
+
+    /// Function to get the last applied update ID of a stream (0 if nothing was applied yet)
+    pub fn last_update_id(&self, stream: StreamKind) -> u64 {
+        self.sequences
+            .get(&stream)
+            .map_or(0, |tracker| tracker.last_update_id)
+    }
+
+    /// Function to get the sequence outcome counters of a stream
+    pub fn sequence_stats(&self, stream: StreamKind) -> SequenceStats {
+        self.sequences
+            .get(&stream)
+            .map_or_else(SequenceStats::default, |tracker| tracker.stats)
+    }
+
+    /// Function to ensure that an update is newer than the last applied update of its stream
+    /// The outcome is counted; duplicates and outdated updates are reported as distinct errors
+    pub fn check_update_sequence(
+        &mut self,
+        stream: StreamKind,
+        last_update_id: u64,
+    ) -> Result<(), OrderBookError> {
+        let tracker = self.tracker_mut(stream);
+        let outcome = tracker.classify(last_update_id);
+        tracker.record(outcome);
+
+        match outcome {
+            SequenceOutcome::Duplicate => Err(OrderBookError::DuplicateUpdate(format!(
+                "Skipping duplicate {:?} update: {}",
+                stream, last_update_id
+            ))),
+            SequenceOutcome::Outdated => Err(OrderBookError::UpdateIdOutdated(format!(
+                "Skipping outdated {:?} update: {}",
+                stream, last_update_id
+            ))),
+            SequenceOutcome::InOrder | SequenceOutcome::Gap => Ok(()),
         }
+    }
+}
 
-        Ok(())
+/// Struct representing the estimated execution of a market order against the current book
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExecutionEstimate {
+    /// Side of the simulated order
+    pub side: Side,
+
+    /// Base quantity that would be filled
+    pub filled_qty: Qty,
+
+    /// Quote notional of the filled quantity
+    pub notional: Decimal,
+
+    /// Volume weighted average fill price, if anything is filled
+    pub average_price: Option<Price>,
+
+    /// Price of the last (worst) level touched, if anything is filled
+    pub worst_price: Option<Price>,
+
+    /// Slippage of the average price versus the mid price in basis points (positive is a cost)
+    pub slippage_bps: Option<f64>,
+
+    /// Number of levels touched, including a partially filled last level
+    pub levels_consumed: usize,
+
+    /// Part of the order the visible book cannot fill, in the units of the requested size
+    pub unfilled: OrderSize,
+}
+
+impl ExecutionEstimate {
+    /// Function to check whether the whole order can be filled by the visible book
+    pub fn is_fully_filled(&self) -> bool {
+        match self.unfilled {
+            OrderSize::Base(qty) => qty.is_zero(),
+            OrderSize::Quote(notional) => notional.is_zero(),
+        }
+    }
+}
+
+/// Struct representing one level of a depth ladder with the totals up to and including it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LadderLevel {
+    /// Price of the level
+    pub price: Price,
+
+    /// Quantity at the level
+    pub qty: Qty,
+
+    /// Quantity of this and all better levels
+    pub cumulative_qty: Qty,
+
+    /// Notional (price * quantity) of this and all better levels
+    pub cumulative_notional: Decimal,
+}
+
+impl LadderLevel {
+    // Function to build ladder levels with running totals from levels ordered best first
+    fn accumulate<'a>(levels: impl Iterator<Item = (&'a Price, &'a Qty)>) -> Vec<Self> {
+        let mut cumulative_qty = Qty::ZERO;
+        let mut cumulative_notional = Decimal::ZERO;
+        levels
+            .map(|(&price, &qty)| {
+                cumulative_qty += qty;
+                cumulative_notional += price * qty;
+                Self {
+                    price,
+                    qty,
+                    cumulative_qty,
+                    cumulative_notional,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Struct representing the top levels of both sides of the order book
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DepthLadder {
+    /// Bid levels, best (highest) first
+    pub bids: Vec<LadderLevel>,
+
+    /// Ask levels, best (lowest) first
+    pub asks: Vec<LadderLevel>,
+}
+
+/// Struct representing the state of an order book as served to other processes (e.g., as JSON)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BookView {
+    /// Symbol of the book (e.g., BNBUSDT)
+    pub symbol: String,
+
+    /// Synchronization state of the diff depth book
+    pub sync_state: SyncState,
+
+    /// Whether the book may be outdated after a stream interruption
+    pub stale: bool,
+
+    /// Bid levels, best (highest) first
+    pub bids: Vec<LadderLevel>,
+
+    /// Ask levels, best (lowest) first
+    pub asks: Vec<LadderLevel>,
+
+    /// Microstructure metrics as of the last applied message, None while a side is empty
+    pub metrics: Option<BookMetrics>,
+
+    /// Rolling trade statistics of every configured window
+    pub trades: Vec<TradeStats>,
+
+    /// The candle in progress, None before the first sample
+    pub candle: Option<Candle>,
+
+    /// Mark price and funding, futures only
+    pub mark_price: Option<MarkPrice>,
+}
+
+/// Struct representing one price level as served to other processes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PriceLevel {
+    /// Price of the level
+    pub price: Price,
+
+    /// Quantity resting at the price, zero when the level was removed
+    pub qty: Qty,
+}
+
+/// Struct holding the number of updates per sequence outcome for a stream
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SequenceStats {
+    /// Updates that continued the sequence
+    pub in_order: u64,
+
+    /// Updates with the same ID as the last applied one
+    pub duplicates: u64,
+
+    /// Updates older than the last applied one
+    pub outdated: u64,
+
+    /// Updates that revealed missing updates
+    pub gaps: u64,
+}
+
+/// Struct tracking the update ID sequence of a single stream
+#[derive(Debug, Clone, Default)]
+pub struct SequenceTracker {
+    // Last applied update ID (0 if nothing was applied yet)
+    last_update_id: u64,
+
+    // Counters for each sequence outcome
+    stats: SequenceStats,
+}
+
+impl SequenceTracker {
+    // Function to classify an update ID of a stream without contiguous IDs (no gap detection)
+    fn classify(&self, update_id: u64) -> SequenceOutcome {
+        match update_id.cmp(&self.last_update_id) {
+            Ordering::Less => SequenceOutcome::Outdated,
+            Ordering::Equal => SequenceOutcome::Duplicate,
+            Ordering::Greater => SequenceOutcome::InOrder,
+        }
+    }
+
+    // Function to count a sequence outcome
+    fn record(&mut self, outcome: SequenceOutcome) {
+        match outcome {
+            SequenceOutcome::InOrder => self.stats.in_order += 1,
+            SequenceOutcome::Duplicate => self.stats.duplicates += 1,
+            SequenceOutcome::Outdated => self.stats.outdated += 1,
+            SequenceOutcome::Gap => self.stats.gaps += 1,
+        }
+    }
+
+    // Function to move the sequence forward to the given update ID
+    fn advance(&mut self, update_id: u64) {
+        self.last_update_id = update_id;
+    }
+
+    // Function to forget the last update ID (e.g., before a resync), counters are kept
+    fn reset(&mut self) {
+        self.last_update_id = 0;
     }
 }
 
@@ -132,26 +1017,26 @@ pub struct BookTickerUpdate {
     last_update_id: u64,
 
     // Best bid price
-    bid_price: f64,
+    bid_price: Price,
 
     // Best bid quantity
-    bid_qty: f64,
+    bid_qty: Qty,
 
     // Best ask price
-    ask_price: f64,
+    ask_price: Price,
 
     // Best ask quantity
-    ask_qty: f64,
+    ask_qty: Qty,
 }
 
 impl BookTickerUpdate {
-    // Constructor function to create a new BookTickerUpdate
+    /// Constructor function to create a new BookTickerUpdate
     pub fn new(
         last_update_id: u64,
-        bid_price: f64,
-        bid_qty: f64,
-        ask_price: f64,
-        ask_qty: f64,
+        bid_price: Price,
+        bid_qty: Qty,
+        ask_price: Price,
+        ask_qty: Qty,
     ) -> Self {
         Self {
             last_update_id,
@@ -162,13 +1047,26 @@ impl BookTickerUpdate {
         }
     }
 
-    // Function to construct a BookTickerUpdate from a reader (deserialized data)
+    /// Function to get the ID of the last order book update
+    pub fn last_update_id(&self) -> u64 {
+        self.last_update_id
+    }
+
+    /// Function to get the bid and ask levels of the update
+    pub fn levels(&self) -> Vec<(Price, Qty)> {
+        vec![
+            (self.bid_price, self.bid_qty),
+            (self.ask_price, self.ask_qty),
+        ]
+    }
+
+    /// Function to construct a BookTickerUpdate from a reader (deserialized data)
     pub fn from_reader(reader: BookTickerUpdateReader) -> Result<Self, OrderBookError> {
-        // Parse the bid and ask prices/quantities from strings to f64
-        let bid_price = parse_f64(&reader.bid_price, "bid_price")?;
-        let bid_qty = parse_f64(&reader.bid_qty, "bid_qty")?;
-        let ask_price = parse_f64(&reader.ask_price, "ask_price")?;
-        let ask_qty = parse_f64(&reader.ask_qty, "ask_qty")?;
+        // Parse the bid and ask prices/quantities from strings to exact decimals
+        let bid_price = parse_decimal(&reader.bid_price, "bid_price")?;
+        let bid_qty = parse_decimal(&reader.bid_qty, "bid_qty")?;
+        let ask_price = parse_decimal(&reader.ask_price, "ask_price")?;
+        let ask_qty = parse_decimal(&reader.ask_qty, "ask_qty")?;
 
         // Return the constructed BookTickerUpdate
         Ok(Self {
@@ -188,15 +1086,15 @@ pub struct DepthUpdate {
     last_update_id: u64,
 
     // List of bid price levels and quantities
-    bids: Vec<(f64, f64)>,
+    bids: Vec<(Price, Qty)>,
 
     // List of ask price levels and quantities
-    asks: Vec<(f64, f64)>,
+    asks: Vec<(Price, Qty)>,
 }
 
 impl DepthUpdate {
-    // Constructor function to create a new DepthUpdate
-    pub fn new(last_update_id: u64, bids: Vec<(f64, f64)>, asks: Vec<(f64, f64)>) -> Self {
+    /// Constructor function to create a new DepthUpdate
+    pub fn new(last_update_id: u64, bids: Vec<(Price, Qty)>, asks: Vec<(Price, Qty)>) -> Self {
         Self {
             last_update_id,
             bids,
@@ -204,74 +1102,442 @@ impl DepthUpdate {
         }
     }
 
-    // Function to construct a DepthUpdate from a reader (deserialized data)
-    pub fn from_reader(reader: DepthUpdateReader) -> Self {
-        Self {
+    /// Function to get the ID of the last order book update
+    pub fn last_update_id(&self) -> u64 {
+        self.last_update_id
+    }
+
+    /// Function to get all levels of the update
+    pub fn levels(&self) -> Vec<(Price, Qty)> {
+        self.bids.iter().chain(&self.asks).copied().collect()
+    }
+
+    /// Function to construct a DepthUpdate from a reader (deserialized data)
+    pub fn from_reader(reader: DepthUpdateReader) -> Result<Self, OrderBookError> {
+        Ok(Self {
             last_update_id: reader.last_update_id,
-            // Parse bids from strings to f64 tuples
-            bids: reader
-                .bids
-                .into_iter()
-                .map(|b| {
-                    (
-                        b[0].parse().unwrap_or_default(),
-                        b[1].parse().unwrap_or_default(),
-                    )
-                })
-                .collect(),
-            // Parse asks from strings to f64 tuples
-            asks: reader
-                .asks
-                .into_iter()
-                .map(|a| {
-                    (
-                        a[0].parse().unwrap_or_default(),
-                        a[1].parse().unwrap_or_default(),
-                    )
-                })
-                .collect(),
-        }
+            // Parse bids and asks from strings to exact decimal tuples
+            bids: parse_levels(reader.bids, "bids")?,
+            asks: parse_levels(reader.asks, "asks")?,
+        })
     }
 }
 
 /// Struct representing a reader for BookTickerUpdate, used for deserialization from JSON
 #[derive(Debug, Deserialize)]
 pub struct BookTickerUpdateReader {
-    // Last update ID of the order book
+    /// Last update ID of the order book
     #[serde(rename = "u")]
     pub last_update_id: u64,
 
-    // Trading pair symbol
+    /// Trading pair symbol
     #[serde(rename = "s")]
     pub symbol: String,
 
-    // Best bid price (as string for deserialization)
+    /// Best bid price (as string for deserialization)
     #[serde(rename = "b")]
     pub bid_price: String,
 
-    // Best bid quantity (as string for deserialization)
+    /// Best bid quantity (as string for deserialization)
     #[serde(rename = "B")]
     pub bid_qty: String,
 
-    // Best ask price (as string for deserialization)
+    /// Best ask price (as string for deserialization)
     #[serde(rename = "a")]
     pub ask_price: String,
 
-    // Best ask quantity (as string for deserialization)
+    /// Best ask quantity (as string for deserialization)
     #[serde(rename = "A")]
     pub ask_qty: String,
 }
 
 /// Struct representing a reader for DepthUpdate, used for deserialization from JSON
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct DepthUpdateReader {
-    // Last update ID of the order book
+    /// Last update ID of the order book
     #[serde(rename = "lastUpdateId")]
     pub last_update_id: u64,
 
-    // Bids as arrays of [price, quantity] in strings
+    /// Bids as arrays of [price, quantity] in strings
     pub bids: Vec<[String; 2]>,
 
-    // Asks as arrays of [price, quantity] in strings
+    /// Asks as arrays of [price, quantity] in strings
+    pub asks: Vec<[String; 2]>,
+}
+
+/// Struct to represent a diff depth event (bid/ask levels changed between two update IDs)
+#[derive(Debug, Clone)]
+pub struct DepthDiff {
+    // First update ID in the event
+    first_update_id: u64,
+
+    // Final update ID in the event
+    final_update_id: u64,
+
+    // Final update ID of the previous event (futures streams only)
+    prev_final_update_id: Option<u64>,
+
+    // List of changed bid price levels and quantities (quantity 0 removes the level)
+    bids: Vec<(Price, Qty)>,
+
+    // List of changed ask price levels and quantities (quantity 0 removes the level)
+    asks: Vec<(Price, Qty)>,
+}
+
+impl DepthDiff {
+    /// Constructor function to create a new DepthDiff
+    pub fn new(
+        first_update_id: u64,
+        final_update_id: u64,
+        prev_final_update_id: Option<u64>,
+        bids: Vec<(Price, Qty)>,
+        asks: Vec<(Price, Qty)>,
+    ) -> Self {
+        Self {
+            first_update_id,
+            final_update_id,
+            prev_final_update_id,
+            bids,
+            asks,
+        }
+    }
+
+    /// Function to get all levels of the event
+    pub fn levels(&self) -> Vec<(Price, Qty)> {
+        self.bids.iter().chain(&self.asks).copied().collect()
+    }
+
+    /// Function to construct a DepthDiff from a reader (deserialized data)
+    pub fn from_reader(reader: DepthDiffReader) -> Result<Self, OrderBookError> {
+        Ok(Self {
+            first_update_id: reader.first_update_id,
+            final_update_id: reader.final_update_id,
+            prev_final_update_id: reader.prev_final_update_id,
+            bids: parse_levels(reader.bids, "bids")?,
+            asks: parse_levels(reader.asks, "asks")?,
+        })
+    }
+}
+
+/// Struct representing a reader for DepthDiff, used for deserialization of `depthUpdate` events
+#[derive(Debug, Deserialize)]
+pub struct DepthDiffReader {
+    /// Event time in milliseconds
+    #[serde(rename = "E")]
+    pub event_time: u64,
+
+    /// Trading pair symbol
+    #[serde(rename = "s")]
+    pub symbol: String,
+
+    /// First update ID in the event
+    #[serde(rename = "U")]
+    pub first_update_id: u64,
+
+    /// Final update ID in the event
+    #[serde(rename = "u")]
+    pub final_update_id: u64,
+
+    /// Final update ID of the previous event (only present on futures streams)
+    #[serde(rename = "pu", default)]
+    pub prev_final_update_id: Option<u64>,
+
+    /// Changed bids as arrays of [price, quantity] in strings
+    #[serde(rename = "b")]
+    pub bids: Vec<[String; 2]>,
+
+    /// Changed asks as arrays of [price, quantity] in strings
+    #[serde(rename = "a")]
     pub asks: Vec<[String; 2]>,
 }
+
+impl DepthDiffReader {
+    /// Function to read the event as a partial depth message (futures `@depth<N>` streams push `depthUpdate` events)
+    pub fn into_partial(self) -> DepthUpdateReader {
+        DepthUpdateReader {
+            last_update_id: self.final_update_id,
+            bids: self.bids,
+            asks: self.asks,
+        }
+    }
+}
+
+/// Struct representing a reader for `trade` events, one per executed trade
+#[derive(Debug, Deserialize)]
+pub struct TradeReader {
+    /// Event time in milliseconds
+    #[serde(rename = "E")]
+    pub event_time: u64,
+
+    /// Trading pair symbol
+    #[serde(rename = "s")]
+    pub symbol: String,
+
+    /// Trade ID
+    #[serde(rename = "t")]
+    pub trade_id: u64,
+
+    /// Price in string
+    #[serde(rename = "p")]
+    pub price: String,
+
+    /// Quantity in string
+    #[serde(rename = "q")]
+    pub qty: String,
+
+    /// Trade time in milliseconds
+    #[serde(rename = "T")]
+    pub trade_time: u64,
+
+    /// Whether the buyer was the maker, i.e., the seller was the aggressor
+    #[serde(rename = "m")]
+    pub buyer_is_maker: bool,
+}
+
+/// Struct representing a reader for `aggTrade` events, trades of one taker order at one price aggregated
+#[derive(Debug, Deserialize)]
+pub struct AggTradeReader {
+    /// Event time in milliseconds
+    #[serde(rename = "E")]
+    pub event_time: u64,
+
+    /// Trading pair symbol
+    #[serde(rename = "s")]
+    pub symbol: String,
+
+    /// Aggregate trade ID
+    #[serde(rename = "a")]
+    pub agg_trade_id: u64,
+
+    /// Price in string
+    #[serde(rename = "p")]
+    pub price: String,
+
+    /// Quantity in string
+    #[serde(rename = "q")]
+    pub qty: String,
+
+    /// First trade ID of the aggregate
+    #[serde(rename = "f")]
+    pub first_trade_id: u64,
+
+    /// Last trade ID of the aggregate
+    #[serde(rename = "l")]
+    pub last_trade_id: u64,
+
+    /// Trade time in milliseconds
+    #[serde(rename = "T")]
+    pub trade_time: u64,
+
+    /// Whether the buyer was the maker, i.e., the seller was the aggressor
+    #[serde(rename = "m")]
+    pub buyer_is_maker: bool,
+}
+
+/// Struct representing a reader for `kline` events, the current state of one candle
+#[derive(Debug, Deserialize)]
+pub struct KlineReader {
+    /// Event time in milliseconds
+    #[serde(rename = "E")]
+    pub event_time: u64,
+
+    /// Trading pair symbol
+    #[serde(rename = "s")]
+    pub symbol: String,
+
+    /// The candle
+    #[serde(rename = "k")]
+    pub kline: KlineDataReader,
+}
+
+/// Struct representing the candle of a `kline` event
+#[derive(Debug, Deserialize)]
+pub struct KlineDataReader {
+    /// Open time in milliseconds
+    #[serde(rename = "t")]
+    pub open_time: u64,
+
+    /// Close time in milliseconds (the last millisecond of the interval)
+    #[serde(rename = "T")]
+    pub close_time: u64,
+
+    /// Interval name (e.g., 1m)
+    #[serde(rename = "i")]
+    pub interval: String,
+
+    /// Open price in string
+    #[serde(rename = "o")]
+    pub open: String,
+
+    /// High price in string
+    #[serde(rename = "h")]
+    pub high: String,
+
+    /// Low price in string
+    #[serde(rename = "l")]
+    pub low: String,
+
+    /// Close (latest) price in string
+    #[serde(rename = "c")]
+    pub close: String,
+
+    /// Base volume in string
+    #[serde(rename = "v")]
+    pub volume: String,
+
+    /// Number of trades
+    #[serde(rename = "n")]
+    pub trade_count: u64,
+
+    /// Whether the candle is closed
+    #[serde(rename = "x")]
+    pub closed: bool,
+}
+
+/// Struct representing a reader for futures `markPriceUpdate` events
+#[derive(Debug, Deserialize)]
+pub struct MarkPriceReader {
+    /// Event time in milliseconds
+    #[serde(rename = "E")]
+    pub event_time: u64,
+
+    /// Trading pair symbol
+    #[serde(rename = "s")]
+    pub symbol: String,
+
+    /// Mark price in string
+    #[serde(rename = "p")]
+    pub mark_price: String,
+
+    /// Index price in string (USD-M only)
+    #[serde(rename = "i", default)]
+    pub index_price: Option<String>,
+
+    /// Estimated settle price in string
+    #[serde(rename = "P", default)]
+    pub estimated_settle_price: Option<String>,
+
+    /// Funding rate in string, empty for delivery contracts
+    #[serde(rename = "r")]
+    pub funding_rate: String,
+
+    /// Next funding time in milliseconds, 0 for delivery contracts
+    #[serde(rename = "T")]
+    pub next_funding_time: u64,
+}
+
+/// Struct representing a reader for futures `forceOrder` events, one per liquidation order
+#[derive(Debug, Deserialize)]
+pub struct ForceOrderReader {
+    /// Event time in milliseconds
+    #[serde(rename = "E")]
+    pub event_time: u64,
+
+    /// The liquidation order
+    #[serde(rename = "o")]
+    pub order: ForceOrderDataReader,
+}
+
+/// Struct representing the liquidation order of a `forceOrder` event
+#[derive(Debug, Deserialize)]
+pub struct ForceOrderDataReader {
+    /// Trading pair symbol
+    #[serde(rename = "s")]
+    pub symbol: String,
+
+    /// Side of the order (BUY or SELL)
+    #[serde(rename = "S")]
+    pub side: String,
+
+    /// Original quantity in string
+    #[serde(rename = "q")]
+    pub qty: String,
+
+    /// Order price in string
+    #[serde(rename = "p")]
+    pub price: String,
+
+    /// Average fill price in string
+    #[serde(rename = "ap")]
+    pub average_price: String,
+
+    /// Accumulated filled quantity in string
+    #[serde(rename = "z")]
+    pub filled_qty: String,
+
+    /// Trade time in milliseconds
+    #[serde(rename = "T")]
+    pub trade_time: u64,
+}
+
+/// Struct representing a Binance message tagged with the name of the stream it was received on
+#[derive(Debug)]
+pub struct StreamMessage {
+    /// Stream name (e.g., bnbusdt@bookTicker)
+    pub stream: String,
+
+    /// Parsed message
+    pub message: BinanceMessage,
+
+    /// Recorded receive time in milliseconds of a replayed message, None for live messages
+    /// Replays use it as the clock of messages without an event time, so they do not depend on the local clock.
+    pub recv_time: Option<u64>,
+}
+
+impl StreamMessage {
+    /// Function to get the uppercase symbol of the stream (the part before the first `@`)
+    pub fn symbol(&self) -> String {
+        self.stream
+            .split('@')
+            .next()
+            .unwrap_or_default()
+            .to_uppercase()
+    }
+}
+
+/// Struct representing a reader for combined stream envelopes (`{"stream":..,"data":..}`)
+#[derive(Debug, Deserialize)]
+pub struct CombinedStreamReader<'a> {
+    /// Stream name the payload was received on
+    pub stream: String,
+
+    /// Raw payload of the stream message
+    #[serde(borrow)]
+    pub data: &'a RawValue,
+}
+
+/// Struct representing the reconnect settings of the supervised stream connection
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    /// Delay before the first reconnect attempt
+    pub initial_backoff: Duration,
+
+    /// Upper bound for the exponentially growing delay
+    pub max_backoff: Duration,
+
+    /// Age after which a connection is proactively rolled over (Binance disconnects after 24h)
+    pub max_connection_age: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            max_connection_age: Duration::from_secs(23 * 60 * 60),
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Function to compute the delay before a reconnect attempt (1-based)
+    /// The delay doubles with every attempt up to `max_backoff`, with a random jitter of up to 50%
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let delay = self
+            .initial_backoff
+            .saturating_mul(1 << exponent)
+            .min(self.max_backoff);
+        delay.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+    }
+}
@@ -0,0 +1,194 @@
+use super::*;
+
+/// Struct representing the trading rules of a symbol, taken from `exchangeInfo`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolInfo {
+    /// Trading pair symbol (e.g., BNBUSDT)
+    pub symbol: String,
+
+    /// Trading status (e.g., TRADING, BREAK)
+    pub status: String,
+
+    /// Base asset (e.g., BNB)
+    pub base_asset: String,
+
+    /// Quote asset (e.g., USDT)
+    pub quote_asset: String,
+
+    /// Minimum price increment (PRICE_FILTER tickSize), zero if not enforced
+    pub tick_size: Price,
+
+    /// Minimum quantity increment (LOT_SIZE stepSize), zero if not enforced
+    pub step_size: Qty,
+}
+
+impl SymbolInfo {
+    /// Function to construct a SymbolInfo from a reader (deserialized data)
+    pub fn from_reader(reader: SymbolInfoReader) -> Result<Self, OrderBookError> {
+        let mut tick_size = Price::ZERO;
+        let mut step_size = Qty::ZERO;
+
+        // Pick the tick size and step size out of the symbol's filters
+        for filter in reader.filters {
+            match filter {
+                SymbolFilterReader::PriceFilter { tick_size: tick } => {
+                    tick_size = parse_decimal(&tick, "tickSize")?;
+                }
+                SymbolFilterReader::LotSize { step_size: step } => {
+                    step_size = parse_decimal(&step, "stepSize")?;
+                }
+                SymbolFilterReader::Other => {}
+            }
+        }
+
+        Ok(Self {
+            symbol: reader.symbol,
+            status: reader.status,
+            base_asset: reader.base_asset,
+            quote_asset: reader.quote_asset,
+            tick_size,
+            step_size,
+        })
+    }
+
+    /// Function to check whether the symbol is currently trading
+    pub fn is_trading(&self) -> bool {
+        self.status == "TRADING"
+    }
+
+    /// Function to check whether a price is a multiple of the tick size
+    pub fn is_on_tick(&self, price: Price) -> bool {
+        price.is_multiple_of(self.tick_size)
+    }
+
+    /// Function to check whether a quantity is a multiple of the step size
+    pub fn is_on_step(&self, qty: Qty) -> bool {
+        qty.is_multiple_of(self.step_size)
+    }
+}
+
+/// Struct representing a registry of symbol metadata loaded from an `exchangeInfo` document
+#[derive(Debug, Clone, Default)]
+pub struct SymbolRegistry {
+    // Symbol metadata keyed by the uppercase symbol
+    symbols: HashMap<String, SymbolInfo>,
+}
+
+impl SymbolRegistry {
+    /// Function to build a registry from an `exchangeInfo` JSON document
+    pub fn from_json(json: &str) -> Result<Self, OrderBookError> {
+        let reader = serde_json::from_str::<ExchangeInfoReader>(json)?;
+        Self::from_reader(reader)
+    }
+
+    /// Function to build a registry from an `exchangeInfo` JSON file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, OrderBookError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// Function to build a registry from the `/api/v3/exchangeInfo` endpoint of a REST API
+    pub async fn from_url(base_url: &str) -> Result<Self, OrderBookError> {
+        Self::from_market_url(base_url, Market::Spot).await
+    }
+
+    /// Function to build a registry from the `exchangeInfo` endpoint of a market's REST API
+    /// (e.g., `/fapi/v1/exchangeInfo` for USD-M futures)
+    pub async fn from_market_url(base_url: &str, market: Market) -> Result<Self, OrderBookError> {
+        let url = format!(
+            "{}{}/exchangeInfo",
+            base_url.trim_end_matches('/'),
+            market.api_prefix()
+        );
+        let response = reqwest::get(url).await?.error_for_status()?;
+        Self::from_reader(response.json::<ExchangeInfoReader>().await?)
+    }
+
+    // Function to build a registry from a reader (deserialized data)
+    fn from_reader(reader: ExchangeInfoReader) -> Result<Self, OrderBookError> {
+        let symbols = reader
+            .symbols
+            .into_iter()
+            .map(|symbol| {
+                let info = SymbolInfo::from_reader(symbol)?;
+                Ok((info.symbol.to_uppercase(), info))
+            })
+            .collect::<Result<_, OrderBookError>>()?;
+
+        Ok(Self { symbols })
+    }
+
+    /// Function to look up the metadata of a symbol (case-insensitive)
+    pub fn get(&self, symbol: &str) -> Option<&SymbolInfo> {
+        self.symbols.get(&symbol.to_uppercase())
+    }
+
+    /// Function to look up the metadata of a symbol, failing for unknown symbols
+    pub fn require(&self, symbol: &str) -> Result<&SymbolInfo, OrderBookError> {
+        self.get(symbol).ok_or_else(|| {
+            OrderBookError::UnknownSymbol(format!("{} is not listed in exchangeInfo", symbol))
+        })
+    }
+
+    /// Function to get the number of symbols in the registry
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    /// Function to check whether the registry is empty
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+}
+
+/// Struct representing a reader for the `exchangeInfo` document, used for deserialization from JSON
+#[derive(Debug, Deserialize)]
+pub struct ExchangeInfoReader {
+    /// Metadata of every listed symbol
+    pub symbols: Vec<SymbolInfoReader>,
+}
+
+/// Struct representing a reader for a single symbol of `exchangeInfo`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolInfoReader {
+    /// Trading pair symbol
+    pub symbol: String,
+
+    /// Trading status (`contractStatus` for COIN-M futures)
+    #[serde(alias = "contractStatus")]
+    pub status: String,
+
+    /// Base asset
+    pub base_asset: String,
+
+    /// Quote asset
+    pub quote_asset: String,
+
+    /// Trading rule filters of the symbol
+    pub filters: Vec<SymbolFilterReader>,
+}
+
+/// Enum representing the `exchangeInfo` symbol filters the order book cares about
+#[derive(Debug, Deserialize)]
+#[serde(tag = "filterType")]
+pub enum SymbolFilterReader {
+    /// Price rules, the tick size is the minimum price increment (as string for deserialization)
+    #[serde(rename = "PRICE_FILTER")]
+    PriceFilter {
+        /// Minimum price increment
+        #[serde(rename = "tickSize")]
+        tick_size: String,
+    },
+
+    /// Quantity rules, the step size is the minimum quantity increment (as string for deserialization)
+    #[serde(rename = "LOT_SIZE")]
+    LotSize {
+        /// Minimum quantity increment
+        #[serde(rename = "stepSize")]
+        step_size: String,
+    },
+
+    /// Any other filter type
+    #[serde(other)]
+    Other,
+}
@@ -1,16 +1,26 @@
-use ordered_float::OrderedFloat;
-
 use super::*;
+use futures::channel::mpsc::unbounded;
+
+// Helper to build an exact decimal from its string form
+fn dec(value: &str) -> Decimal {
+    value.parse().unwrap()
+}
 
 #[test]
 fn test_update_book_ticker() {
     let mut orderbook = OrderBook::new("BNBUSDT".to_string());
-    let book_ticker_update = BookTickerUpdate::new(1, 25.3519, 31.21, 25.3652, 40.66);
+    let book_ticker_update = BookTickerUpdate::new(
+        1,
+        dec("25.3519"),
+        dec("31.21"),
+        dec("25.3652"),
+        dec("40.66"),
+    );
 
     orderbook.update_book_ticker(&book_ticker_update);
     let best_bid_ask = orderbook.get_best_bid_ask().unwrap();
-    assert_eq!(best_bid_ask.0, (25.3519, 31.21));
-    assert_eq!(best_bid_ask.1, (25.3652, 40.66));
+    assert_eq!(best_bid_ask.0, (dec("25.3519"), dec("31.21")));
+    assert_eq!(best_bid_ask.1, (dec("25.3652"), dec("40.66")));
 }
 
 #[test]
@@ -18,22 +28,2091 @@ fn test_update_depth() {
     let mut orderbook = OrderBook::new("BNBUSDT".to_string());
     let depth_update = DepthUpdate::new(
         2,
-        vec![(0.0024, 10.0), (0.0025, 5.0)],
-        vec![(0.0026, 100.0)],
+        vec![(dec("0.0024"), dec("10.0")), (dec("0.0025"), dec("5.0"))],
+        vec![(dec("0.0026"), dec("100.0"))],
     );
 
     orderbook.update_depth(&depth_update);
-    assert_eq!(orderbook.get_volume_at_price(0.0024), 10.0);
-    assert_eq!(orderbook.get_volume_at_price(0.0026), 100.0);
+    assert_eq!(orderbook.get_volume_at_price(dec("0.0024")), dec("10.0"));
+    assert_eq!(orderbook.get_volume_at_price(dec("0.0026")), dec("100.0"));
 }
 
 #[test]
 fn test_get_volume_at_price() {
     let mut orderbook = OrderBook::new("BNBUSDT".to_string());
-    orderbook.bids.insert(OrderedFloat(0.0024), 10.0);
-    orderbook.asks.insert(OrderedFloat(0.0026), 100.0);
+    orderbook.bids.insert(dec("0.0024"), dec("10.0"));
+    orderbook.asks.insert(dec("0.0026"), dec("100.0"));
+
+    assert_eq!(orderbook.get_volume_at_price(dec("0.0024")), dec("10.0"));
+    assert_eq!(orderbook.get_volume_at_price(dec("0.0026")), dec("100.0"));
+    assert_eq!(orderbook.get_volume_at_price(dec("0.0030")), dec("0.0"));
+}
+
+#[test]
+fn test_depth_diff_buffered_until_snapshot() {
+    let mut orderbook = OrderBook::new("BNBUSDT".to_string());
+    let diff = DepthDiff::new(101, 105, None, vec![(dec("0.0024"), dec("7.0"))], vec![]);
+
+    assert_eq!(
+        orderbook.apply_depth_diff(diff).unwrap(),
+        SyncState::AwaitingSnapshot
+    );
+    assert_eq!(orderbook.get_volume_at_price(dec("0.0024")), dec("0.0"));
+
+    let snapshot = DepthUpdate::new(
+        102,
+        vec![(dec("0.0024"), dec("10.0"))],
+        vec![(dec("0.0026"), dec("100.0"))],
+    );
+    assert_eq!(
+        orderbook.apply_depth_snapshot(&snapshot).unwrap(),
+        SyncState::Synced
+    );
+    assert_eq!(orderbook.get_volume_at_price(dec("0.0024")), dec("7.0"));
+    assert_eq!(orderbook.get_volume_at_price(dec("0.0026")), dec("100.0"));
+}
+
+#[test]
+fn test_depth_snapshot_discards_old_events_and_removes_levels() {
+    let mut orderbook = OrderBook::new("BNBUSDT".to_string());
+    let old = DepthDiff::new(90, 99, None, vec![(dec("0.0023"), dec("1.0"))], vec![]);
+    let next = DepthDiff::new(100, 102, None, vec![(dec("0.0024"), dec("0.0"))], vec![]);
+    orderbook.apply_depth_diff(old).unwrap();
+    orderbook.apply_depth_diff(next).unwrap();
+
+    let snapshot = DepthUpdate::new(
+        100,
+        vec![(dec("0.0024"), dec("10.0"))],
+        vec![(dec("0.0026"), dec("100.0"))],
+    );
+    orderbook.apply_depth_snapshot(&snapshot).unwrap();
+
+    assert_eq!(orderbook.get_volume_at_price(dec("0.0023")), dec("0.0"));
+    assert_eq!(orderbook.get_volume_at_price(dec("0.0024")), dec("0.0"));
+    assert_eq!(orderbook.get_volume_at_price(dec("0.0026")), dec("100.0"));
+}
+
+#[test]
+fn test_depth_snapshot_outdated() {
+    let mut orderbook = OrderBook::new("BNBUSDT".to_string());
+    let diff = DepthDiff::new(150, 155, None, vec![(dec("0.0024"), dec("7.0"))], vec![]);
+    orderbook.apply_depth_diff(diff).unwrap();
+
+    let snapshot = DepthUpdate::new(120, vec![(dec("0.0024"), dec("10.0"))], vec![]);
+    assert!(matches!(
+        orderbook.apply_depth_snapshot(&snapshot),
+        Err(OrderBookError::SnapshotOutdated(_))
+    ));
+    assert_eq!(orderbook.sync_state(), SyncState::AwaitingSnapshot);
+}
+
+#[test]
+fn test_depth_diff_gap_triggers_resync() {
+    let mut orderbook = OrderBook::new("BNBUSDT".to_string());
+    let snapshot = DepthUpdate::new(
+        100,
+        vec![(dec("0.0024"), dec("10.0"))],
+        vec![(dec("0.0026"), dec("100.0"))],
+    );
+    orderbook.apply_depth_snapshot(&snapshot).unwrap();
+
+    let stale = DepthDiff::new(95, 100, None, vec![(dec("0.0024"), dec("1.0"))], vec![]);
+    assert_eq!(
+        orderbook.apply_depth_diff(stale).unwrap(),
+        SyncState::Synced
+    );
+    assert_eq!(orderbook.get_volume_at_price(dec("0.0024")), dec("10.0"));
+
+    let gap = DepthDiff::new(105, 110, None, vec![(dec("0.0024"), dec("1.0"))], vec![]);
+    assert!(matches!(
+        orderbook.apply_depth_diff(gap),
+        Err(OrderBookError::SequenceGap(_))
+    ));
+    assert_eq!(orderbook.sync_state(), SyncState::AwaitingSnapshot);
+    assert!(orderbook.get_best_bid_ask().is_none());
+}
+
+#[test]
+fn test_depth_snapshot_keeps_events_after_a_replayed_gap() {
+    let mut orderbook = OrderBook::new("BNBUSDT".to_string());
+    let events = [
+        DepthDiff::new(101, 102, None, vec![(dec("0.0024"), dec("1.0"))], vec![]),
+        DepthDiff::new(105, 106, None, vec![(dec("0.0024"), dec("2.0"))], vec![]),
+        DepthDiff::new(107, 108, None, vec![(dec("0.0024"), dec("3.0"))], vec![]),
+    ];
+    for diff in events {
+        orderbook.apply_depth_diff(diff).unwrap();
+    }
+
+    let snapshot = DepthUpdate::new(100, vec![(dec("0.0024"), dec("10.0"))], vec![]);
+    assert!(matches!(
+        orderbook.apply_depth_snapshot(&snapshot),
+        Err(OrderBookError::SequenceGap(_))
+    ));
+    assert_eq!(orderbook.sync_state(), SyncState::AwaitingSnapshot);
+
+    // A snapshot bridging the gap replays the events after it
+    let snapshot = DepthUpdate::new(104, vec![(dec("0.0024"), dec("10.0"))], vec![]);
+    assert_eq!(
+        orderbook.apply_depth_snapshot(&snapshot).unwrap(),
+        SyncState::Synced
+    );
+    assert_eq!(orderbook.get_volume_at_price(dec("0.0024")), dec("3.0"));
+}
+
+#[test]
+fn test_snapshot_requests_are_deduplicated_and_backed_off() {
+    let mut orderbook = OrderBook::new("BNBUSDT".to_string());
+    assert!(!orderbook.request_snapshot(1_000));
+
+    let diff = DepthDiff::new(101, 105, None, vec![(dec("0.0024"), dec("7.0"))], vec![]);
+    orderbook.apply_depth_diff(diff).unwrap();
+    assert!(orderbook.request_snapshot(1_000));
+    assert!(!orderbook.request_snapshot(1_000));
+
+    // A failed request delays the next one
+    let failed = Err(OrderBookError::SnapshotUnavailable("down".to_string()));
+    orderbook.complete_snapshot_request(&failed, 1_000);
+    assert!(!orderbook.request_snapshot(1_000));
+    assert!(orderbook.request_snapshot(2_000));
+
+    let snapshot = DepthUpdate::new(102, vec![(dec("0.0024"), dec("10.0"))], vec![]);
+    let synced = orderbook.apply_depth_snapshot(&snapshot);
+    orderbook.complete_snapshot_request(&synced, 2_000);
+    assert!(!orderbook.request_snapshot(2_000));
+}
+
+#[test]
+fn test_futures_depth_diff_checks_previous_update_id() {
+    let mut orderbook = OrderBook::new("BTCUSDT".to_string());
+    let snapshot = DepthUpdate::new(
+        100,
+        vec![(dec("100.0"), dec("1.0"))],
+        vec![(dec("101.0"), dec("1.0"))],
+    );
+    orderbook.apply_depth_snapshot(&snapshot).unwrap();
+
+    let first = DepthDiff::new(98, 103, Some(97), vec![(dec("100.0"), dec("2.0"))], vec![]);
+    let second = DepthDiff::new(
+        104,
+        106,
+        Some(103),
+        vec![(dec("100.0"), dec("3.0"))],
+        vec![],
+    );
+    let gap = DepthDiff::new(
+        110,
+        112,
+        Some(108),
+        vec![(dec("100.0"), dec("4.0"))],
+        vec![],
+    );
+    orderbook.apply_depth_diff(first).unwrap();
+    orderbook.apply_depth_diff(second).unwrap();
+    assert_eq!(orderbook.get_volume_at_price(dec("100.0")), dec("3.0"));
+    assert!(orderbook.apply_depth_diff(gap).is_err());
+}
+
+#[test]
+fn test_parse_depth_diff_message() {
+    let json = include_str!("../test-bed/depth_diff_update.json");
+    assert!(matches!(
+        parse_binance_message(json),
+        Some(BinanceMessage::DepthDiff(_))
+    ));
+    let json = include_str!("../test-bed/depth_update.json");
+    assert!(matches!(
+        parse_binance_message(json),
+        Some(BinanceMessage::DepthUpdate(_))
+    ));
+}
+
+// Helper to serve a single HTTP response from a local stand-in server
+// Returns the server's base URL and a handle resolving to the raw request it received
+async fn spawn_http_stand_in(body: &'static str) -> (String, tokio::task::JoinHandle<String>) {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buf = [0u8; 1024];
+        let n = socket.read(&mut buf).await.unwrap();
+        let request = String::from_utf8_lossy(&buf[..n]).to_string();
+
+        let body = body.trim();
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+        request
+    });
+
+    (format!("http://{}", addr), server)
+}
+
+#[tokio::test]
+async fn test_rest_snapshot_source_against_local_server() {
+    // Local HTTP stand-in for the Binance REST API
+    let (base_url, server) =
+        spawn_http_stand_in(include_str!("../test-bed/depth_update.json")).await;
+
+    let source = RestSnapshotSource::new(&base_url, 100);
+    let snapshot = source.fetch_depth_snapshot("bnbusdt").await.unwrap();
+    assert_eq!(snapshot.last_update_id, 160);
+    assert_eq!(
+        snapshot.bids,
+        vec![["0.0024".to_string(), "10".to_string()]]
+    );
+
+    let request = server.await.unwrap();
+    assert!(request.starts_with("GET /api/v3/depth?symbol=BNBUSDT&limit=100 "));
+}
+
+#[test]
+fn test_partial_depth_replaces_stale_levels() {
+    let mut orderbook = OrderBook::new("BNBUSDT".to_string());
+    orderbook.update_depth(&DepthUpdate::new(
+        1,
+        vec![(dec("0.0025"), dec("5.0")), (dec("0.0024"), dec("10.0"))],
+        vec![(dec("0.0026"), dec("100.0")), (dec("0.0027"), dec("50.0"))],
+    ));
+    orderbook.update_depth(&DepthUpdate::new(
+        2,
+        vec![(dec("0.0023"), dec("8.0")), (dec("0.0022"), dec("4.0"))],
+        vec![(dec("0.0024"), dec("20.0")), (dec("0.0025"), dec("30.0"))],
+    ));
+
+    // Levels that dropped out of the top N are gone
+    assert_eq!(orderbook.get_volume_at_price(dec("0.0026")), dec("0.0"));
+    assert_eq!(
+        orderbook.get_best_bid_ask().unwrap().0,
+        (dec("0.0023"), dec("8.0"))
+    );
+    assert_eq!(
+        orderbook.get_best_bid_ask().unwrap().1,
+        (dec("0.0024"), dec("20.0"))
+    );
+
+    assert!(orderbook.is_level_authoritative(dec("0.0022")));
+    assert!(orderbook.is_level_authoritative(dec("0.0025")));
+    assert!(!orderbook.is_level_authoritative(dec("0.0021")));
+    assert!(!orderbook.is_level_authoritative(dec("0.0026")));
+}
+
+#[test]
+fn test_partial_depth_merge_mode() {
+    let mut orderbook = OrderBook::new("BNBUSDT".to_string());
+    orderbook.set_depth_apply_mode(DepthApplyMode::Merge);
+    orderbook.update_depth(&DepthUpdate::new(
+        1,
+        vec![(dec("0.0024"), dec("10.0"))],
+        vec![],
+    ));
+    orderbook.update_depth(&DepthUpdate::new(
+        2,
+        vec![(dec("0.0025"), dec("5.0"))],
+        vec![],
+    ));
+
+    assert_eq!(orderbook.get_volume_at_price(dec("0.0024")), dec("10.0"));
+    assert!(!orderbook.is_level_authoritative(dec("0.0024")));
+}
+
+#[test]
+fn test_sequence_tracked_per_stream() {
+    let mut orderbook = OrderBook::new("BNBUSDT".to_string());
+
+    // A large book ticker ID does not make a smaller depth ID outdated
+    orderbook
+        .check_update_sequence(StreamKind::BookTicker, 400900217)
+        .unwrap();
+    orderbook.update_book_ticker(&BookTickerUpdate::new(
+        400900217,
+        dec("25.35"),
+        dec("1.0"),
+        dec("25.36"),
+        dec("1.0"),
+    ));
+    orderbook
+        .check_update_sequence(StreamKind::PartialDepth, 160)
+        .unwrap();
+    orderbook.update_depth(&DepthUpdate::new(
+        160,
+        vec![(dec("25.35"), dec("1.0"))],
+        vec![],
+    ));
+
+    assert!(matches!(
+        orderbook.check_update_sequence(StreamKind::PartialDepth, 160),
+        Err(OrderBookError::DuplicateUpdate(_))
+    ));
+    assert!(matches!(
+        orderbook.check_update_sequence(StreamKind::PartialDepth, 150),
+        Err(OrderBookError::UpdateIdOutdated(_))
+    ));
+
+    let stats = orderbook.sequence_stats(StreamKind::PartialDepth);
+    assert_eq!(stats.in_order, 1);
+    assert_eq!(stats.duplicates, 1);
+    assert_eq!(stats.outdated, 1);
+    assert_eq!(orderbook.last_update_id(StreamKind::BookTicker), 400900217);
+}
+
+#[test]
+fn test_depth_diff_sequence_counters() {
+    let mut orderbook = OrderBook::new("BNBUSDT".to_string());
+    orderbook
+        .apply_depth_snapshot(&DepthUpdate::new(
+            100,
+            vec![(dec("0.0024"), dec("10.0"))],
+            vec![],
+        ))
+        .unwrap();
+
+    orderbook
+        .apply_depth_diff(DepthDiff::new(101, 103, None, vec![], vec![]))
+        .unwrap();
+    orderbook
+        .apply_depth_diff(DepthDiff::new(101, 103, None, vec![], vec![]))
+        .unwrap();
+    orderbook
+        .apply_depth_diff(DepthDiff::new(90, 99, None, vec![], vec![]))
+        .unwrap();
+    assert!(orderbook
+        .apply_depth_diff(DepthDiff::new(110, 111, None, vec![], vec![]))
+        .is_err());
+
+    let stats = orderbook.sequence_stats(StreamKind::DiffDepth);
+    assert_eq!(
+        stats,
+        SequenceStats {
+            in_order: 1,
+            duplicates: 1,
+            outdated: 1,
+            gaps: 1
+        }
+    );
+}
+
+#[test]
+fn test_book_ticker_hides_levels_better_than_top_of_book() {
+    let mut orderbook = OrderBook::new("BNBUSDT".to_string());
+    orderbook.update_depth(&DepthUpdate::new(
+        1,
+        vec![(dec("25.34"), dec("5.0")), (dec("25.36"), dec("3.0"))],
+        vec![(dec("25.37"), dec("4.0")), (dec("25.39"), dec("2.0"))],
+    ));
+
+    // The ticker reports a lower best bid and a higher best ask than the depth data
+    orderbook.update_book_ticker(&BookTickerUpdate::new(
+        2,
+        dec("25.35"),
+        dec("1.0"),
+        dec("25.38"),
+        dec("1.5"),
+    ));
+
+    let best_bid_ask = orderbook.get_best_bid_ask().unwrap();
+    assert_eq!(best_bid_ask.0, (dec("25.35"), dec("1.0")));
+    assert_eq!(best_bid_ask.1, (dec("25.38"), dec("1.5")));
+    assert_eq!(orderbook.get_volume_at_price(dec("25.36")), dec("0.0"));
+    assert_eq!(orderbook.get_volume_at_price(dec("25.37")), dec("0.0"));
+    assert_eq!(orderbook.get_volume_at_price(dec("25.39")), dec("2.0"));
+    assert!(orderbook.check_crossed().is_ok());
+}
+
+#[test]
+fn test_book_ticker_overlay_is_dropped_once_depth_moves_past_it() {
+    let mut orderbook = OrderBook::new("BNBUSDT".to_string());
+    orderbook
+        .apply_depth_snapshot(&DepthUpdate::new(
+            100,
+            vec![(dec("25.34"), dec("5.0")), (dec("25.35"), dec("3.0"))],
+            vec![(dec("25.37"), dec("4.0"))],
+        ))
+        .unwrap();
+    let ticker = BookTickerUpdate::new(1, dec("25.35"), dec("1.0"), dec("25.37"), dec("1.0"));
+    orderbook.update_book_ticker(&ticker);
+
+    // The ticker overlays the depth quantities without changing them
+    assert_eq!(orderbook.get_volume_at_price(dec("25.35")), dec("1.0"));
+    assert_eq!(orderbook.bids.get(&dec("25.35")), Some(&dec("3.0")));
+
+    // A newer diff bids through the ticker's ask: the overlay is dropped and the cross is reported
+    let diff = DepthDiff::new(101, 101, None, vec![(dec("25.38"), dec("2.0"))], vec![]);
+    orderbook.apply_depth_diff(diff).unwrap();
+    assert_eq!(
+        orderbook.get_best_bid_ask().unwrap().0,
+        (dec("25.38"), dec("2.0"))
+    );
+    assert!(matches!(
+        orderbook.check_crossed(),
+        Err(OrderBookError::CrossedBook(_))
+    ));
+
+    // An interrupted stream drops the overlay as well
+    let ticker = BookTickerUpdate::new(2, dec("25.35"), dec("1.0"), dec("25.37"), dec("1.0"));
+    orderbook.update_book_ticker(&ticker);
+    orderbook.mark_stale();
+    assert_eq!(orderbook.get_volume_at_price(dec("25.35")), dec("3.0"));
+}
+
+#[test]
+fn test_crossed_book_is_reported() {
+    let mut orderbook = OrderBook::new("BNBUSDT".to_string());
+    orderbook.set_depth_apply_mode(DepthApplyMode::Merge);
+    orderbook.update_depth(&DepthUpdate::new(
+        1,
+        vec![(dec("25.37"), dec("5.0"))],
+        vec![(dec("25.36"), dec("4.0"))],
+    ));
+
+    assert!(orderbook.is_crossed());
+    assert!(matches!(
+        orderbook.check_crossed(),
+        Err(OrderBookError::CrossedBook(_))
+    ));
+}
+
+#[test]
+fn test_decimal_round_trips_exchange_strings() {
+    for value in ["25.35190000", "0.0024", "100", "0.00000001", "-1.50"] {
+        assert_eq!(dec(value).to_string(), value);
+    }
+    assert_eq!(dec("25.35190000"), dec("25.3519"));
+    assert!(dec("25.3519") < dec("25.36520000"));
+    assert_eq!(dec("25.3519") * dec("2"), dec("50.7038"));
+    assert_eq!(dec("50.7038") / dec("2"), dec("25.3519"));
+    assert!("1.000000001".parse::<Decimal>().is_err());
+    assert!("abc".parse::<Decimal>().is_err());
+    assert_eq!(Decimal::new(253519, 4).unwrap(), dec("25.3519"));
+    assert!(Decimal::new(1, 9).is_err());
+}
+
+#[test]
+fn test_decimal_arithmetic_out_of_range() {
+    let huge = dec("100000000000000000000");
+    assert_eq!(dec("-0.5") * dec("0.5"), dec("-0.25"));
+    assert_eq!(dec("-7") / dec("2"), dec("-3.5"));
+    assert!(huge.checked_mul(huge).is_none());
+    assert!(dec("1").checked_div(Decimal::ZERO).is_none());
+
+    // The operators saturate instead of panicking or wrapping around
+    assert!((huge * huge).is_positive());
+    assert!((huge * (Decimal::ZERO - huge)) < Decimal::ZERO);
+    assert!((dec("1") / Decimal::ZERO).is_positive());
+    assert!((Decimal::ZERO / Decimal::ZERO).is_zero());
+    assert_eq!(huge * huge + huge, huge * huge);
+    assert!((huge * (Decimal::ZERO - huge)).abs().is_positive());
+
+    // Parsing rejects values out of range instead of overflowing
+    let max_int = (i128::MAX / 100_000_000).to_string();
+    assert!(format!("{}.99999999", max_int).parse::<Decimal>().is_err());
+    assert!(format!("{}.1", max_int).parse::<Decimal>().is_ok());
+}
+
+#[test]
+fn test_volume_at_price_matches_exchange_string() {
+    let json = include_str!("../test-bed/book_ticker_update_input.json");
+    let Some(BinanceMessage::BookTicker(reader)) = parse_binance_message(json) else {
+        panic!("expected a book ticker message");
+    };
+
+    let mut orderbook = OrderBook::new("BNBUSDT".to_string());
+    orderbook.update_book_ticker(&BookTickerUpdate::from_reader(reader).unwrap());
+
+    // The user's "25.3519" matches the exchange's "25.35190000" exactly
+    assert_eq!(
+        orderbook.get_volume_at_price(dec("25.3519")),
+        dec("31.21000000")
+    );
+    assert_eq!(
+        orderbook.get_best_bid_ask().unwrap().0 .0.to_string(),
+        "25.35190000"
+    );
+}
+
+#[test]
+fn test_symbol_registry_from_exchange_info() {
+    let registry = SymbolRegistry::from_file("test-bed/exchange_info.json").unwrap();
+    assert_eq!(registry.len(), 3);
+
+    let info = registry.get("bnbusdt").unwrap();
+    assert_eq!(info.base_asset, "BNB");
+    assert_eq!(info.quote_asset, "USDT");
+    assert_eq!(info.tick_size, dec("0.0001"));
+    assert_eq!(info.step_size, dec("0.001"));
+    assert!(info.is_trading());
+    assert!(!registry.get("LUNAUSDT").unwrap().is_trading());
+
+    assert!(matches!(
+        OrderBook::from_registry(&registry, "FOOBAR"),
+        Err(OrderBookError::UnknownSymbol(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_off_tick_prices_and_off_step_quantities_are_flagged() {
+    let registry = SymbolRegistry::from_file("test-bed/exchange_info.json").unwrap();
+    let mut orderbook = OrderBook::from_registry(&registry, "BNBUSDT").unwrap();
+
+    let prices = |levels: Vec<(Price, Qty)>| -> Vec<Price> {
+        levels.into_iter().map(|(price, _)| price).collect()
+    };
+    let on_tick = BookTickerUpdate::new(1, dec("25.3519"), dec("1"), dec("25.3652"), dec("1"));
+    assert!(orderbook.check_tick_size(&prices(on_tick.levels())).is_ok());
+
+    let off_tick = DepthUpdate::new(
+        2,
+        vec![(dec("25.35195"), dec("1"))],
+        vec![(dec("25.36525"), dec("1"))],
+    );
+    assert!(matches!(
+        orderbook.check_tick_size(&prices(off_tick.levels())),
+        Err(OrderBookError::OffTickPrice(_))
+    ));
+    assert_eq!(orderbook.off_tick_prices(), 2);
+    assert!(matches!(
+        orderbook.check_step_size(&[dec("1.0005"), dec("1.001")]),
+        Err(OrderBookError::OffStepQty(_))
+    ));
+    assert_eq!(orderbook.off_step_qtys(), 1);
+
+    // Flagged updates are still applied
+    let ticker = r#"{"u":3,"s":"BNBUSDT","b":"25.35195","B":"1.0005","a":"25.3652","A":"1"}"#;
+    let source = RestSnapshotSource::new("http://127.0.0.1:1", 100);
+    apply_binance_message(
+        &mut orderbook,
+        parse_binance_message(ticker).unwrap(),
+        &source,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        orderbook.get_volume_at_price(dec("25.35195")),
+        dec("1.0005")
+    );
+    assert_eq!(orderbook.off_tick_prices(), 3);
+    assert_eq!(orderbook.off_step_qtys(), 2);
+}
+
+#[tokio::test]
+async fn test_symbol_registry_from_local_server() {
+    let (base_url, server) =
+        spawn_http_stand_in(include_str!("../test-bed/exchange_info.json")).await;
+
+    let registry = SymbolRegistry::from_url(&base_url).await.unwrap();
+    assert!(registry.get("BNBBTC").is_some());
+    assert!(server
+        .await
+        .unwrap()
+        .starts_with("GET /api/v3/exchangeInfo "));
+}
+
+#[test]
+fn test_stream_command_json() {
+    let command = StreamCommand::Subscribe(vec!["bnbusdt@bookTicker".to_string()]);
+    let json: serde_json::Value = serde_json::from_str(&command.to_json(7)).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({"method": "SUBSCRIBE", "params": ["bnbusdt@bookTicker"], "id": 7})
+    );
+}
+
+#[tokio::test]
+async fn test_book_manager_routes_combined_stream_messages() {
+    let mut manager = BookManager::new(DepthMode::Partial, None);
+    let (command_tx, mut command_rx) = unbounded();
+    manager.set_command_sender(command_tx);
+    manager.add_symbol("bnbusdt").unwrap();
+    manager.add_symbol("BTCUSDT").unwrap();
+
+    assert_eq!(
+        command_rx.try_recv().ok(),
+        Some(StreamCommand::Subscribe(vec![
+            "bnbusdt@bookTicker".to_string(),
+            "bnbusdt@depth20@100ms".to_string()
+        ]))
+    );
+    assert_eq!(manager.symbols(), vec!["BNBUSDT", "BTCUSDT"]);
+
+    let message =
+        parse_stream_message(include_str!("../test-bed/combined_stream_update.json")).unwrap();
+    manager.apply(message).unwrap();
+
+    // Partial depth envelopes carry no symbol in the payload and are routed by stream name
+    let depth = r#"{"stream":"btcusdt@depth20@100ms","data":{"lastUpdateId":160,"bids":[["100.5","1"]],"asks":[["100.6","2"]]}}"#;
+    manager.apply(parse_stream_message(depth).unwrap()).unwrap();
+
+    let bnb = manager.book("BNBUSDT").unwrap();
+    assert_eq!(bnb.get_volume_at_price(dec("25.3519")), dec("31.21"));
+    let btc = manager.book("BTCUSDT").unwrap();
+    assert_eq!(btc.get_volume_at_price(dec("100.6")), dec("2"));
+
+    manager.remove_symbol("BTCUSDT").unwrap();
+    command_rx.try_recv().unwrap();
+    assert!(matches!(
+        command_rx.try_recv().ok(),
+        Some(StreamCommand::Unsubscribe(_))
+    ));
+    assert!(matches!(
+        manager.apply(parse_stream_message(depth).unwrap()),
+        Err(OrderBookError::UnknownSymbol(_))
+    ));
+}
+
+#[test]
+fn test_reconnect_backoff_is_capped_with_jitter() {
+    let policy = ReconnectPolicy {
+        initial_backoff: Duration::from_millis(100),
+        max_backoff: Duration::from_millis(1000),
+        ..ReconnectPolicy::default()
+    };
+
+    for (attempt, base) in [(1, 100), (2, 200), (4, 800), (5, 1000), (40, 1000)] {
+        let delay = policy.backoff(attempt);
+        assert!(delay <= Duration::from_millis(base), "attempt {}", attempt);
+        assert!(
+            delay >= Duration::from_millis(base / 2),
+            "attempt {}",
+            attempt
+        );
+    }
+}
+
+#[test]
+fn test_stale_diff_book_resyncs_from_snapshot() {
+    let mut orderbook = OrderBook::new("BNBUSDT".to_string());
+    let snapshot = DepthUpdate::new(100, vec![(dec("1.0"), dec("5"))], vec![]);
+    let diff =
+        |first, last, qty| DepthDiff::new(first, last, None, vec![(dec("1.0"), dec(qty))], vec![]);
+
+    orderbook.apply_depth_diff(diff(99, 101, "6")).unwrap();
+    orderbook.apply_depth_snapshot(&snapshot).unwrap();
+    assert!(!orderbook.is_stale());
+
+    // After a reconnect the old levels stay visible, but new events wait for a snapshot
+    orderbook.mark_stale();
+    assert!(orderbook.is_stale());
+    assert_eq!(
+        orderbook.apply_depth_diff(diff(150, 151, "7")).unwrap(),
+        SyncState::AwaitingSnapshot
+    );
+    assert_eq!(orderbook.get_volume_at_price(dec("1.0")), dec("6"));
+
+    let snapshot = DepthUpdate::new(150, vec![(dec("1.0"), dec("8"))], vec![]);
+    assert_eq!(
+        orderbook.apply_depth_snapshot(&snapshot).unwrap(),
+        SyncState::Synced
+    );
+    assert!(!orderbook.is_stale());
+    assert_eq!(orderbook.get_volume_at_price(dec("1.0")), dec("7"));
+}
+
+#[tokio::test]
+async fn test_websocket_client_reconnects_and_answers_pings() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("ws://{}", listener.local_addr().unwrap());
+    let update = include_str!("../test-bed/combined_stream_update.json").to_string();
+
+    // Stand-in server: the first connection is dropped after one message, the second one is pinged
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        ws.send(Message::Text(update.clone())).await.unwrap();
+        ws.close(None).await.unwrap();
+
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        ws.send(Message::Ping(b"keepalive".to_vec())).await.unwrap();
+        let pong = loop {
+            match ws.next().await.unwrap().unwrap() {
+                Message::Pong(payload) => break payload,
+                _ => continue,
+            }
+        };
+        ws.send(Message::Text(update)).await.unwrap();
+        pong
+    });
+
+    let (tx, mut rx) = unbounded();
+    let (_command_tx, command_rx) = unbounded();
+    let (event_tx, mut event_rx) = unbounded();
+    let policy = ReconnectPolicy {
+        initial_backoff: Duration::from_millis(10),
+        ..ReconnectPolicy::default()
+    };
+    let client = tokio::spawn(async move {
+        binance_websocket_client(
+            &base_url,
+            vec!["bnbusdt@bookTicker".to_string()],
+            tx,
+            command_rx,
+            event_tx,
+            policy,
+            None,
+        )
+        .await
+    });
+
+    assert_eq!(rx.next().await.unwrap().symbol(), "BNBUSDT");
+    assert_eq!(rx.next().await.unwrap().symbol(), "BNBUSDT");
+    assert_eq!(server.await.unwrap(), b"keepalive".to_vec());
+
+    let streams = vec!["bnbusdt@bookTicker".to_string()];
+    assert_eq!(
+        event_rx.next().await,
+        Some(ConnectionEvent::Connected(streams.clone()))
+    );
+    assert!(matches!(
+        event_rx.next().await,
+        Some(ConnectionEvent::Disconnected(_))
+    ));
+    assert!(matches!(
+        event_rx.next().await,
+        Some(ConnectionEvent::Reconnecting { attempt: 1, .. })
+    ));
+    assert_eq!(
+        event_rx.next().await,
+        Some(ConnectionEvent::Connected(streams))
+    );
+    client.abort();
+}
+
+#[tokio::test]
+async fn test_book_processor_start_pause_stop() {
+    let mut manager = BookManager::new(DepthMode::Partial, None);
+    manager.add_symbol("BTCUSDT").unwrap();
+    let manager = Arc::new(RwLock::new(manager));
+    let (tx, rx) = unbounded();
+    let source = RestSnapshotSource::new("http://127.0.0.1:1", 100);
+    let mut processor = BookProcessor::new(Arc::clone(&manager), rx, source);
+
+    let depth = |id: u64, qty: &str| {
+        let json = format!(
+            r#"{{"stream":"btcusdt@depth20@100ms","data":{{"lastUpdateId":{},"bids":[["100.5","{}"]],"asks":[]}}}}"#,
+            id, qty
+        );
+        parse_stream_message(&json).unwrap()
+    };
+    // Helper waiting until the task has applied the expected quantity
+    let volume_reaches = |qty: &'static str| {
+        let manager = Arc::clone(&manager);
+        async move {
+            for _ in 0..200 {
+                let volume = manager
+                    .read()
+                    .await
+                    .book("BTCUSDT")
+                    .unwrap()
+                    .get_volume_at_price(dec("100.5"));
+                if volume == dec(qty) {
+                    return true;
+                }
+                sleep(Duration::from_millis(5)).await;
+            }
+            false
+        }
+    };
+
+    processor.start();
+    tx.unbounded_send(depth(1, "1")).unwrap();
+    tx.unbounded_send(depth(2, "2")).unwrap();
+    assert!(volume_reaches("2").await);
+
+    // Paused processing leaves messages queued until it is resumed
+    processor.pause();
+    sleep(Duration::from_millis(20)).await;
+    tx.unbounded_send(depth(3, "3")).unwrap();
+    assert!(!volume_reaches("3").await);
+    processor.start();
+    assert!(volume_reaches("3").await);
+
+    // Stopped processing can be restarted with the same receiver
+    processor.stop().await;
+    assert_eq!(processor.state(), ProcessingState::Stopped);
+    tx.unbounded_send(depth(4, "4")).unwrap();
+    processor.start();
+    assert!(volume_reaches("4").await);
+    processor.stop().await;
+}
+
+#[test]
+fn test_top_levels_accumulate_quantity_and_notional() {
+    let mut orderbook = OrderBook::new("BNBUSDT".to_string());
+    orderbook.update_depth(&DepthUpdate::new(
+        1,
+        vec![
+            (dec("99.5"), dec("1")),
+            (dec("100"), dec("2")),
+            (dec("98"), dec("4")),
+        ],
+        vec![(dec("101"), dec("0.5")), (dec("102.5"), dec("2"))],
+    ));
 
-    assert_eq!(orderbook.get_volume_at_price(0.0024), 10.0);
-    assert_eq!(orderbook.get_volume_at_price(0.0026), 100.0);
-    assert_eq!(orderbook.get_volume_at_price(0.0030), 0.0);
+    let ladder = orderbook.top_levels(2);
+    let bids: Vec<_> = ladder
+        .bids
+        .iter()
+        .map(|l| (l.price, l.qty, l.cumulative_qty, l.cumulative_notional))
+        .collect();
+    assert_eq!(
+        bids,
+        vec![
+            (dec("100"), dec("2"), dec("2"), dec("200")),
+            (dec("99.5"), dec("1"), dec("3"), dec("299.5")),
+        ]
+    );
+    assert_eq!(ladder.asks.len(), 2);
+    assert_eq!(ladder.asks[1].cumulative_qty, dec("2.5"));
+    assert_eq!(ladder.asks[1].cumulative_notional, dec("255.5"));
+
+    // Levels hidden by the book ticker overlay are not part of the ladder
+    orderbook.update_book_ticker(&BookTickerUpdate::new(
+        2,
+        dec("99.5"),
+        dec("1"),
+        dec("101"),
+        dec("0.5"),
+    ));
+    assert_eq!(orderbook.top_levels(1).bids[0].price, dec("99.5"));
+}
+
+#[test]
+fn test_simulate_market_order_walks_the_book() {
+    let mut orderbook = OrderBook::new("BNBUSDT".to_string());
+    orderbook.update_depth(&DepthUpdate::new(
+        1,
+        vec![(dec("99"), dec("1")), (dec("98"), dec("2"))],
+        vec![(dec("101"), dec("1")), (dec("102"), dec("1"))],
+    ));
+
+    // Buying 1.5 takes the first ask and half of the second one
+    let buy = orderbook.simulate_market_order(Side::Buy, OrderSize::Base(dec("1.5")));
+    assert_eq!(buy.filled_qty, dec("1.5"));
+    assert_eq!(buy.notional, dec("152"));
+    assert_eq!(buy.worst_price, Some(dec("102")));
+    assert_eq!(buy.levels_consumed, 2);
+    assert!(buy.is_fully_filled());
+    let average = buy.average_price.unwrap();
+    assert_eq!(average, dec("101.33333333"));
+    let expected_bps = (average.to_f64() - 100.0) / 100.0 * 10_000.0;
+    assert!((buy.slippage_bps.unwrap() - expected_bps).abs() < 1e-9);
+
+    // Selling 197 quote notional takes the first bid and half of the second one
+    let sell = orderbook.simulate_market_order(Side::Sell, OrderSize::Quote(dec("197")));
+    assert_eq!(sell.filled_qty, dec("2"));
+    assert_eq!(sell.average_price, Some(dec("98.5")));
+    assert!((sell.slippage_bps.unwrap() - 150.0).abs() < 1e-9);
+    assert!(sell.is_fully_filled());
+
+    // Orders larger than the visible book report the unfilled remainder
+    let large = orderbook.simulate_market_order(Side::Buy, OrderSize::Base(dec("5")));
+    assert_eq!(large.filled_qty, dec("2"));
+    assert_eq!(large.unfilled, OrderSize::Base(dec("3")));
+    assert!(!large.is_fully_filled());
+}
+
+#[tokio::test]
+async fn test_book_metrics_are_refreshed_on_applied_messages() {
+    let mut orderbook = OrderBook::new("BNBUSDT".to_string());
+    let source = RestSnapshotSource::new("http://127.0.0.1:1", 100);
+    let depth = r#"{"lastUpdateId":1,"bids":[["99","3"],["98.95","1"],["90","10"]],"asks":[["101","1"],["101.05","2"]]}"#;
+    apply_binance_message(
+        &mut orderbook,
+        parse_binance_message(depth).unwrap(),
+        &source,
+    )
+    .await
+    .unwrap();
+
+    let metrics = orderbook.metrics().unwrap().clone();
+    assert_eq!(metrics.mid, dec("100"));
+    assert_eq!(metrics.spread, dec("2"));
+    assert!((metrics.spread_bps - 200.0).abs() < 1e-9);
+    // More bid size at the top pulls the microprice towards the ask
+    assert_eq!(metrics.microprice, dec("100.5"));
+    // Top 5 levels: 14 bid vs 3 ask
+    assert!((metrics.imbalance.unwrap() - 11.0 / 17.0).abs() < 1e-9);
+
+    // Levels within 110 bps of the mid (98.9 - 101.1)
+    let liquidity = orderbook.liquidity_within_bps(110.0);
+    assert_eq!(liquidity.bid_qty, dec("4"));
+    assert_eq!(liquidity.bid_notional, dec("395.95"));
+    assert_eq!(liquidity.ask_qty, dec("3"));
+    assert_eq!(liquidity.ask_notional, dec("303.1"));
+    assert_eq!(metrics.liquidity, orderbook.liquidity_within_bps(10.0));
+}
+
+#[tokio::test]
+async fn test_stream_recorder_rotates_compressed_files() {
+    let directory = std::env::temp_dir().join(format!("recorder-test-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    let config = RecorderConfig {
+        directory: directory.clone(),
+        prefix: "test".to_string(),
+        max_file_bytes: 200,
+        compress: true,
+    };
+    let (recorder, writer) = StreamRecorder::start(config, true);
+
+    let update = include_str!("../test-bed/combined_stream_update.json").trim();
+    let frames: Vec<_> = (0..3)
+        .map(|i| {
+            RecordedFrame::new(
+                UNIX_EPOCH + Duration::from_nanos(1_000 + i),
+                Some("bnbusdt@bookTicker".to_string()),
+                update.to_string(),
+            )
+        })
+        .collect();
+    for frame in &frames {
+        recorder.record(frame.clone());
+    }
+    // Frames received while recording is switched off are not written
+    recorder.set_enabled(false);
+    recorder.record(RecordedFrame::new(
+        UNIX_EPOCH,
+        None,
+        r#"{"result":null,"id":1}"#.to_string(),
+    ));
+    drop(recorder);
+    writer.await.unwrap().unwrap();
+
+    let mut paths: Vec<_> = std::fs::read_dir(&directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    paths.sort();
+    assert_eq!(paths.len(), 3);
+    assert!(paths[0].ends_with("test.1000.jsonl.gz"));
+
+    let recorded: Vec<_> = paths
+        .iter()
+        .flat_map(|path| read_recording(path).unwrap())
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(recorded, frames);
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[tokio::test]
+async fn test_snapshot_fetch_does_not_hold_the_manager_lock() {
+    let mut manager = BookManager::new(DepthMode::Diff, None);
+    manager.add_symbol("BNBUSDT").unwrap();
+    let manager = Arc::new(RwLock::new(manager));
+    let (tx, rx) = unbounded();
+    // The source only answers once a snapshot is published
+    let source = ReplaySnapshotSource::new();
+    let mut processor = BookProcessor::new(Arc::clone(&manager), rx, source.clone());
+    processor.start();
+
+    let diff = |first: u64, last: u64, qty: &str| {
+        let json = format!(
+            r#"{{"stream":"bnbusdt@depth@100ms","data":{{"e":"depthUpdate","E":1,"s":"BNBUSDT","U":{},"u":{},"b":[["25.35","{}"]],"a":[]}}}}"#,
+            first, last, qty
+        );
+        parse_stream_message(&json).unwrap()
+    };
+    tx.unbounded_send(diff(157, 160, "10")).unwrap();
+    tx.unbounded_send(diff(161, 161, "11")).unwrap();
+    let ticker = r#"{"stream":"bnbusdt@bookTicker","data":{"u":1,"s":"BNBUSDT","b":"25.35","B":"5","a":"25.36","A":"5"}}"#;
+    tx.unbounded_send(parse_stream_message(ticker).unwrap())
+        .unwrap();
+
+    // Messages are applied and the books stay readable while the snapshot is being fetched
+    let mut applied = false;
+    for _ in 0..200 {
+        let manager = tokio::time::timeout(Duration::from_secs(1), manager.read())
+            .await
+            .unwrap();
+        let orderbook = manager.book("BNBUSDT").unwrap();
+        applied = orderbook.last_update_id(StreamKind::BookTicker) == 1;
+        if applied {
+            assert_eq!(orderbook.sync_state(), SyncState::AwaitingSnapshot);
+            break;
+        }
+        drop(manager);
+        sleep(Duration::from_millis(5)).await;
+    }
+    assert!(applied);
+
+    source.publish(
+        "BNBUSDT",
+        r#"{"lastUpdateId":158,"bids":[["25.35","1"]],"asks":[["25.36","2"]]}"#.to_string(),
+    );
+    let mut synced = false;
+    for _ in 0..200 {
+        let manager = manager.read().await;
+        let orderbook = manager.book("BNBUSDT").unwrap();
+        synced = orderbook.sync_state() == SyncState::Synced;
+        if synced {
+            assert_eq!(orderbook.last_update_id(StreamKind::DiffDepth), 161);
+            assert_eq!(orderbook.get_volume_at_price(dec("25.35")), dec("11"));
+            break;
+        }
+        drop(manager);
+        sleep(Duration::from_millis(5)).await;
+    }
+    assert!(synced);
+    processor.stop().await;
+}
+
+#[tokio::test]
+async fn test_replay_rebuilds_book_with_recorded_snapshots() {
+    let mut manager = BookManager::new(DepthMode::Diff, None);
+    manager.add_symbol("BNBUSDT").unwrap();
+    let manager = Arc::new(RwLock::new(manager));
+    let (tx, rx) = unbounded();
+    let source = ReplaySnapshotSource::new();
+    let mut processor = BookProcessor::new(Arc::clone(&manager), rx, source.clone());
+    processor.start();
+
+    let paths = [PathBuf::from("test-bed/recorded_session.jsonl")];
+    let stats = replay_recording(&paths, tx, ReplaySpeed::Multiplier(10.0), &source)
+        .await
+        .unwrap();
+    assert_eq!(
+        stats,
+        ReplayStats {
+            frames: 5,
+            messages: 3,
+            snapshots: 1,
+            skipped: 1
+        }
+    );
+
+    // Wait until the processor has applied the last recorded message and the snapshot
+    for _ in 0..200 {
+        let manager = manager.read().await;
+        let orderbook = manager.book("BNBUSDT").unwrap();
+        if orderbook.last_update_id(StreamKind::BookTicker) > 0
+            && orderbook.sync_state() == SyncState::Synced
+        {
+            break;
+        }
+        drop(manager);
+        sleep(Duration::from_millis(5)).await;
+    }
+    processor.stop().await;
+
+    let manager = manager.read().await;
+    let orderbook = manager.book("BNBUSDT").unwrap();
+    assert_eq!(orderbook.sync_state(), SyncState::Synced);
+    assert_eq!(orderbook.last_update_id(StreamKind::DiffDepth), 161);
+    assert_eq!(
+        orderbook.bids.iter().collect::<Vec<_>>(),
+        vec![(&dec("25.35"), &dec("10"))]
+    );
+    assert_eq!(orderbook.get_volume_at_price(dec("25.36")), dec("100"));
+}
+
+// Helper to replay recording files into a fresh diff book manager and get the resulting book
+// once `done` holds for it
+async fn replay_into_book(
+    paths: &[PathBuf],
+    symbol: &str,
+    done: impl Fn(&OrderBook) -> bool,
+) -> OrderBook {
+    let mut manager = BookManager::new(DepthMode::Diff, None);
+    manager.add_symbol(symbol).unwrap();
+    let manager = Arc::new(RwLock::new(manager));
+    let (tx, rx) = unbounded();
+    let source = ReplaySnapshotSource::new();
+    let mut processor = BookProcessor::new(Arc::clone(&manager), rx, source.clone());
+    processor.start();
+    replay_recording(paths, tx, ReplaySpeed::AsFastAsPossible, &source)
+        .await
+        .unwrap();
+
+    // Wait until the processor has applied the last recorded message
+    for _ in 0..200 {
+        let manager = manager.read().await;
+        if done(manager.book(symbol).unwrap()) {
+            break;
+        }
+        drop(manager);
+        sleep(Duration::from_millis(5)).await;
+    }
+    processor.stop().await;
+    let orderbook = manager.read().await.book(symbol).unwrap().clone();
+    orderbook
+}
+
+#[tokio::test]
+async fn test_replays_of_a_recording_are_identical() {
+    let paths = [PathBuf::from("test-bed/recorded_session.jsonl")];
+    let done = |orderbook: &OrderBook| {
+        orderbook.last_update_id(StreamKind::BookTicker) > 0
+            && orderbook.sync_state() == SyncState::Synced
+    };
+    let first = replay_into_book(&paths, "BNBUSDT", done).await;
+    sleep(Duration::from_millis(20)).await;
+    let second = replay_into_book(&paths, "BNBUSDT", done).await;
+
+    // Messages without an event time (the book ticker) and the snapshot run on the recorded clock
+    assert_eq!(first.clock(), 1_700_000_000_150);
+    assert_eq!(
+        first.candles().current().map(|candle| candle.open_time),
+        Some(1_699_999_980_000)
+    );
+    assert_eq!(
+        serde_json::to_value(first.view(10)).unwrap(),
+        serde_json::to_value(second.view(10)).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_replayed_book_tickers_keep_trades_on_the_recorded_clock() {
+    let paths = [PathBuf::from("test-bed/recorded_trades.jsonl")];
+    let orderbook = replay_into_book(&paths, "BNBUSDT", |orderbook| {
+        orderbook.last_update_id(StreamKind::BookTicker) == 2
+    })
+    .await;
+
+    // The last book ticker moves the clock to its recorded time, 70s after the first trade
+    assert_eq!(orderbook.clock(), 1_700_000_070_000);
+    let windows = orderbook.trades().window_stats(orderbook.clock());
+    assert_eq!((windows[0].trade_count, windows[1].trade_count), (1, 2));
+    assert_eq!(windows[0].sell_volume, dec("1"));
+    assert_eq!(orderbook.trades().recent().count(), 2);
+}
+
+#[test]
+fn test_replay_speed_parsing() {
+    assert_eq!(
+        "max".parse::<ReplaySpeed>().unwrap(),
+        ReplaySpeed::AsFastAsPossible
+    );
+    assert_eq!(
+        "realtime".parse::<ReplaySpeed>().unwrap(),
+        ReplaySpeed::RealTime
+    );
+    assert_eq!(
+        "10x".parse::<ReplaySpeed>().unwrap(),
+        ReplaySpeed::Multiplier(10.0)
+    );
+    assert!("-2".parse::<ReplaySpeed>().is_err());
+}
+
+#[test]
+fn test_recording_symbols_and_exit_codes() {
+    let paths = [PathBuf::from("test-bed/recorded_session.jsonl")];
+    assert_eq!(
+        recording_symbols(&paths)
+            .unwrap()
+            .into_iter()
+            .collect::<Vec<_>>(),
+        vec!["BNBUSDT".to_string()]
+    );
+
+    // Every error variant is reported with its own exit code
+    let missing = recording_symbols(&[PathBuf::from("test-bed/missing.jsonl")]).unwrap_err();
+    assert_eq!(missing.exit_code(), 10);
+    assert_eq!(OrderBookError::IncorrectJsonData.exit_code(), 18);
+    assert_eq!(
+        OrderBookError::SnapshotUnavailable(String::new()).exit_code(),
+        26
+    );
+}
+
+#[test]
+fn test_ladder_serializes_decimals_as_strings() {
+    let ladder = DepthLadder {
+        bids: vec![LadderLevel {
+            price: dec("25.35"),
+            qty: dec("10"),
+            cumulative_qty: dec("10"),
+            cumulative_notional: dec("253.5"),
+        }],
+        asks: Vec::new(),
+    };
+    assert_eq!(
+        serde_json::to_value(&ladder).unwrap(),
+        serde_json::json!({
+            "bids": [{
+                "price": "25.35",
+                "qty": "10",
+                "cumulative_qty": "10",
+                "cumulative_notional": "253.5"
+            }],
+            "asks": []
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_health_requires_diff_books_to_be_synced() {
+    let mut manager = BookManager::new(DepthMode::Diff, None);
+    manager.add_symbol("bnbusdt").unwrap();
+    // The book ticker gives both sides before the diff book has its snapshot
+    let ticker = r#"{"stream":"bnbusdt@bookTicker","data":{"u":1,"s":"BNBUSDT","b":"25.35","B":"5","a":"25.36","A":"5"}}"#;
+    manager
+        .apply(parse_stream_message(ticker).unwrap())
+        .unwrap();
+    let manager = Arc::new(RwLock::new(manager));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/health", listener.local_addr().unwrap());
+    let server = tokio::spawn(serve_api(listener, Arc::clone(&manager)));
+    let health = reqwest::get(&url).await.unwrap();
+    assert_eq!(health.status(), 503);
+    let body: serde_json::Value = health.json().await.unwrap();
+    assert_eq!(body["unhealthy"], serde_json::json!(["BNBUSDT"]));
+
+    {
+        let mut manager = manager.write().await;
+        let diff = r#"{"stream":"bnbusdt@depth@100ms","data":{"e":"depthUpdate","E":1,"s":"BNBUSDT","U":157,"u":160,"b":[["25.35","10"]],"a":[]}}"#;
+        manager.apply(parse_stream_message(diff).unwrap()).unwrap();
+        assert!(manager.request_snapshot("BNBUSDT"));
+        let snapshot = r#"{"lastUpdateId":158,"bids":[["25.35","1"]],"asks":[["25.36","2"]]}"#;
+        let Some(BinanceMessage::DepthUpdate(reader)) = parse_binance_message(snapshot) else {
+            panic!("not a depth snapshot");
+        };
+        manager.apply_snapshot("BNBUSDT", Ok(reader)).unwrap();
+    }
+    assert_eq!(reqwest::get(&url).await.unwrap().status(), 200);
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_http_api_serves_books() {
+    let mut manager = BookManager::new(DepthMode::Partial, None);
+    manager.add_symbol("bnbusdt").unwrap();
+    let source = RestSnapshotSource::new("http://127.0.0.1:1", 100);
+    let depth =
+        r#"{"lastUpdateId":1,"bids":[["99","3"],["98","1"]],"asks":[["101","1"],["102","2"]]}"#;
+    apply_binance_message(
+        manager.book_mut("BNBUSDT").unwrap(),
+        parse_binance_message(depth).unwrap(),
+        &source,
+    )
+    .await
+    .unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let server = tokio::spawn(serve_api(listener, Arc::new(RwLock::new(manager))));
+    let get = |path: &str| reqwest::get(format!("{}{}", base, path));
+
+    let health = get("/health").await.unwrap();
+    assert_eq!(health.status(), 200);
+    let symbols: serde_json::Value = get("/symbols").await.unwrap().json().await.unwrap();
+    assert_eq!(symbols, serde_json::json!(["BNBUSDT"]));
+
+    let book: serde_json::Value = get("/book/bnbusdt?depth=1")
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(book["bids"].as_array().unwrap().len(), 1);
+    assert_eq!(book["asks"][0]["price"], "101");
+    assert_eq!(book["metrics"]["mid"], "100");
+
+    let bbo: serde_json::Value = get("/bbo/BNBUSDT").await.unwrap().json().await.unwrap();
+    assert_eq!(bbo["bid"], serde_json::json!({"price": "99", "qty": "3"}));
+    let volume: serde_json::Value = get("/volume/bnbusdt?price=102")
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(volume["qty"], "2");
+
+    // Buying 2 takes 1 at 101 and 1 at 102
+    let impact: serde_json::Value = get("/impact/bnbusdt?side=buy&qty=2")
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(impact["average_price"], "101.5");
+    assert_eq!(impact["levels_consumed"], 2);
+
+    assert_eq!(get("/book/ethusdt").await.unwrap().status(), 404);
+    assert_eq!(
+        get("/impact/bnbusdt?side=hold&qty=1")
+            .await
+            .unwrap()
+            .status(),
+        400
+    );
+    assert_eq!(
+        get("/impact/bnbusdt?side=buy&qty=100000000000000000000q")
+            .await
+            .unwrap()
+            .status(),
+        400
+    );
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_fanout_pushes_sequenced_book_events() {
+    // Helper to read the next text frame as JSON
+    async fn next_json<S>(socket: &mut S) -> serde_json::Value
+    where
+        S: futures::Stream<Item = Result<Message, tungstenite::Error>> + Unpin,
+    {
+        let frame = socket.next().await.unwrap().unwrap();
+        serde_json::from_str(frame.to_text().unwrap()).unwrap()
+    }
+
+    let mut manager = BookManager::new(DepthMode::Partial, None);
+    manager.add_symbol("bnbusdt").unwrap();
+    let depth = r#"{"stream":"bnbusdt@depth20@100ms","data":{"lastUpdateId":1,"bids":[["99","3"],["98","1"]],"asks":[["101","1"],["102","2"]]}}"#;
+    manager.apply(parse_stream_message(depth).unwrap()).unwrap();
+    let manager = Arc::new(RwLock::new(manager));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let server = tokio::spawn(serve_fanout(listener, Arc::clone(&manager)));
+    let (mut socket, _) = connect_async(url).await.unwrap();
+
+    // Subscribing replies with the current state of the channel
+    let requests = [
+        r#"{"op":"subscribe","symbol":"bnbusdt","channel":"diff"}"#,
+        r#"{"op":"subscribe","symbol":"BNBUSDT","channel":"bbo"}"#,
+        r#"{"op":"subscribe","symbol":"bnbusdt","channel":"depth","depth":1}"#,
+        r#"{"op":"subscribe","symbol":"ethusdt","channel":"bbo"}"#,
+    ];
+    for request in requests {
+        socket.send(Message::text(request)).await.unwrap();
+    }
+    assert_eq!(next_json(&mut socket).await["type"], "subscribed");
+    let snapshot = next_json(&mut socket).await;
+    assert_eq!(
+        (&snapshot["type"], &snapshot["seq"]),
+        (&"snapshot".into(), &1.into())
+    );
+    assert_eq!(
+        snapshot["bids"][1],
+        serde_json::json!({"price": "98", "qty": "1"})
+    );
+    assert_eq!(next_json(&mut socket).await["channel"], "bbo");
+    let bbo = next_json(&mut socket).await;
+    assert_eq!(bbo["ask"], serde_json::json!({"price": "101", "qty": "1"}));
+    assert_eq!(next_json(&mut socket).await["type"], "subscribed");
+    let top = next_json(&mut socket).await;
+    assert_eq!(top["bids"].as_array().unwrap().len(), 1);
+    assert_eq!(next_json(&mut socket).await["type"], "error");
+
+    // The next update is pushed on every channel with the following sequence number
+    let depth = r#"{"stream":"bnbusdt@depth20@100ms","data":{"lastUpdateId":2,"bids":[["99","5"]],"asks":[["101","1"],["102","2"]]}}"#;
+    manager
+        .write()
+        .await
+        .apply(parse_stream_message(depth).unwrap())
+        .unwrap();
+    let diff = next_json(&mut socket).await;
+    assert_eq!((&diff["type"], &diff["seq"]), (&"diff".into(), &2.into()));
+    assert_eq!(
+        diff["bids"],
+        serde_json::json!([{"price": "99", "qty": "5"}, {"price": "98", "qty": "0"}])
+    );
+    assert_eq!(diff["asks"], serde_json::json!([]));
+    let bbo = next_json(&mut socket).await;
+    assert_eq!((&bbo["seq"], &bbo["bid"]["qty"]), (&2.into(), &"5".into()));
+    let top = next_json(&mut socket).await;
+    assert_eq!((&top["type"], &top["seq"]), (&"depth".into(), &2.into()));
+
+    // A change below the subscribed depth of 1 is not sent on the depth channel
+    let mut received = Vec::new();
+    for (id, bid_qty) in [(3, "5"), (4, "6")] {
+        let depth = format!(
+            r#"{{"stream":"bnbusdt@depth20@100ms","data":{{"lastUpdateId":{},"bids":[["99","{}"]],"asks":[["101","1"],["102","3"]]}}}}"#,
+            id, bid_qty
+        );
+        manager
+            .write()
+            .await
+            .apply(parse_stream_message(&depth).unwrap())
+            .unwrap();
+        // Views published in quick succession are merged, so every update is awaited
+        loop {
+            let event = next_json(&mut socket).await;
+            let done = event["type"] == "depth" || (id == 3 && event["type"] == "diff");
+            received.push((event["type"].clone(), event["seq"].clone()));
+            if done {
+                break;
+            }
+        }
+    }
+    assert_eq!(
+        received,
+        vec![
+            ("diff".into(), 3.into()),
+            ("diff".into(), 4.into()),
+            ("bbo".into(), 3.into()),
+            ("depth".into(), 4.into())
+        ]
+    );
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_metrics_endpoint_exports_feed_and_book_metrics() {
+    let mut manager = BookManager::new(DepthMode::Partial, None);
+    manager.add_symbol("ltcusdt").unwrap();
+    let depth = r#"{"stream":"ltcusdt@depth20@100ms","data":{"lastUpdateId":5,"bids":[["99","3"]],"asks":[["101.5","1"]]}}"#;
+    manager.apply(parse_stream_message(depth).unwrap()).unwrap();
+    // The same update again is rejected and counted by error variant
+    assert!(manager.apply(parse_stream_message(depth).unwrap()).is_err());
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/metrics", listener.local_addr().unwrap());
+    let server = tokio::spawn(serve_api(listener, Arc::new(RwLock::new(manager))));
+    let response = reqwest::get(url).await.unwrap();
+    assert_eq!(response.status(), 200);
+    let body = response.text().await.unwrap();
+
+    assert!(body.contains(r#"orderbook_best_bid{symbol="LTCUSDT"} 99"#));
+    assert!(body.contains(r#"orderbook_best_ask{symbol="LTCUSDT"} 101.5"#));
+    assert!(body.contains(r#"orderbook_spread{symbol="LTCUSDT"} 2.5"#));
+    assert!(body.contains(r#"orderbook_messages_applied_total{type="depth"}"#));
+    assert!(body
+        .contains(r#"orderbook_messages_rejected_total{reason="DuplicateUpdate",type="depth"}"#));
+    assert!(body.contains("orderbook_queue_depth"));
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_trades_feed_rolling_trade_stats() {
+    let mut orderbook = OrderBook::new("BNBBTC".to_string());
+    orderbook
+        .trades_mut()
+        .set_windows(vec![Duration::from_secs(60), Duration::from_secs(300)])
+        .unwrap();
+    let source = RestSnapshotSource::new("http://127.0.0.1:1", 100);
+    let lines: Vec<&str> = include_str!("../test-bed/trade_updates.json")
+        .lines()
+        .collect();
+    for line in &lines {
+        let message = parse_binance_message(line).unwrap();
+        apply_binance_message(&mut orderbook, message, &source)
+            .await
+            .unwrap();
+    }
+    assert!(matches!(
+        parse_binance_message(lines[2]),
+        Some(BinanceMessage::AggTrade(_))
+    ));
+
+    // The same trade again is rejected and not counted twice
+    let duplicate = parse_binance_message(lines[3]).unwrap();
+    assert!(matches!(
+        apply_binance_message(&mut orderbook, duplicate, &source).await,
+        Err(OrderBookError::DuplicateUpdate(_))
+    ));
+
+    // The 1m window ends at the clock of the book, the last trade, and only holds it
+    let trades = orderbook.trades();
+    let last = trades.last_trade().unwrap();
+    assert_eq!((last.price, last.aggressor), (dec("0.004"), Side::Buy));
+    assert_eq!(orderbook.clock(), last.time);
+    let minute = trades.stats(Duration::from_secs(60), orderbook.clock());
+    assert_eq!(minute.trade_count, 1);
+    assert_eq!(minute.vwap, Some(dec("0.004")));
+
+    // Buyer-maker trades are sells; the VWAP weights every trade by its quantity
+    let five_minutes = &trades.window_stats(orderbook.clock())[1];
+    assert_eq!(five_minutes.trade_count, 4);
+    assert_eq!(five_minutes.buy_volume, dec("60"));
+    assert_eq!(five_minutes.sell_volume, dec("110"));
+    assert_eq!(five_minutes.notional, dec("0.27"));
+    assert!(five_minutes
+        .vwap
+        .unwrap()
+        .to_string()
+        .starts_with("0.001588"));
+    assert_eq!(trades.recent().next().unwrap().id, 12347);
+
+    // Without new trades the windows keep moving with the clock and empty out
+    let last_time = last.time;
+    orderbook.advance_clock(last_time + 120_000);
+    let windows = orderbook.trades().window_stats(orderbook.clock());
+    assert_eq!((windows[0].trade_count, windows[1].trade_count), (0, 4));
+    assert_eq!(windows[0].last_price, Some(dec("0.004")));
+    orderbook.advance_clock(last_time + 600_000);
+    assert_eq!(orderbook.trades().recent().count(), 0);
+
+    // An empty window list would drop every trade and is rejected
+    assert!(matches!(
+        orderbook.trades_mut().set_windows(Vec::new()),
+        Err(OrderBookError::ParseError(_))
+    ));
+    assert_eq!(orderbook.trades().windows().len(), 2);
+    assert!(TradeStore::new(Vec::new()).is_err());
+
+    // The manager checks the list before touching any book or the default for new ones
+    let mut manager = BookManager::new(DepthMode::Diff, None);
+    assert!(manager.set_trade_windows(Vec::new()).is_err());
+    manager.add_symbol("BNBBTC").unwrap();
+    assert_eq!(manager.book("BNBBTC").unwrap().trades().windows().len(), 3);
+}
+
+#[test]
+fn test_trade_stream_subscriptions_follow_the_setting() {
+    let mut manager = BookManager::new(DepthMode::Diff, None);
+    manager.add_symbol("bnbusdt").unwrap();
+    let (command_tx, mut command_rx) = unbounded();
+    manager.set_command_sender(command_tx);
+
+    manager.set_trade_stream(Some(TradeStream::Trade)).unwrap();
+    manager
+        .set_trade_stream(Some(TradeStream::AggTrade))
+        .unwrap();
+    assert_eq!(
+        command_rx.try_recv().ok(),
+        Some(StreamCommand::Subscribe(vec!["bnbusdt@trade".to_string()]))
+    );
+    assert_eq!(
+        command_rx.try_recv().ok(),
+        Some(StreamCommand::Unsubscribe(
+            vec!["bnbusdt@trade".to_string()]
+        ))
+    );
+    assert_eq!(
+        command_rx.try_recv().ok(),
+        Some(StreamCommand::Subscribe(vec![
+            "bnbusdt@aggTrade".to_string()
+        ]))
+    );
+    assert_eq!(
+        manager.stream_names("ethusdt"),
+        vec![
+            "ethusdt@bookTicker",
+            "ethusdt@depth@100ms",
+            "ethusdt@aggTrade"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_kline_candles_follow_the_exchange() {
+    let mut orderbook = OrderBook::new("BNBBTC".to_string());
+    let source = RestSnapshotSource::new("http://127.0.0.1:1", 100);
+    let lines: Vec<&str> = include_str!("../test-bed/kline_updates.json")
+        .lines()
+        .collect();
+
+    // Klines are rejected while the candles are built from mid prices
+    let kline = parse_binance_message(lines[0]).unwrap();
+    assert!(matches!(
+        apply_binance_message(&mut orderbook, kline, &source).await,
+        Err(OrderBookError::CandleMismatch(_))
+    ));
+
+    orderbook
+        .candles_mut()
+        .configure(KlineInterval::OneMinute, CandleSource::Kline);
+    for line in &lines {
+        let kline = parse_binance_message(line).unwrap();
+        apply_binance_message(&mut orderbook, kline, &source)
+            .await
+            .unwrap();
+    }
+
+    // The closing kline replaced the first candle, the next interval is in progress
+    let candles = orderbook.candles();
+    let closed: Vec<_> = candles.closed().collect();
+    assert_eq!(closed.len(), 1);
+    assert_eq!(
+        (closed[0].close, closed[0].volume, closed[0].trade_count),
+        (dec("0.0022"), dec("1200"), 120)
+    );
+    assert!(closed[0].closed);
+    let current = candles.current().unwrap();
+    assert_eq!(
+        (current.open_time, current.close_time),
+        (1672515840000, 1672515899999)
+    );
+
+    // After a reconnect the candle is incomplete until the exchange resends it
+    orderbook.mark_stale();
+    assert!(!orderbook.candles().current().unwrap().complete);
+    let resent = parse_binance_message(lines[2]).unwrap();
+    apply_binance_message(&mut orderbook, resent, &source)
+        .await
+        .unwrap();
+    assert!(orderbook.candles().current().unwrap().complete);
+    assert_eq!(
+        orderbook.view(1).candle,
+        orderbook.candles().current().copied()
+    );
+
+    // A kline of another interval does not fit the candles
+    let five_minutes = lines[2].replace("1672515899999", "1672516079999");
+    let kline = parse_binance_message(&five_minutes).unwrap();
+    assert!(matches!(
+        apply_binance_message(&mut orderbook, kline, &source).await,
+        Err(OrderBookError::CandleMismatch(_))
+    ));
+}
+
+#[test]
+fn test_trade_candles_across_quiet_intervals_and_reconnects() {
+    let start = 1672515780000;
+    let trade = |id: u64, offset: u64, price: &str, qty: &str| Trade {
+        id,
+        price: dec(price),
+        qty: dec(qty),
+        time: start + offset,
+        aggressor: Side::Buy,
+    };
+    let mut orderbook = OrderBook::new("BNBBTC".to_string());
+    orderbook
+        .candles_mut()
+        .configure(KlineInterval::OneMinute, CandleSource::Trades);
+
+    orderbook.record_trade(StreamKind::Trade, trade(1, 1_000, "10", "1"));
+    orderbook.record_trade(StreamKind::Trade, trade(2, 30_000, "12", "2"));
+    // Three minutes later: the two quiet minutes become flat candles at the last close
+    orderbook.record_trade(StreamKind::Trade, trade(3, 180_005, "11", "1"));
+    // The stream drops; nothing is known about the minutes until the next trade
+    orderbook.mark_stale();
+    orderbook.record_trade(StreamKind::Trade, trade(4, 400_000, "9", "1"));
+
+    let candles = orderbook.candles();
+    let closed: Vec<_> = candles.closed().collect();
+    let open_times: Vec<_> = closed
+        .iter()
+        .map(|candle| candle.open_time - start)
+        .collect();
+    assert_eq!(open_times, vec![180_000, 120_000, 60_000, 0]);
+    let complete: Vec<_> = closed.iter().map(|candle| candle.complete).collect();
+    assert_eq!(complete, vec![false, true, true, true]);
+
+    let first = closed[3];
+    assert_eq!(
+        (first.open, first.high, first.low, first.close),
+        (dec("10"), dec("12"), dec("10"), dec("12"))
+    );
+    assert_eq!((first.volume, first.trade_count), (dec("3"), 2));
+    assert_eq!(
+        (closed[1].open, closed[1].close, closed[1].volume),
+        (dec("12"), dec("12"), Qty::ZERO)
+    );
+
+    let current = candles.current().unwrap();
+    assert_eq!(current.open_time - start, 360_000);
+    assert!(!current.complete);
+    assert_eq!(current.close, dec("9"));
+}
+
+#[test]
+fn test_kline_intervals_align_like_binance() {
+    let time = 1672515782136; // Saturday 2022-12-31 19:43:02 UTC
+    let interval = |name: &str| name.parse::<KlineInterval>().unwrap();
+    assert_eq!(interval("1m").open_time(time), 1672515780000);
+    assert_eq!(interval("4h").open_time(time), 1672502400000);
+    assert_eq!(interval("1d").open_time(time), 1672444800000);
+    // Weeks start on Monday 2022-12-26
+    assert_eq!(interval("1w").open_time(time), 1672012800000);
+    assert_eq!(interval("15m").stream_name("BNBUSDT"), "bnbusdt@kline_15m");
+    assert!("1M".parse::<KlineInterval>().is_err());
+
+    let mut manager = BookManager::new(DepthMode::Diff, None);
+    manager
+        .set_candles(interval("5m"), CandleSource::Kline)
+        .unwrap();
+    assert_eq!(
+        manager.stream_names("bnbusdt"),
+        vec![
+            "bnbusdt@bookTicker",
+            "bnbusdt@depth@100ms",
+            "bnbusdt@kline_5m"
+        ]
+    );
+}
+
+#[test]
+fn test_futures_first_depth_diff_bridges_non_contiguous_ids() {
+    let mut orderbook = OrderBook::new("BTCUSDT".to_string());
+    let snapshot = DepthUpdate::new(
+        100,
+        vec![(dec("100.0"), dec("1.0"))],
+        vec![(dec("101.0"), dec("1.0"))],
+    );
+    orderbook.apply_depth_snapshot(&snapshot).unwrap();
+
+    // Futures update IDs skip values: U is past the snapshot, but the previous event was the snapshot's
+    let first = DepthDiff::new(
+        103,
+        108,
+        Some(100),
+        vec![(dec("100.0"), dec("2.0"))],
+        vec![],
+    );
+    orderbook.apply_depth_diff(first).unwrap();
+    assert_eq!(orderbook.get_volume_at_price(dec("100.0")), dec("2.0"));
+    assert_eq!(orderbook.last_update_id(StreamKind::DiffDepth), 108);
+}
+
+#[tokio::test]
+async fn test_usd_m_futures_streams_feed_the_book() {
+    let mut manager = BookManager::new(DepthMode::Partial, None);
+    manager.set_market(Market::UsdM).unwrap();
+    manager.add_symbol("btcusdt").unwrap();
+    assert_eq!(
+        manager.stream_names("btcusdt"),
+        vec![
+            "btcusdt@bookTicker",
+            "btcusdt@depth20@100ms",
+            "btcusdt@markPrice@1s",
+            "btcusdt@forceOrder"
+        ]
+    );
+    assert!(manager.set_market(Market::Spot).is_err());
+
+    let lines: Vec<&str> = include_str!("../test-bed/futures_updates.json")
+        .lines()
+        .collect();
+    for line in &lines {
+        manager.apply(parse_stream_message(line).unwrap()).unwrap();
+    }
+
+    // The partial depth stream's `depthUpdate` payload is read as a complete picture of the top levels
+    let orderbook = manager.book("BTCUSDT").unwrap();
+    assert_eq!(orderbook.sync_state(), SyncState::AwaitingSnapshot);
+    let ((bid, _), (ask, _)) = orderbook.get_best_bid_ask().unwrap();
+    assert_eq!((bid, ask), (dec("16500.10"), dec("16500.20")));
+
+    let mark_price = orderbook.mark_price().unwrap();
+    assert_eq!(mark_price.mark_price, dec("16500.15"));
+    assert_eq!(mark_price.index_price, Some(dec("16498.92")));
+    assert_eq!(mark_price.funding_rate, Some(dec("0.0001")));
+    assert_eq!(mark_price.next_funding_time, Some(1672531200000));
+
+    let liquidation = orderbook.liquidations().next().unwrap();
+    assert_eq!(liquidation.side, Side::Sell);
+    assert_eq!(liquidation.notional(), dec("230.727"));
+
+    // Mark prices are sequenced by their event time
+    let repeated = parse_stream_message(lines[1]).unwrap();
+    assert!(matches!(
+        manager.apply(repeated),
+        Err(OrderBookError::DuplicateUpdate(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_rest_snapshot_source_uses_the_market_path() {
+    let (base_url, server) =
+        spawn_http_stand_in(include_str!("../test-bed/depth_update.json")).await;
+
+    let source = RestSnapshotSource::new(&base_url, 100).with_market(Market::CoinM);
+    source.fetch_depth_snapshot("btcusd_perp").await.unwrap();
+
+    let request = server.await.unwrap();
+    assert!(request.starts_with("GET /dapi/v1/depth?symbol=BTCUSD_PERP&limit=100 "));
+}
+
+// Helper to decode every line of a recorded feed and apply the events to a fresh book
+async fn replay_feed<F: MarketDataFeed>(feed: &mut F, symbol: &str, frames: &str) -> OrderBook {
+    let mut orderbook = OrderBook::new(symbol.to_string());
+    for frame in frames.lines() {
+        for event in feed.decode(frame).unwrap() {
+            apply_feed_event(&mut orderbook, event, &NoSnapshotSource)
+                .await
+                .unwrap();
+        }
+    }
+    orderbook
+}
+
+#[tokio::test]
+async fn test_coinbase_level2_feed_builds_the_book() {
+    let mut feed = CoinbaseFeed::new();
+    assert_eq!(feed.book_symbol("BTC-USD"), "BTCUSD");
+    let orderbook = replay_feed(
+        &mut feed,
+        "BTCUSD",
+        include_str!("../test-bed/coinbase_level2.json"),
+    )
+    .await;
+
+    // The snapshot was replaced level by level: the old best bid is gone, the best offer shrank
+    assert_eq!(orderbook.sync_state(), SyncState::Synced);
+    assert_eq!(
+        orderbook.get_best_bid_ask().unwrap(),
+        ((dec("21921.5"), dec("0.8")), (dec("21921.74"), dec("0.3")))
+    );
+    assert_eq!(orderbook.get_volume_at_price(dec("21921.73")), dec("0"));
+    assert_eq!(orderbook.bids.len() + orderbook.asks.len(), 4);
+
+    // A skipped message on the connection is reported, and forgotten after reconnecting
+    let frame = r#"{"channel":"heartbeats","timestamp":"2023-02-09T20:32:52Z","sequence_num":6,"events":[]}"#;
+    assert!(matches!(
+        feed.decode(frame),
+        Err(OrderBookError::SequenceGap(_))
+    ));
+    feed.reset();
+    assert!(feed.decode(frame).unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_kraken_book_feed_truncates_to_the_subscribed_depth() {
+    let mut feed = KrakenFeed::new(10);
+    let frames = include_str!("../test-bed/kraken_book.json");
+    let update = frames.lines().last().unwrap();
+    let event = feed.decode(update).unwrap().pop().unwrap();
+    assert_eq!(
+        (event.venue, event.symbol.as_str()),
+        (Venue::Kraken, "BTCUSD")
+    );
+    assert_eq!(event.event_time, Some(1696613755440));
+
+    let orderbook = replay_feed(&mut KrakenFeed::new(10), "BTCUSD", frames).await;
+    assert_eq!((orderbook.bids.len(), orderbook.asks.len()), (10, 10));
+    assert_eq!(
+        orderbook.get_best_bid_ask().unwrap(),
+        ((dec("26999.5"), dec("0.25")), (dec("27001"), dec("1")))
+    );
+    // The worst bid was pushed out of the top 10 by the new best bid
+    assert_eq!(orderbook.bids.keys().next(), Some(&dec("26991")));
+    assert_eq!(orderbook.asks.keys().next_back(), Some(&dec("27010")));
+
+    // Small prices and some quantities are sent in exponent notation
+    let frames = include_str!("../test-bed/kraken_exponent.json");
+    let orderbook = replay_feed(&mut KrakenFeed::new(10), "SHIBUSD", frames).await;
+    assert!(orderbook.bids.is_empty());
+    assert_eq!(
+        orderbook.asks.iter().collect::<Vec<_>>(),
+        vec![
+            (&dec("0.00000988"), &dec("2500000")),
+            (&dec("0.000015"), &dec("1000"))
+        ]
+    );
+    assert_eq!(parse_json_number("-1.25e1", "qty").unwrap(), dec("-12.5"));
+    assert!(parse_json_number("1e-9", "qty").is_err());
+    assert!(parse_json_number("1e", "qty").is_err());
+}
+
+#[tokio::test]
+async fn test_binance_feed_decodes_combined_stream_frames() {
+    let mut feed = BinanceFeed::new(Market::UsdM, DepthMode::Partial);
+    assert_eq!(feed.url(), "wss://fstream.binance.com/stream");
+    assert_eq!(
+        feed.subscribe_frames(&["btcusdt".to_string()]),
+        vec![
+            r#"{"id":1,"method":"SUBSCRIBE","params":["btcusdt@bookTicker","btcusdt@depth20@100ms"]}"#
+        ]
+    );
+
+    // Only the depth frame has a venue-neutral form; mark prices and liquidations are skipped
+    let frames = include_str!("../test-bed/futures_updates.json");
+    let events: Vec<FeedEvent> = frames
+        .lines()
+        .flat_map(|frame| feed.decode(frame).unwrap())
+        .collect();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].update.kind(), "depth");
+
+    // A book message that does not decode ends the connection, other messages are skipped
+    let broken = r#"{"stream":"btcusdt@depth20@100ms","data":{"e":"depthUpdate","b":"oops"}}"#;
+    assert!(matches!(
+        feed.decode(broken),
+        Err(OrderBookError::ParseError(_))
+    ));
+    assert!(feed.decode(r#"{"result":null,"id":1}"#).unwrap().is_empty());
+    assert!(feed
+        .decode(r#"{"stream":"btcusdt@kline_1m","data":{"e":"kline"}}"#)
+        .unwrap()
+        .is_empty());
+
+    let orderbook = replay_feed(&mut feed, "BTCUSDT", frames).await;
+    let ((bid, _), (ask, _)) = orderbook.get_best_bid_ask().unwrap();
+    assert_eq!((bid, ask), (dec("16500.10"), dec("16500.20")));
+    assert_eq!(
+        parse_rfc3339_millis("2023-02-09T20:32:51.902113508Z").unwrap(),
+        1675974771902
+    );
+}
+
+#[test]
+fn test_binance_feed_events_leave_snapshot_fetches_to_the_caller() {
+    let mut feed = BinanceFeed::new(Market::Spot, DepthMode::Diff);
+    let mut manager = BookManager::new(DepthMode::Diff, None);
+    manager.add_symbol("BNBUSDT").unwrap();
+
+    // Diff events are buffered under the manager without fetching anything
+    let frame = r#"{"stream":"bnbusdt@depth@100ms","data":{"e":"depthUpdate","E":1,"s":"BNBUSDT","U":157,"u":160,"b":[["25.35","10"]],"a":[]}}"#;
+    for event in feed.decode(frame).unwrap() {
+        manager.apply_event(event).unwrap();
+    }
+    assert_eq!(
+        manager.book("BNBUSDT").unwrap().sync_state(),
+        SyncState::AwaitingSnapshot
+    );
+    assert!(manager.request_snapshot("BNBUSDT"));
+    assert!(!manager.request_snapshot("BNBUSDT"));
+
+    // The snapshot fetched by the caller, outside the lock, syncs the book
+    let json = r#"{"lastUpdateId":158,"bids":[["25.35","1"]],"asks":[["25.36","2"]]}"#;
+    let Some(BinanceMessage::DepthUpdate(reader)) = parse_binance_message(json) else {
+        panic!("not a depth snapshot");
+    };
+    manager.apply_snapshot("BNBUSDT", Ok(reader)).unwrap();
+    let orderbook = manager.book("BNBUSDT").unwrap();
+    assert_eq!(orderbook.sync_state(), SyncState::Synced);
+    assert_eq!(orderbook.get_volume_at_price(dec("25.35")), dec("10"));
+}
+
+#[tokio::test]
+async fn test_consolidated_book_merges_sources_with_attribution() {
+    // Helper to build a synced book from bid and ask levels
+    fn source_book(symbol: &str, bids: &[(&str, &str)], asks: &[(&str, &str)]) -> OrderBook {
+        let levels = |levels: &[(&str, &str)]| -> Vec<(Price, Qty)> {
+            levels.iter().map(|(p, q)| (dec(p), dec(q))).collect()
+        };
+        let mut orderbook = OrderBook::new(symbol.to_string());
+        orderbook
+            .apply_depth_snapshot(&DepthUpdate::new(1, levels(bids), levels(asks)))
+            .unwrap();
+        orderbook
+    }
+
+    let spot = source_book(
+        "BTCUSDT",
+        &[("100.0", "1"), ("99.9", "2")],
+        &[("100.2", "1"), ("100.3", "1")],
+    );
+    let futures = source_book(
+        "BTCUSDT",
+        &[("100.0", "3"), ("99.8", "1")],
+        &[("100.1", "2"), ("100.3", "4")],
+    );
+    let mut manager = BookManager::new(DepthMode::Diff, None);
+    manager.add_consolidated("btcusdt-all").unwrap();
+
+    // A symbol is served either as a book or as a consolidated book
+    assert!(manager.add_consolidated("BTCUSDT-ALL").is_err());
+    assert!(manager.add_symbol("btcusdt-all").is_err());
+    manager.add_symbol("BTCUSDT").unwrap();
+    assert!(manager.add_consolidated("btcusdt").is_err());
+    assert!(manager.remove_symbol("BTCUSDT").is_ok());
+    manager
+        .update_consolidated("BTCUSDT-ALL", "binance:btcusdt", &spot)
+        .unwrap();
+    manager
+        .update_consolidated("BTCUSDT-ALL", "binance-usd-m:btcusdt", &futures)
+        .unwrap();
+
+    // The merged book answers like any other book
+    assert_eq!(manager.symbols(), vec!["BTCUSDT-ALL"]);
+    let merged = manager.book("btcusdt-all").unwrap();
+    assert_eq!(merged.sync_state(), SyncState::Synced);
+    assert_eq!(merged.get_volume_at_price(dec("100.3")), dec("5"));
+    assert_eq!(
+        merged.get_best_bid_ask().unwrap(),
+        ((dec("100.0"), dec("4")), (dec("100.1"), dec("2")))
+    );
+
+    // Every level lists the quantity of each source, largest first
+    let consolidated = manager.consolidated("BTCUSDT-ALL").unwrap();
+    let (best_bid, best_ask) = consolidated.best_bid_offer();
+    let best_bid = best_bid.unwrap();
+    let sources: Vec<(&str, Qty)> = best_bid
+        .sources
+        .iter()
+        .map(|source| (source.source.as_str(), source.qty))
+        .collect();
+    assert_eq!(
+        sources,
+        vec![
+            ("binance-usd-m:btcusdt", dec("3")),
+            ("binance:btcusdt", dec("1"))
+        ]
+    );
+    assert_eq!(best_ask.unwrap().sources[0].source, "binance-usd-m:btcusdt");
+    assert!(consolidated.crossed_markets().is_empty());
+
+    // A futures bid above the spot ask crosses the markets, across sources only
+    let futures = source_book("BTCUSDT", &[("100.25", "1")], &[("100.3", "4")]);
+    assert!(matches!(
+        manager.update_consolidated("BTCUSDT-ALL", "binance-usd-m:btcusdt", &futures),
+        Err(OrderBookError::CrossedBook(_))
+    ));
+    // Only the changed levels of the source were replaced
+    let merged = manager.book("BTCUSDT-ALL").unwrap();
+    assert_eq!(merged.get_volume_at_price(dec("100.3")), dec("5"));
+    assert_eq!(merged.get_volume_at_price(dec("100.1")), dec("0"));
+    assert_eq!(merged.get_volume_at_price(dec("100.0")), dec("1"));
+    let crossed = manager
+        .consolidated("BTCUSDT-ALL")
+        .unwrap()
+        .crossed_markets();
+    assert_eq!(crossed.len(), 1);
+    assert_eq!(
+        crossed[0].to_string(),
+        "binance-usd-m:btcusdt bid 100.25 >= binance:btcusdt ask 100.2"
+    );
+
+    // A stale source is left out of the merged levels and the crossing checks until it is resynchronized
+    let mut stale = spot.clone();
+    stale.mark_stale();
+    manager
+        .update_consolidated("BTCUSDT-ALL", "binance:btcusdt", &stale)
+        .unwrap();
+    let merged = manager.book("BTCUSDT-ALL").unwrap();
+    assert!(!merged.is_stale());
+    assert_eq!(
+        merged.get_best_bid_ask().unwrap(),
+        ((dec("100.25"), dec("1")), (dec("100.3"), dec("4")))
+    );
+    let view = manager.consolidated("BTCUSDT-ALL").unwrap().view(2);
+    assert!(view.stale);
+    assert_eq!(view.asks[0].sources.len(), 1);
+
+    // The consolidated view is served next to the single book endpoints
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let server = tokio::spawn(serve_api(listener, Arc::new(RwLock::new(manager))));
+    let view: serde_json::Value =
+        reqwest::get(format!("{}/consolidated/btcusdt-all?depth=2", base))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+    assert_eq!(view["stale"], true);
+    assert_eq!(view["sources"].as_array().unwrap().len(), 2);
+    assert_eq!(view["best_bid"]["price"], "100.25");
+    assert_eq!(
+        view["asks"][0]["sources"][0]["source"],
+        "binance-usd-m:btcusdt"
+    );
+    let bbo: serde_json::Value = reqwest::get(format!("{}/bbo/btcusdt-all", base))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(
+        bbo["ask"],
+        serde_json::json!({"price": "100.3", "qty": "4"})
+    );
+    server.abort();
+
+    // Without a live source the merged book is stale; a resynchronized source brings it back
+    let mut consolidated = ConsolidatedBook::new("BTCUSDT-ALL".to_string());
+    consolidated.update_source("binance:btcusdt", &stale);
+    assert!(consolidated.book().is_stale());
+    assert!(consolidated.book().get_best_bid_ask().is_none());
+    consolidated.update_source("binance:btcusdt", &spot);
+    assert!(!consolidated.book().is_stale());
+    assert_eq!(
+        consolidated.book().get_volume_at_price(dec("99.9")),
+        dec("2")
+    );
+    consolidated.remove_source("binance:btcusdt").unwrap();
+    assert!(consolidated.book().bids.is_empty() && consolidated.book().asks.is_empty());
 }
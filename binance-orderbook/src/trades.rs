@@ -0,0 +1,227 @@
+use super::*;
+
+/// Struct representing an executed trade, normalized from `trade` and `aggTrade` events
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Trade {
+    /// Trade ID, or aggregate trade ID for `aggTrade` events
+    pub id: u64,
+
+    /// Price of the trade
+    pub price: Price,
+
+    /// Base quantity of the trade
+    pub qty: Qty,
+
+    /// Trade time in milliseconds
+    pub time: u64,
+
+    /// Side of the taker (aggressor) order
+    pub aggressor: Side,
+}
+
+impl Trade {
+    /// Function to construct a Trade from a `trade` event reader (deserialized data)
+    pub fn from_reader(reader: TradeReader) -> Result<Self, OrderBookError> {
+        Ok(Self {
+            id: reader.trade_id,
+            price: parse_decimal(&reader.price, "price")?,
+            qty: parse_decimal(&reader.qty, "qty")?,
+            time: reader.trade_time,
+            aggressor: Self::aggressor(reader.buyer_is_maker),
+        })
+    }
+
+    /// Function to construct a Trade from an `aggTrade` event reader (deserialized data)
+    pub fn from_agg_reader(reader: AggTradeReader) -> Result<Self, OrderBookError> {
+        Ok(Self {
+            id: reader.agg_trade_id,
+            price: parse_decimal(&reader.price, "price")?,
+            qty: parse_decimal(&reader.qty, "qty")?,
+            time: reader.trade_time,
+            aggressor: Self::aggressor(reader.buyer_is_maker),
+        })
+    }
+
+    // Function to get the taker side: a maker buyer means the seller crossed the spread
+    fn aggressor(buyer_is_maker: bool) -> Side {
+        if buyer_is_maker {
+            Side::Sell
+        } else {
+            Side::Buy
+        }
+    }
+
+    /// Function to get the quote notional (price * quantity) of the trade
+    pub fn notional(&self) -> Decimal {
+        self.price * self.qty
+    }
+}
+
+/// Struct holding the statistics of the trades within a rolling window
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct TradeStats {
+    /// Length of the window in milliseconds
+    pub window_ms: u64,
+
+    /// Number of trades within the window
+    pub trade_count: u64,
+
+    /// Base quantity bought by aggressive buyers
+    pub buy_volume: Qty,
+
+    /// Base quantity sold by aggressive sellers
+    pub sell_volume: Qty,
+
+    /// Quote notional of every trade within the window
+    pub notional: Decimal,
+
+    /// Volume weighted average price, None without trades
+    pub vwap: Option<Price>,
+
+    /// Price of the most recent trade (which may be older than the window)
+    pub last_price: Option<Price>,
+}
+
+impl TradeStats {
+    /// Function to get the total base volume of the window
+    pub fn volume(&self) -> Qty {
+        self.buy_volume + self.sell_volume
+    }
+}
+
+/// Struct holding the recent trades of a symbol and their rolling statistics
+/// Windows end at the time passed by the caller, usually the clock of the book ([`OrderBook::clock`]), so the
+/// statistics follow the feed's clock (also when replaying) and age while no trade arrives.
+/// Trades older than the longest window are dropped.
+#[derive(Debug, Clone)]
+pub struct TradeStore {
+    // Rolling windows of the statistics
+    windows: Vec<Duration>,
+
+    // Trades within the longest window, oldest first
+    trades: VecDeque<Trade>,
+
+    // The most recent trade
+    last_trade: Option<Trade>,
+}
+
+impl Default for TradeStore {
+    fn default() -> Self {
+        Self {
+            windows: vec![
+                Duration::from_secs(60),
+                Duration::from_secs(5 * 60),
+                Duration::from_secs(15 * 60),
+            ],
+            trades: VecDeque::new(),
+            last_trade: None,
+        }
+    }
+}
+
+impl TradeStore {
+    /// Constructor function to create an empty TradeStore with the given rolling windows
+    /// An empty list is rejected: the trades are kept for the longest window, so none would ever be dropped.
+    pub fn new(windows: Vec<Duration>) -> Result<Self, OrderBookError> {
+        let mut store = Self::default();
+        store.set_windows(windows)?;
+        Ok(store)
+    }
+
+    /// Function to get the rolling windows of the statistics
+    pub fn windows(&self) -> &[Duration] {
+        &self.windows
+    }
+
+    /// Function to check a list of rolling windows, which needs at least one window
+    pub fn check_windows(windows: &[Duration]) -> Result<(), OrderBookError> {
+        if windows.is_empty() {
+            return Err(OrderBookError::ParseError(
+                "at least one trade window is required".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Function to change the rolling windows; trades already dropped are not restored
+    pub fn set_windows(&mut self, windows: Vec<Duration>) -> Result<(), OrderBookError> {
+        Self::check_windows(&windows)?;
+        self.windows = windows;
+        if let Some(last) = self.last_trade {
+            self.evict(last.time);
+        }
+        Ok(())
+    }
+
+    /// Function to add a trade and drop the trades that fell out of the longest window
+    pub fn record(&mut self, trade: Trade) {
+        if self.last_trade.is_none_or(|last| trade.time >= last.time) {
+            self.last_trade = Some(trade);
+        }
+        self.trades.push_back(trade);
+        if let Some(last) = self.last_trade {
+            self.evict(last.time);
+        }
+    }
+
+    /// Function to get the most recent trade
+    pub fn last_trade(&self) -> Option<&Trade> {
+        self.last_trade.as_ref()
+    }
+
+    /// Function to iterate over the retained trades, most recent first
+    pub fn recent(&self) -> impl Iterator<Item = &Trade> {
+        self.trades.iter().rev()
+    }
+
+    /// Function to compute the statistics of the trades within `window` before `now` (milliseconds)
+    pub fn stats(&self, window: Duration, now: u64) -> TradeStats {
+        let window_ms = window.as_millis() as u64;
+        let mut stats = TradeStats {
+            window_ms,
+            last_price: self.last_trade.map(|trade| trade.price),
+            ..TradeStats::default()
+        };
+        for trade in self
+            .trades
+            .iter()
+            .filter(|trade| trade.time + window_ms > now)
+        {
+            stats.trade_count += 1;
+            stats.notional += trade.notional();
+            match trade.aggressor {
+                Side::Buy => stats.buy_volume += trade.qty,
+                Side::Sell => stats.sell_volume += trade.qty,
+            }
+        }
+        let volume = stats.volume();
+        if volume.is_positive() {
+            stats.vwap = Some(stats.notional / volume);
+        }
+        stats
+    }
+
+    /// Function to compute the statistics of every configured window before `now` (milliseconds)
+    pub fn window_stats(&self, now: u64) -> Vec<TradeStats> {
+        self.windows
+            .iter()
+            .map(|&window| self.stats(window, now))
+            .collect()
+    }
+
+    /// Function to drop the trades older than the longest window before `now` from the front
+    /// (trades arrive in time order)
+    pub fn evict(&mut self, now: u64) {
+        let Some(longest) = self.windows.iter().max() else {
+            return;
+        };
+        let longest_ms = longest.as_millis() as u64;
+        while self
+            .trades
+            .front()
+            .is_some_and(|trade| trade.time + longest_ms <= now)
+        {
+            self.trades.pop_front();
+        }
+    }
+}
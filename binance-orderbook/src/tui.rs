@@ -0,0 +1,808 @@
+use super::*;
+use crossterm::event::{Event, EventStream, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Paragraph},
+    DefaultTerminal, Frame,
+};
+use std::{
+    collections::VecDeque,
+    sync::{Mutex, PoisonError},
+};
+
+// Number of lines kept for the log pane
+const LOG_CAPACITY: usize = 500;
+
+// Largest number of ladder levels per side
+const MAX_DEPTH: usize = 100;
+
+// Time between two redraws while no key is pressed
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+// Width of the price, quantity and cumulative quantity columns of the ladder
+const COLUMN_WIDTH: usize = 14;
+
+/// Struct holding the most recent lines shown in the log pane
+#[derive(Debug, Clone, Default)]
+pub struct LogBuffer {
+    // Log lines with their level, oldest first
+    lines: Arc<Mutex<VecDeque<(log::Level, String)>>>,
+}
+
+impl LogBuffer {
+    /// Function to append a line, dropping the oldest one once the buffer is full
+    pub fn push(&self, level: log::Level, line: impl Into<String>) {
+        let mut lines = self.lines.lock().unwrap_or_else(PoisonError::into_inner);
+        if lines.len() == LOG_CAPACITY {
+            lines.pop_front();
+        }
+        lines.push_back((level, line.into()));
+    }
+
+    // Function to get the newest `count` lines, oldest first
+    fn tail(&self, count: usize) -> Vec<(log::Level, String)> {
+        let lines = self.lines.lock().unwrap_or_else(PoisonError::into_inner);
+        lines
+            .iter()
+            .skip(lines.len().saturating_sub(count))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Struct representing a logger writing to the log pane, as output on stderr would corrupt the screen
+/// Only records of this crate are kept; the level is read from `RUST_LOG` (e.g., `debug`) and defaults to `info`
+pub struct TuiLogger {
+    // The buffer shown in the log pane
+    logs: LogBuffer,
+
+    // Most verbose level kept
+    level: log::LevelFilter,
+}
+
+impl TuiLogger {
+    /// Function to install the logger as the global logger
+    pub fn install(logs: LogBuffer) {
+        let level = std::env::var("RUST_LOG")
+            .ok()
+            .and_then(|level| level.parse().ok())
+            .unwrap_or(log::LevelFilter::Info);
+        if log::set_boxed_logger(Box::new(Self { logs, level })).is_ok() {
+            log::set_max_level(level);
+        }
+    }
+}
+
+impl log::Log for TuiLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= self.level && metadata.target().starts_with("binance_orderbook")
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            self.logs.push(record.level(), record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Function to run the full-screen terminal UI on the books until the user quits
+/// The processing task is stopped on exit; the terminal is restored even if drawing fails.
+pub async fn run_tui<S>(
+    manager: Arc<RwLock<BookManager>>, // A shared, thread-safe reference to the book manager
+    mut processor: BookProcessor<S>,   // The task applying the stream messages to the books
+    recorder: Arc<StreamRecorder>,     // The raw stream recorder, switched on or off with `r`
+    status: watch::Receiver<FeedStatus>, // The status of the message source, shown in the status bar
+    logs: LogBuffer,                     // The lines shown in the log pane
+) -> Result<(), OrderBookError>
+where
+    S: SnapshotSource + Clone + Send + Sync + 'static,
+{
+    let active = manager
+        .read()
+        .await
+        .symbols()
+        .first()
+        .cloned()
+        .unwrap_or_default();
+    let mut app = App {
+        active,
+        depth: 10,
+        input: None,
+        show_candles: false,
+        logs,
+        exit: false,
+    };
+
+    let mut terminal = ratatui::try_init()?;
+    let result = app
+        .run(&mut terminal, &manager, &mut processor, &recorder, &status)
+        .await;
+    ratatui::restore();
+
+    processor.stop().await;
+    result
+}
+
+/// Struct holding what one frame shows besides the state of the UI itself
+struct View<'a> {
+    // The book of the active symbol, if it is managed
+    orderbook: Option<&'a OrderBook>,
+
+    // All managed symbols
+    symbols: Vec<String>,
+
+    // The status of the message source
+    status: FeedStatus,
+
+    // The state of the processing task
+    processing: ProcessingState,
+
+    // Whether raw recording is switched on
+    recording: bool,
+}
+
+/// Struct holding the state of the terminal UI between frames
+struct App {
+    // Symbol shown in the ladder and stats panes
+    active: String,
+
+    // Number of ladder levels per side
+    depth: usize,
+
+    // Command line typed after `:`, None while keys are shortcuts
+    input: Option<String>,
+
+    // Whether the lower right pane shows the candles instead of the trades
+    show_candles: bool,
+
+    // Lines shown in the log pane
+    logs: LogBuffer,
+
+    // Whether the user asked to quit
+    exit: bool,
+}
+
+impl App {
+    // Function to redraw on every tick and handle keys until the user quits
+    async fn run<S>(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        manager: &Arc<RwLock<BookManager>>,
+        processor: &mut BookProcessor<S>,
+        recorder: &StreamRecorder,
+        status: &watch::Receiver<FeedStatus>,
+    ) -> Result<(), OrderBookError>
+    where
+        S: SnapshotSource + Clone + Send + Sync + 'static,
+    {
+        let mut events = EventStream::new();
+        let mut redraw = tokio::time::interval(REDRAW_INTERVAL);
+
+        while !self.exit {
+            {
+                // Processing waits only while the frame is drawn
+                let manager = manager.read().await;
+                let view = View {
+                    orderbook: manager.book(&self.active),
+                    symbols: manager.symbols(),
+                    status: status.borrow().clone(),
+                    processing: processor.state(),
+                    recording: recorder.is_enabled(),
+                };
+                terminal.draw(|frame| self.render(frame, &view))?;
+            }
+
+            tokio::select! {
+                _ = redraw.tick() => {}
+                event = events.next() => match event {
+                    Some(Ok(Event::Key(key))) if key.kind == KeyEventKind::Press => {
+                        self.handle_key(key, manager, processor, recorder).await;
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => return Err(e.into()),
+                    // The terminal has no more input
+                    None => break,
+                },
+            }
+        }
+
+        Ok(())
+    }
+
+    // Function to add a line to the log pane regardless of the log level
+    fn report(&self, level: log::Level, line: impl Into<String>) {
+        self.logs.push(level, line);
+    }
+
+    // Function to handle a key, either as a shortcut or as part of the command line
+    async fn handle_key<S>(
+        &mut self,
+        key: KeyEvent,
+        manager: &Arc<RwLock<BookManager>>,
+        processor: &mut BookProcessor<S>,
+        recorder: &StreamRecorder,
+    ) where
+        S: SnapshotSource + Clone + Send + Sync + 'static,
+    {
+        if let Some(input) = &mut self.input {
+            match key.code {
+                KeyCode::Enter => {
+                    let command = self.input.take().unwrap_or_default();
+                    self.run_command(command.trim(), manager, processor).await;
+                }
+                KeyCode::Esc => self.input = None,
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Char(c) => input.push(c),
+                _ => {}
+            }
+            return;
+        }
+
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => self.exit = true,
+            KeyCode::Char('q') | KeyCode::Esc => self.exit = true,
+            KeyCode::Tab | KeyCode::Right => self.cycle_symbol(manager, 1).await,
+            KeyCode::BackTab | KeyCode::Left => self.cycle_symbol(manager, -1).await,
+            KeyCode::Char('+') | KeyCode::Up => self.depth = (self.depth + 1).min(MAX_DEPTH),
+            KeyCode::Char('-') | KeyCode::Down => self.depth = self.depth.saturating_sub(1).max(1),
+            KeyCode::Char('p') => {
+                match processor.state() {
+                    ProcessingState::Running => processor.pause(),
+                    _ => processor.start(),
+                }
+                self.report(
+                    log::Level::Info,
+                    format!("Processing: {:?}", processor.state()),
+                );
+            }
+            KeyCode::Char('r') => {
+                recorder.set_enabled(!recorder.is_enabled());
+                let state = if recorder.is_enabled() { "on" } else { "off" };
+                self.report(log::Level::Info, format!("Raw recording: {}", state));
+            }
+            KeyCode::Char('c') => self.show_candles = !self.show_candles,
+            KeyCode::Char(':') => self.input = Some(String::new()),
+            _ => {}
+        }
+    }
+
+    // Function to make the next (or previous) managed symbol the active one
+    async fn cycle_symbol(&mut self, manager: &RwLock<BookManager>, step: isize) {
+        let symbols = manager.read().await.symbols();
+        if symbols.is_empty() {
+            return;
+        }
+        let current = symbols.iter().position(|symbol| *symbol == self.active);
+        let next = current.map_or(0, |index| {
+            (index as isize + step).rem_euclid(symbols.len() as isize) as usize
+        });
+        self.active = symbols[next].clone();
+    }
+
+    // Function to run a command typed after `:` and report the result in the log pane
+    async fn run_command<S>(
+        &mut self,
+        command: &str,
+        manager: &Arc<RwLock<BookManager>>,
+        processor: &BookProcessor<S>,
+    ) where
+        S: SnapshotSource + Clone + Send + Sync + 'static,
+    {
+        let (name, argument) = command
+            .split_once(char::is_whitespace)
+            .map_or((command, ""), |(name, argument)| (name, argument.trim()));
+
+        let result = match name.to_lowercase().as_str() {
+            "" => Ok(()),
+            // Start tracking a symbol and show it
+            "add" => manager.write().await.add_symbol(argument).map(|()| {
+                self.active = argument.to_uppercase();
+                self.report(log::Level::Info, format!("Added {}", self.active));
+            }),
+            // Stop tracking a symbol
+            "remove" => manager.write().await.remove_symbol(argument).map(|()| {
+                self.report(
+                    log::Level::Info,
+                    format!("Removed {}", argument.to_uppercase()),
+                );
+            }),
+            // Show another managed symbol
+            "symbol" => {
+                let symbol = argument.to_uppercase();
+                if manager.read().await.book(&symbol).is_some() {
+                    self.active = symbol;
+                    Ok(())
+                } else {
+                    Err(OrderBookError::UnknownSymbol(symbol))
+                }
+            }
+            // Set the number of ladder levels per side
+            "depth" => argument
+                .parse::<usize>()
+                .map(|depth| self.depth = depth.clamp(1, MAX_DEPTH))
+                .map_err(|e| OrderBookError::ParseError(format!("invalid depth: {}", e))),
+            // Show the quantity resting at a price
+            "volume" => match argument.parse::<Price>() {
+                Ok(price) => {
+                    self.with_book(manager, |orderbook| {
+                        format!(
+                            "Volume at price {}: {}",
+                            price,
+                            orderbook.get_volume_at_price(price)
+                        )
+                    })
+                    .await
+                }
+                Err(e) => Err(e),
+            },
+            // Estimate the execution of a market order for a base quantity or a quote notional (`1000q`)
+            "buy" | "sell" => {
+                match name
+                    .parse::<Side>()
+                    .and_then(|side| Ok((side, argument.parse()?)))
+                {
+                    Ok((side, size)) => {
+                        self.with_book(manager, |orderbook| {
+                            describe_execution(&orderbook.simulate_market_order(side, size))
+                        })
+                        .await
+                    }
+                    Err(e) => Err(e),
+                }
+            }
+            // Apply a Binance message to the active book
+            "json" => {
+                let mut books = manager.write().await;
+                let result = match (
+                    books.book_mut(&self.active),
+                    parse_binance_message(argument),
+                ) {
+                    (None, _) => Err(OrderBookError::UnknownSymbol(self.active.clone())),
+                    (_, None) => Err(OrderBookError::IncorrectJsonData),
+                    (Some(orderbook), Some(message)) => apply_binance_update(orderbook, message, None),
+                };
+                // A diff book waiting for a snapshot is bootstrapped without holding the lock
+                if books.request_snapshot(&self.active) {
+                    tokio::spawn(sync_shared_book(
+                        Arc::clone(manager),
+                        self.active.clone(),
+                        processor.source().clone(),
+                    ));
+                }
+                result.map(|()| self.report(log::Level::Info, "Message applied"))
+            }
+            // Show the update sequence counters of every stream of the active book
+            "stats" => self.with_book(manager, describe_sequences).await,
+            // Change the interval of the candles of every book, keeping their source
+            "candles" => match argument.parse::<KlineInterval>() {
+                Ok(interval) => {
+                    let mut manager = manager.write().await;
+                    let source = manager
+                        .book(&self.active)
+                        .map_or(CandleSource::Mid, |orderbook| orderbook.candles().source());
+                    manager.set_candles(interval, source).map(|()| {
+                        self.show_candles = true;
+                        self.report(log::Level::Info, format!("Candle interval: {}", interval));
+                    })
+                }
+                Err(e) => Err(e),
+            },
+            _ => Err(OrderBookError::ParseError(format!(
+                "unknown command {:?} (add, remove, symbol, depth, volume, buy, sell, json, stats, candles)",
+                name
+            ))),
+        };
+
+        if let Err(e) = result {
+            self.report(log::Level::Error, e.to_string());
+        }
+    }
+
+    // Function to report a description of the active book
+    async fn with_book(
+        &self,
+        manager: &RwLock<BookManager>,
+        describe: impl FnOnce(&OrderBook) -> String,
+    ) -> Result<(), OrderBookError> {
+        let manager = manager.read().await;
+        let orderbook = manager
+            .book(&self.active)
+            .ok_or_else(|| OrderBookError::UnknownSymbol(self.active.clone()))?;
+        for line in describe(orderbook).lines() {
+            self.report(log::Level::Info, line);
+        }
+        Ok(())
+    }
+
+    // Function to lay out and draw the panes
+    fn render(&self, frame: &mut Frame, view: &View) {
+        let [main, logs, status, input] = Layout::vertical([
+            Constraint::Min(8),
+            Constraint::Length(8),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [ladder, side] =
+            Layout::horizontal([Constraint::Percentage(65), Constraint::Percentage(35)])
+                .areas(main);
+        let [stats, trades] =
+            Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(side);
+
+        self.render_ladder(frame, ladder, view.orderbook);
+        self.render_stats(frame, stats, view.orderbook);
+        if self.show_candles {
+            self.render_candles(frame, trades, view.orderbook);
+        } else {
+            self.render_trades(frame, trades, view.orderbook);
+        }
+        self.render_logs(frame, logs);
+        self.render_status(frame, status, view);
+        self.render_input(frame, input);
+    }
+
+    // Function to draw the ladder: asks above, bids below the spread, each level with a size bar
+    fn render_ladder(&self, frame: &mut Frame, area: Rect, orderbook: Option<&OrderBook>) {
+        let block = Block::bordered().title(format!(" {} · depth {} ", self.active, self.depth));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let Some(orderbook) = orderbook else {
+            frame.render_widget(Paragraph::new("No order book"), inner);
+            return;
+        };
+        let ladder = orderbook.top_levels(self.depth);
+        if ladder.bids.is_empty() && ladder.asks.is_empty() {
+            frame.render_widget(Paragraph::new("Orderbook is empty."), inner);
+            return;
+        }
+
+        let [header, body] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(inner);
+        frame.render_widget(
+            Paragraph::new(format!(
+                "{:>w$} {:>w$} {:>w$}",
+                "Price",
+                "Qty",
+                "Cum. Qty",
+                w = COLUMN_WIDTH
+            ))
+            .style(Style::new().add_modifier(Modifier::BOLD)),
+            header,
+        );
+
+        // Bars are scaled to the largest level shown
+        let max_qty = ladder
+            .bids
+            .iter()
+            .chain(&ladder.asks)
+            .map(|level| level.qty.to_f64())
+            .fold(0.0, f64::max);
+        let bar_width = (body.width as usize).saturating_sub(3 * (COLUMN_WIDTH + 1));
+        let row = |level: &LadderLevel, color: Color| {
+            let bar = if max_qty > 0.0 {
+                ((level.qty.to_f64() / max_qty * bar_width as f64).round() as usize).max(1)
+            } else {
+                0
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!(
+                        "{:>w$} {:>w$} {:>w$} ",
+                        level.price.to_string(),
+                        level.qty.to_string(),
+                        level.cumulative_qty.to_string(),
+                        w = COLUMN_WIDTH
+                    ),
+                    Style::new().fg(color),
+                ),
+                Span::styled("█".repeat(bar.min(bar_width)), Style::new().fg(color)),
+            ])
+        };
+
+        let mut lines: Vec<Line> = ladder
+            .asks
+            .iter()
+            .rev()
+            .map(|level| row(level, Color::Red))
+            .collect();
+        let spread = orderbook
+            .spread()
+            .map_or_else(|| "n/a".to_string(), |spread| spread.to_string());
+        lines.push(Line::styled(
+            format!("{:>w$} spread {}", "", spread, w = COLUMN_WIDTH),
+            Style::new().fg(Color::DarkGray),
+        ));
+        lines.extend(ladder.bids.iter().map(|level| row(level, Color::Green)));
+
+        // Keep the spread in the middle when not all levels fit
+        let height = body.height as usize;
+        let offset = ladder
+            .asks
+            .len()
+            .saturating_sub(height / 2)
+            .min(lines.len().saturating_sub(height));
+        frame.render_widget(Paragraph::new(lines).scroll((offset as u16, 0)), body);
+    }
+
+    // Function to draw the microstructure metrics and the sync state of the active book
+    fn render_stats(&self, frame: &mut Frame, area: Rect, orderbook: Option<&OrderBook>) {
+        let block = Block::bordered().title(" Stats ");
+        let Some(orderbook) = orderbook else {
+            frame.render_widget(Paragraph::new("").block(block), area);
+            return;
+        };
+
+        let mut lines = match orderbook.metrics() {
+            Some(metrics) => {
+                let imbalance = metrics.imbalance.unwrap_or_default();
+                let imbalance_color = if imbalance >= 0.0 {
+                    Color::Green
+                } else {
+                    Color::Red
+                };
+                let liquidity = &metrics.liquidity;
+                vec![
+                    Line::from(format!("Mid:        {}", metrics.mid)),
+                    Line::from(format!(
+                        "Spread:     {} ({:.2} bps)",
+                        metrics.spread, metrics.spread_bps
+                    )),
+                    Line::from(format!("Microprice: {}", metrics.microprice)),
+                    Line::from(vec![
+                        Span::raw("Imbalance:  "),
+                        Span::styled(
+                            format!("{:+.4} ", imbalance),
+                            Style::new().fg(imbalance_color),
+                        ),
+                        Span::styled(
+                            "█".repeat((imbalance.abs() * 10.0).round() as usize),
+                            Style::new().fg(imbalance_color),
+                        ),
+                    ]),
+                    Line::from(format!(
+                        "Bids near:  {} ({})",
+                        liquidity.bid_qty, liquidity.bid_notional
+                    )),
+                    Line::from(format!(
+                        "Asks near:  {} ({})",
+                        liquidity.ask_qty, liquidity.ask_notional
+                    )),
+                ]
+            }
+            None => vec![Line::from("Orderbook is empty.")],
+        };
+        if let Some(mark_price) = orderbook.mark_price() {
+            lines.push(Line::from(describe_mark_price(mark_price)));
+        }
+        if let Some(liquidation) = orderbook.liquidations().next() {
+            lines.push(Line::styled(
+                format!("Liquidation: {}", describe_liquidation(liquidation)),
+                Style::new().fg(Color::Yellow),
+            ));
+        }
+
+        lines.push(Line::from(""));
+        lines.push(Line::from(format!(
+            "Sync:       {:?}",
+            orderbook.sync_state()
+        )));
+        if orderbook.is_stale() {
+            lines.push(Line::styled(
+                "Stale, waiting for resync",
+                Style::new().fg(Color::Yellow),
+            ));
+        }
+        for stream in StreamKind::ALL {
+            let stats = orderbook.sequence_stats(stream);
+            if stats.in_order > 0 || stats.gaps > 0 {
+                lines.push(Line::from(format!(
+                    "{:?}: {} updates, {} gaps",
+                    stream, stats.in_order, stats.gaps
+                )));
+            }
+        }
+
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    // Function to draw the rolling trade statistics and the most recent trades of the active book
+    fn render_trades(&self, frame: &mut Frame, area: Rect, orderbook: Option<&OrderBook>) {
+        let block = Block::bordered().title(" Trades ");
+        let Some(orderbook) = orderbook else {
+            frame.render_widget(Paragraph::new("").block(block), area);
+            return;
+        };
+        let trades = orderbook.trades();
+        if trades.last_trade().is_none() {
+            let hint = "No trades (start with --trades trade or agg-trade)";
+            frame.render_widget(Paragraph::new(hint).block(block), area);
+            return;
+        }
+
+        let mut lines: Vec<Line> = trades
+            .window_stats(orderbook.clock())
+            .iter()
+            .map(|stats| Line::from(describe_trade_stats(stats)))
+            .collect();
+        lines.push(Line::from(""));
+        let visible = (area.height as usize).saturating_sub(2 + lines.len());
+        for trade in trades.recent().take(visible) {
+            let color = match trade.aggressor {
+                Side::Buy => Color::Green,
+                Side::Sell => Color::Red,
+            };
+            lines.push(Line::styled(
+                format!(
+                    "{} {:>w$} {:>w$}",
+                    time_of_day(trade.time),
+                    trade.price.to_string(),
+                    trade.qty.to_string(),
+                    w = COLUMN_WIDTH
+                ),
+                Style::new().fg(color),
+            ));
+        }
+
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    // Function to draw the candle in progress and the most recent closed candles of the active book
+    fn render_candles(&self, frame: &mut Frame, area: Rect, orderbook: Option<&OrderBook>) {
+        let Some(candles) = orderbook.map(OrderBook::candles) else {
+            frame.render_widget(Paragraph::new("").block(Block::bordered()), area);
+            return;
+        };
+        let block = Block::bordered().title(format!(
+            " Candles {} {:?} ",
+            candles.interval(),
+            candles.source()
+        ));
+        let Some(current) = candles.current() else {
+            frame.render_widget(Paragraph::new("No candles yet").block(block), area);
+            return;
+        };
+
+        let visible = (area.height as usize).saturating_sub(2);
+        let lines: Vec<Line> = std::iter::once(current)
+            .chain(candles.closed())
+            .take(visible)
+            .map(|candle| {
+                let color = if candle.close >= candle.open {
+                    Color::Green
+                } else {
+                    Color::Red
+                };
+                let style = Style::new().fg(color);
+                // The candle in progress is highlighted
+                let style = if candle.closed {
+                    style
+                } else {
+                    style.add_modifier(Modifier::BOLD)
+                };
+                Line::styled(describe_candle(candle), style)
+            })
+            .collect();
+
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    // Function to draw the newest log lines
+    fn render_logs(&self, frame: &mut Frame, area: Rect) {
+        let lines: Vec<Line> = self
+            .logs
+            .tail(area.height.saturating_sub(2) as usize)
+            .into_iter()
+            .map(|(level, line)| {
+                let color = match level {
+                    log::Level::Error => Color::Red,
+                    log::Level::Warn => Color::Yellow,
+                    log::Level::Info => Color::Reset,
+                    log::Level::Debug | log::Level::Trace => Color::DarkGray,
+                };
+                Line::styled(line, Style::new().fg(color))
+            })
+            .collect();
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(" Log ")),
+            area,
+        );
+    }
+
+    // Function to draw the status bar: feed status, processing, recording and the symbols
+    fn render_status(&self, frame: &mut Frame, area: Rect, view: &View) {
+        let status_color = if view.status.is_healthy() {
+            Color::Green
+        } else {
+            Color::Yellow
+        };
+        let mut spans = vec![
+            Span::styled(
+                format!(" {} ", view.status.describe()),
+                Style::new().fg(Color::Black).bg(status_color),
+            ),
+            Span::raw(format!(
+                " Processing: {:?} │ Recording: {} │",
+                view.processing,
+                if view.recording { "on" } else { "off" }
+            )),
+        ];
+        for symbol in &view.symbols {
+            let style = if *symbol == self.active {
+                Style::new().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::new()
+            };
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled(symbol.clone(), style));
+        }
+        frame.render_widget(Paragraph::new(Line::from(spans)), area);
+    }
+
+    // Function to draw the command line, or the key bindings while no command is typed
+    fn render_input(&self, frame: &mut Frame, area: Rect) {
+        let line = match &self.input {
+            Some(input) => Line::from(format!(":{}█", input)),
+            None => Line::styled(
+                "q quit · ←/→ symbol · +/- depth · p pause/resume · r record · c trades/candles · : command (add, remove, symbol, depth, volume, buy, sell, json, stats, candles)",
+                Style::new().fg(Color::DarkGray),
+            ),
+        };
+        frame.render_widget(Paragraph::new(line), area);
+    }
+}
+
+/// Function to describe the estimated execution of a market order
+fn describe_execution(estimate: &ExecutionEstimate) -> String {
+    let Some(average_price) = estimate.average_price else {
+        return "Orderbook is empty.".to_string();
+    };
+
+    let mut description = format!(
+        "{:?} {} for {}: average price {}, worst price {}, levels consumed {}",
+        estimate.side,
+        estimate.filled_qty,
+        estimate.notional,
+        average_price,
+        estimate.worst_price.unwrap_or_default(),
+        estimate.levels_consumed
+    );
+    if let Some(slippage_bps) = estimate.slippage_bps {
+        description += &format!(", slippage vs mid {:.2} bps", slippage_bps);
+    }
+    // Warn if the visible book is too thin for the order
+    if !estimate.is_fully_filled() {
+        let unfilled = match estimate.unfilled {
+            OrderSize::Base(qty) => format!("{} base", qty),
+            OrderSize::Quote(notional) => format!("{} quote", notional),
+        };
+        description += &format!("\nUnfilled: {}", unfilled);
+    }
+    description
+}
+
+/// Function to describe the last update ID and the sequence outcome counters of every stream
+fn describe_sequences(orderbook: &OrderBook) -> String {
+    StreamKind::ALL
+        .into_iter()
+        .map(|stream| {
+            let stats = orderbook.sequence_stats(stream);
+            format!(
+                "{:?}: last update ID: {}, in order: {}, duplicates: {}, outdated: {}, gaps: {}",
+                stream,
+                orderbook.last_update_id(stream),
+                stats.in_order,
+                stats.duplicates,
+                stats.outdated,
+                stats.gaps
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}